@@ -10,33 +10,90 @@ use ark_ec::{
     short_weierstrass::Projective,
     CurveGroup
 };
+use ark_std::Zero;
 use ark_ff::{UniformRand, field_hashers::DefaultFieldHasher};
 use num_bigint::{BigUint, BigInt};
 use super::eqs::BG;
 use sha2::Sha256;
+use rand::thread_rng;
+use std::fmt;
+use std::sync::Arc;
+
+type G2Hasher = MapToCurveBasedHasher<
+    Projective<G2Config>,
+    DefaultFieldHasher<Sha256, 128>,
+    WBMap<G2Config>
+>;
+
+//Hash to curve function of BLS. It is built once from the Domain Separation Tag and shared between the
+//copies of BG.
+#[derive(Clone)]
+pub struct H2(Arc<G2Hasher>);
+
+impl H2 {
+    pub fn new(dst: &String) -> H2 {
+        H2(Arc::new(G2Hasher::new(dst.as_bytes()).unwrap()))
+    }
+}
+
+impl fmt::Debug for H2 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "H2")
+    }
+}
 
 pub struct BLS;
 
 impl BLS {
-    pub fn k_gen(bg: &mut BG) -> (Fr, Fr, G1Projective, G1Projective) {
-        let sk1 = Fr::rand(&mut bg.rng);
-        let sk2 = Fr::rand(&mut bg.rng);
+    pub fn k_gen(bg: &BG) -> (Fr, Fr, G1Projective, G1Projective) {
+        let mut rng = thread_rng();
+        let sk1 = Fr::rand(&mut rng);
+        let sk2 = Fr::rand(&mut rng);
         let pk1 = bg.p1 * sk1;
         let pk2 = pk1 * sk2;
         return (sk1, sk2, pk1, pk2);
     }
 
-    pub fn sign(bg: &mut BG, sk2: &Fr, m: &String) -> G2Projective {
+    pub fn sign(bg: &BG, sk2: &Fr, m: &String) -> G2Projective {
         let h = Self::hash_g2(bg, &m);
         return h * sk2;
     }
 
-    pub fn verify(bg: &mut BG, pk1: &G1Projective, pk2: &G1Projective, m: &String, s: &G2Projective) -> bool {
-        if pk2.into_affine() == bg.p1 {
+    pub fn verify(bg: &BG, pk1: &G1Projective, pk2: &G1Projective, m: &String, s: &G2Projective) -> bool {
+        if pk2.into_affine() == bg.p1 || Self::has_identity(pk1, pk2, s) {
             return false;
         }
         let h = Self::hash_g2(bg, &m);
-        return Bls12_381::pairing(pk1, s) == Bls12_381::pairing(pk2, h);
+        Bls12_381::multi_pairing([*pk1, -*pk2], [*s, h]).is_zero()
+    }
+
+    //Checks several signatures at once: prod e(d_j*pk1_j, s_j) * e(-d_j*pk2_j, H(m_j)) = 1 for random d_j,
+    //which costs a single final exponentiation.
+    pub fn verify_batch(bg: &BG, pk1s: &[G1Projective], pk2s: &[G1Projective], ms: &[String], 
+                        ss: &[G2Projective]) -> bool {
+        let len = ss.len();
+        if pk1s.len() != len || pk2s.len() != len || ms.len() != len {
+            return false;
+        }
+        let mut rng = thread_rng();
+        let mut a: Vec<G1Projective> = Vec::with_capacity(2 * len);
+        let mut b: Vec<G2Projective> = Vec::with_capacity(2 * len);
+        for j in 0..len {
+            if pk2s[j].into_affine() == bg.p1 || Self::has_identity(&pk1s[j], &pk2s[j], &ss[j]) {
+                return false;
+            }
+            let d = Fr::rand(&mut rng);
+            a.push(pk1s[j] * d);
+            b.push(ss[j]);
+            a.push(-(pk2s[j] * d));
+            b.push(Self::hash_g2(bg, &ms[j]));
+        }
+        Bls12_381::multi_pairing(a, b).is_zero()
+    }
+
+    //With an identity public key or signature, the verification equation holds for every message.
+    fn has_identity(pk1: &G1Projective, pk2: &G1Projective, s: &G2Projective) -> bool {
+        pk1.is_zero() || pk2.is_zero() || s.is_zero()
     }
 
     pub fn _randomize_g1(s: &G1Projective, r: &BigInt) -> G1Projective {
@@ -49,14 +106,8 @@ impl BLS {
         return *s * r_;
     }
 
-    fn hash_g2(bg: &mut BG, m: &String) -> G2Projective {
-        let mtc = MapToCurveBasedHasher::<
-            Projective<G2Config>,
-            DefaultFieldHasher<Sha256, 128>,
-            WBMap<G2Config>
-        >::new(&bg.dst.as_bytes())
-        .unwrap();
-        let h = mtc.hash(m.as_bytes());
+    fn hash_g2(bg: &BG, m: &String) -> G2Projective {
+        let h = bg.h2.0.hash(m.as_bytes());
         return h.unwrap().mul_by_cofactor_to_group();
     }
 }
\ No newline at end of file
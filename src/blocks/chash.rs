@@ -1,6 +1,10 @@
 use num_bigint::{BigInt, RandBigInt, Sign};
+use num_integer::Integer;
 use glass_pumpkin::safe_prime;
 use rand::thread_rng;
+use sha2::{Sha256, Digest};
+
+use super::fixed_base::FixedBase;
 
 pub struct CHash;
 
@@ -23,31 +27,164 @@ impl CHash {
         }
     }
 
-    pub fn k_gen(p: &BigInt, q: &BigInt, g: &BigInt) -> (BigInt, BigInt) {
+    pub fn k_gen(p: &BigInt, q: &BigInt, g: &BigInt, fb: Option<&FixedBase>) -> (BigInt, BigInt) {
         let mut rng = thread_rng();
         let min = BigInt::from(1);
         let sk = rng.gen_bigint_range(&min, &q);
-        let pk = g.modpow(&sk, &p);
+        let pk = FixedBase::pow_or(fb, g, &sk, p);
         return (sk, pk);
     }
 
-    pub fn hash(p: &BigInt, q: &BigInt, g: &BigInt, pk: &BigInt, m: &BigInt) -> (BigInt, BigInt) {
+    //Key pair whose trapdoor is derived from a seed, so that the trapdoor can be transmitted as the seed.
+    pub fn k_gen_from_seed(p: &BigInt, q: &BigInt, g: &BigInt, seed: &[u8], fb: Option<&FixedBase>) -> (BigInt, BigInt) {
+        let sk = Self::trapdoor_from_seed(q, seed);
+        let pk = FixedBase::pow_or(fb, g, &sk, p);
+        (sk, pk)
+    }
+
+    //SHA-256 in counter mode, reduced into [1, q-1].
+    pub fn trapdoor_from_seed(q: &BigInt, seed: &[u8]) -> BigInt {
+        let len = (q.bits() as usize).div_ceil(8) + 16;
+        let mut bytes: Vec<u8> = Vec::with_capacity(len + 32);
+        let mut c = 0u32;
+        while bytes.len() < len {
+            bytes.extend_from_slice(&Sha256::new().chain_update(seed).chain_update(c.to_be_bytes()).finalize());
+            c += 1;
+        }
+        BigInt::from_bytes_be(Sign::Plus, &bytes[..len]) % (q - 1u8) + 1u8
+    }
+
+    pub fn hash(p: &BigInt, q: &BigInt, g: &BigInt, pk: &BigInt, m: &BigInt, fb: Option<&FixedBase>) -> (BigInt, BigInt) {
+        let (r, pkr) = Self::hash_offline(p, q, pk);
+        Self::hash_online(p, g, m, &r, &pkr, fb)
+    }
+
+    //Message independent part of hash: the randomness r and pk^r.
+    pub fn hash_offline(p: &BigInt, q: &BigInt, pk: &BigInt) -> (BigInt, BigInt) {
         let mut rng = thread_rng();
         let min = BigInt::from(1);
         let r = rng.gen_bigint_range(&min, &q);
-        let h = (g.modpow( &m, &p) * pk.modpow( &r, &p)) % p;
-        return (h, r);
+        let pkr = pk.modpow(&r, p);
+        (r, pkr)
+    }
+
+    pub fn hash_online(p: &BigInt, g: &BigInt, m: &BigInt, r: &BigInt, pkr: &BigInt, fb: Option<&FixedBase>) -> (BigInt, BigInt) {
+        let h = (FixedBase::pow_or(fb, g, m, p) * pkr) % p;
+        (h, r.clone())
     }
 
-    pub fn check(p: &BigInt, _q: &BigInt, g: &BigInt, pk: &BigInt, m: &BigInt, r: &BigInt, h: &BigInt) -> bool {
-        let hp = (g.modpow( &m, &p) * pk.modpow( &r, &p)) % p;
+    pub fn check(p: &BigInt, g: &BigInt, pk: &BigInt, m: &BigInt, r: &BigInt, h: &BigInt, fb: Option<&FixedBase>) -> bool {
+        let hp = (FixedBase::pow_or(fb, g, m, p) * pk.modpow( r, p)) % p;
         return hp == *h;
     }
 
+    //Checks several hashes at once with random 128-bit weights a_i:
+    //g^(sum a_i*m_i) * prod pk_i^(a_i*r_i) = prod h_i^a_i mod p.
+    //The exponents are reduced mod q, so every pk_i and h_i must be in the subgroup of quadratic residues.
+    pub fn check_batch(p: &BigInt, q: &BigInt, g: &BigInt, pks: &[BigInt], ms: &[BigInt], 
+                        rs: &[BigInt], hs: &[BigInt]) -> bool {
+        let len = hs.len();
+        if pks.len() != len || ms.len() != len || rs.len() != len {
+            return false;
+        }
+        let mut rng = thread_rng();
+        let one = BigInt::from(1);
+        let min = BigInt::from(1);
+        let max = BigInt::from(2).pow(128);
+        let mut em = BigInt::from(0);
+        let mut lhs = BigInt::from(1);
+        let mut rhs = BigInt::from(1);
+        for i in 0..len {
+            if hs[i] >= *p || pks[i] >= *p || !Self::is_qr(p, &pks[i]) || !Self::is_qr(p, &hs[i]) {
+                return false;
+            }
+            let a = if i == 0 { one.clone() } else { rng.gen_bigint_range(&min, &max) };
+            em = (em + &a * &ms[i]) % q;
+            lhs = (lhs * pks[i].modpow(&((&a * &rs[i]) % q), p)) % p;
+            rhs = (rhs * hs[i].modpow(&a, p)) % p;
+        }
+        lhs = (lhs * g.modpow(&em, p)) % p;
+        lhs == rhs
+    }
+
     pub fn adapt(_p: &BigInt, q: &BigInt, _g: &BigInt, sk: &BigInt, m: &BigInt, r: &BigInt, mp: &BigInt) -> BigInt {
         let lhs = (((m - mp + q) % q) + ((sk * r) % q)) % q;
         let sk_inv = &sk.modinv(&q).unwrap();
         let rp = (lhs * sk_inv) % q;
         return rp;
     }
+
+    //Key-exposure-free chameleon hash (Ateniese and de Medeiros): h = r - (pk^e * g^s mod p) mod q with e = H(m, r),
+    //for a randomness (r, s) in Z_q^2. A collision is a Schnorr signature (e, s) on the new message with a fresh
+    //nonce, so that, unlike with adapt, two collisions do not reveal sk.
+    pub fn hash_kef(p: &BigInt, q: &BigInt, g: &BigInt, pk: &BigInt, m: &BigInt) -> (BigInt, BigInt, BigInt) {
+        let mut rng = thread_rng();
+        let zero = BigInt::from(0);
+        let r = rng.gen_bigint_range(&zero, q);
+        let s = rng.gen_bigint_range(&zero, q);
+        let e = Self::challenge(q, m, &r);
+        let h = (&r - (pk.modpow(&e, p) * g.modpow(&s, p) % p)).mod_floor(q);
+        (h, r, s)
+    }
+
+    pub fn check_kef(p: &BigInt, q: &BigInt, g: &BigInt, pk: &BigInt, m: &BigInt, rs: (&BigInt, &BigInt), 
+                        h: &BigInt) -> bool {
+        let (r, s) = rs;
+        let zero = BigInt::from(0);
+        if [r, s, h].iter().any(|x| **x < zero || **x >= *q) {
+            return false;
+        }
+        let e = Self::challenge(q, m, r);
+        (r - (pk.modpow(&e, p) * g.modpow(s, p) % p)).mod_floor(q) == *h
+    }
+
+    //New randomness r' of a collision of hash_kef to mp for the nonce commitment c = g^k mod p, and its challenge
+    //e' = H(mp, r'): r' = h + (c mod q) mod q and the collision is (r', k - e' * sk mod q), which holders of shares of
+    //sk can compute as a threshold Schnorr signature.
+    pub fn collision_kef(q: &BigInt, h: &BigInt, mp: &BigInt, c: &BigInt) -> (BigInt, BigInt) {
+        let rp = (h + c % q) % q;
+        let e = Self::challenge(q, mp, &rp);
+        (rp, e)
+    }
+
+    pub fn adapt_kef(p: &BigInt, q: &BigInt, g: &BigInt, sk: &BigInt, h: &BigInt, mp: &BigInt) -> (BigInt, BigInt) {
+        let mut rng = thread_rng();
+        let min = BigInt::from(1);
+        let k = rng.gen_bigint_range(&min, q);
+        let (rp, e) = Self::collision_kef(q, h, mp, &g.modpow(&k, p));
+        let sp = (k - e * sk).mod_floor(q);
+        (rp, sp)
+    }
+
+    //Challenge e = H(m, r) of hash_kef, in [1, q-1].
+    fn challenge(q: &BigInt, m: &BigInt, r: &BigInt) -> BigInt {
+        let t = m.to_str_radix(36) + ":" + r.to_str_radix(36).as_str();
+        Self::trapdoor_from_seed(q, t.as_bytes())
+    }
+
+    //Jacobi symbol test: x is in the order q subgroup of Z*_p iff it is a non-zero quadratic residue.
+    fn is_qr(p: &BigInt, x: &BigInt) -> bool {
+        let zero = BigInt::from(0);
+        let mut a = x.mod_floor(p);
+        let mut n = p.clone();
+        if a == zero {
+            return false;
+        }
+        let mut t = 1;
+        while a != zero {
+            while a.is_even() {
+                a /= 2;
+                let r = &n % 8u8;
+                if r == BigInt::from(3) || r == BigInt::from(5) {
+                    t = -t;
+                }
+            }
+            std::mem::swap(&mut a, &mut n);
+            if (&a % 4u8) == BigInt::from(3) && (&n % 4u8) == BigInt::from(3) {
+                t = -t;
+            }
+            a %= &n;
+        }
+        n == BigInt::from(1) && t == 1
+    }
 }
\ No newline at end of file
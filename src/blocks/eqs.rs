@@ -1,21 +1,59 @@
 use delegatable_credentials::mercurial_sig::*;
 use num_bigint::{BigUint, BigInt};
 use delegatable_credentials::util::generator_pair;
-use ark_bls12_381::{Bls12_381, Fr, G1Affine, G2Affine};
+use ark_bls12_381::{Bls12_381, Fr, G1Affine, G1Projective, G2Affine};
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
+use ark_std::Zero;
+use ark_ff::UniformRand;
 use ark_std::rand::{rngs::StdRng, SeedableRng};
 use rand::rngs::OsRng;
-use rand::RngCore;
+use rand::{thread_rng, RngCore};
+use ark_serialize::CanonicalSerialize;
+use std::fmt::Debug;
+use super::bls::H2;
 
+//Pairing group of BLS and EQS. It holds no randomness (the algorithms draw from thread_rng), so it can be shared
+//between threads.
 #[derive(Clone, Debug)]
 pub struct BG {
-    pub rng: StdRng,
     pub p1: G1Affine,
     pub p2: G2Affine,
+    pub p2_prep: <Bls12_381 as Pairing>::G2Prepared,
     pub n: u32,
     //Domain Separation Tag
-    pub dst: String
+    pub dst: String,
+    pub h2: H2
 }
 
+//Equivalence class signature on vectors of G1 elements, as used by IUT. A backend may keep its own parameters
+//(Params, built by setup from the pairing group bg), the vector length comes from bg. verify and verify_batch
+//check m against the first m.len() elements of pk, since IUT signs vectors shorter than bg.n.
+pub trait EQSScheme {
+    type Params: Clone + Debug + Send + Sync;
+    type SecretKey: Clone + Debug + Send + Sync;
+    type PublicKey: Clone + Debug + Send + Sync;
+    type Signature: Clone + Debug + Send + Sync;
+
+    fn setup(bg: &BG) -> Self::Params;
+    fn k_gen(bg: &BG, pp: &Self::Params) -> (Self::SecretKey, Self::PublicKey);
+    fn sign(bg: &BG, pp: &Self::Params, sk: &Self::SecretKey, m: &[G1Affine]) -> Self::Signature;
+    fn verify(bg: &BG, pp: &Self::Params, pk: &Self::PublicKey, m: &[G1Affine], s: &Self::Signature) -> bool;
+    fn chg_rep(bg: &BG, pp: &Self::Params, pk: &Self::PublicKey, 
+                m: &[G1Affine], s: &Self::Signature, r: &BigInt
+    ) -> (Self::Signature, Vec<G1Affine>);
+
+    fn verify_batch(bg: &BG, pp: &Self::Params, pk: &Self::PublicKey, ms: &[Vec<G1Affine>], ss: &[Self::Signature]) -> bool {
+        ms.len() == ss.len() && (0..ss.len()).all(|i| Self::verify(bg, pp, pk, &ms[i], &ss[i]))
+    }
+
+    //Encodings hashed into the IUT signatures, and encoded sizes.
+    fn pk_bytes(pk: &Self::PublicKey, bytes: &mut Vec<u8>);
+    fn sig_bytes(s: &Self::Signature, bytes: &mut Vec<u8>);
+    fn pk_size(pk: &Self::PublicKey) -> usize;
+    fn sig_size(s: &Self::Signature) -> usize;
+}
+
+#[derive(Clone, Debug)]
 pub struct EQS;
 
 impl EQS {
@@ -23,35 +61,148 @@ impl EQS {
         let mut rng = StdRng::seed_from_u64(OsRng.next_u64());
         let (p1, p2) = generator_pair::<Bls12_381, StdRng>(&mut rng);
         let bg = BG {
-            rng: rng,
             p1: p1,
             p2: p2,
+            p2_prep: p2.into(),
             n: n,
-            dst: dst.clone()
+            dst: dst.clone(),
+            h2: H2::new(dst)
         }; 
         return bg;
     }
 
-    pub fn k_gen(bg: &mut BG) -> (SecretKey<Bls12_381>, PreparedPublicKey<Bls12_381>) {
-        let sk = SecretKey::new(&mut bg.rng, bg.n).unwrap();
+    pub fn k_gen(bg: &BG) -> (SecretKey<Bls12_381>, PreparedPublicKey<Bls12_381>) {
+        let sk = SecretKey::new(&mut thread_rng(), bg.n).unwrap();
         let pk = PublicKey::<Bls12_381>::new(&sk, &bg.p2);
         let prep_pk = PreparedPublicKey::from(pk.clone());
         return (sk, prep_pk);
     }
 
-    pub fn sign(bg: &mut BG, sk: &SecretKey<Bls12_381>, m: &Vec<G1Affine>) -> Signature<Bls12_381> {
-        return Signature::new(&mut bg.rng, &m, &sk, &bg.p1, &bg.p2).unwrap();
+    pub fn sign(bg: &BG, sk: &SecretKey<Bls12_381>, m: &[G1Affine]) -> Signature<Bls12_381> {
+        Signature::new(&mut thread_rng(), m, sk, &bg.p1, &bg.p2).unwrap()
+    }
+
+    pub fn verify(bg: &BG, pk: &PreparedPublicKey<Bls12_381>, m: &[G1Affine], s: &Signature<Bls12_381>) -> bool {
+        m.len() == pk.0.len() && Self::verify_prefix(bg, pk, m, s)
     }
 
-    pub fn verify(bg: &mut BG, pk: &PreparedPublicKey<Bls12_381>, m: &Vec<G1Affine>, s: &Signature<Bls12_381>) -> bool {
-        return s.verify(&m, pk.clone(), &bg.p1, bg.p2.clone()).is_ok();
+    //Same as verify, with the first m.len() elements of pk. Identity elements can be appended to m without
+    //changing the signature, so the caller has to authenticate the length (IUT rejects identity BLS keys).
+    pub fn verify_prefix(bg: &BG, pk: &PreparedPublicKey<Bls12_381>, m: &[G1Affine], s: &Signature<Bls12_381>) -> bool {
+        if m.len() > pk.0.len() {
+            return false;
+        }
+        let y_tilde = <Bls12_381 as Pairing>::G2Prepared::from(s.Y_tilde);
+        let mut a = m.to_owned();
+        let mut b = pk.0[..m.len()].to_vec();
+        a.push((-s.Z.into_group()).into_affine());
+        b.push(y_tilde.clone());
+        if !Bls12_381::multi_pairing(a, b).is_zero() {
+            return false;
+        }
+        Bls12_381::multi_pairing(
+            [s.Y, (-bg.p1.into_group()).into_affine()], 
+            [bg.p2_prep.clone(), y_tilde]
+        ).is_zero()
     }
 
-    pub fn chg_rep(bg: &mut BG, _pk: &PreparedPublicKey<Bls12_381>, 
-                    m: &Vec<G1Affine>, s: &Signature<Bls12_381>, r: &BigInt
+    //Checks several signatures under the same public key with a single multi-pairing. Both verification
+    //equations of every signature are weighted by random scalars and merged, the message terms being
+    //aggregated per public key element.
+    pub fn verify_batch(bg: &BG, pk: &PreparedPublicKey<Bls12_381>, ms: &[Vec<G1Affine>], ss: &[Signature<Bls12_381>]) -> bool {
+        ms.iter().all(|m| m.len() == pk.0.len()) && Self::verify_batch_prefix(bg, pk, ms, ss)
+    }
+
+    //Same as verify_batch, with the first ms[i].len() elements of pk for each message (see verify_prefix).
+    pub fn verify_batch_prefix(
+        bg: &BG, pk: &PreparedPublicKey<Bls12_381>, ms: &[Vec<G1Affine>], ss: &[Signature<Bls12_381>]
+    ) -> bool {
+        if ms.len() != ss.len() {
+            return false;
+        }
+        let mut rng = thread_rng();
+        let mut m_sum: Vec<G1Projective> = vec![G1Projective::zero(); pk.0.len()];
+        let mut y_sum = G1Projective::zero();
+        let mut a: Vec<G1Affine> = Vec::with_capacity(pk.0.len() + ss.len() + 1);
+        let mut b = Vec::with_capacity(pk.0.len() + ss.len() + 1);
+        for i in 0..ss.len() {
+            if ms[i].len() > pk.0.len() {
+                return false;
+            }
+            let d = Fr::rand(&mut rng);
+            let e = Fr::rand(&mut rng);
+            for k in 0..ms[i].len() {
+                m_sum[k] += ms[i][k] * d;
+            }
+            y_sum += ss[i].Y * e;
+            a.push((-(ss[i].Z * d + bg.p1 * e)).into_affine());
+            b.push(<Bls12_381 as Pairing>::G2Prepared::from(ss[i].Y_tilde));
+        }
+        a.push(y_sum.into_affine());
+        b.push(bg.p2_prep.clone());
+        a.extend(G1Projective::normalize_batch(&m_sum));
+        b.extend(pk.0.iter().cloned());
+        Bls12_381::multi_pairing(a, b) .is_zero()
+    }
+
+    pub fn chg_rep(_bg: &BG, _pk: &PreparedPublicKey<Bls12_381>, 
+                    m: &[G1Affine], s: &Signature<Bls12_381>, r: &BigInt
     ) -> (Signature<Bls12_381>, Vec<G1Affine>) {
         let r_ = Fr::from(BigUint::from_bytes_be(&r.to_bytes_be().1));
-        let (sp, mp) = s.change_rep(&mut bg.rng, &r_, &m);
-        return (sp, mp);
+        s.change_rep(&mut thread_rng(), &r_, m)
+    }
+}
+
+//The backend of delegatable_credentials, it uses the generators of bg and has no parameters of its own.
+impl EQSScheme for EQS {
+    type Params = ();
+    type SecretKey = SecretKey<Bls12_381>;
+    type PublicKey = PreparedPublicKey<Bls12_381>;
+    type Signature = Signature<Bls12_381>;
+
+    fn setup(_bg: &BG) -> Self::Params {
+    }
+
+    fn k_gen(bg: &BG, _pp: &Self::Params) -> (Self::SecretKey, Self::PublicKey) {
+        EQS::k_gen(bg)
+    }
+
+    fn sign(bg: &BG, _pp: &Self::Params, sk: &Self::SecretKey, m: &[G1Affine]) -> Self::Signature {
+        EQS::sign(bg, sk, m)
+    }
+
+    fn verify(bg: &BG, _pp: &Self::Params, pk: &Self::PublicKey, m: &[G1Affine], s: &Self::Signature) -> bool {
+        EQS::verify_prefix(bg, pk, m, s)
+    }
+
+    fn chg_rep(bg: &BG, _pp: &Self::Params, pk: &Self::PublicKey, 
+                m: &[G1Affine], s: &Self::Signature, r: &BigInt
+    ) -> (Self::Signature, Vec<G1Affine>) {
+        EQS::chg_rep(bg, pk, m, s, r)
+    }
+
+    fn verify_batch(bg: &BG, _pp: &Self::Params, pk: &Self::PublicKey, ms: &[Vec<G1Affine>], ss: &[Self::Signature]) -> bool {
+        EQS::verify_batch_prefix(bg, pk, ms, ss)
+    }
+
+    fn pk_bytes(pk: &Self::PublicKey, bytes: &mut Vec<u8>) {
+        for p in pk.0.iter() {
+            p.serialize_compressed(&mut *bytes).unwrap();
+        }
+    }
+
+    fn sig_bytes(s: &Self::Signature, bytes: &mut Vec<u8>) {
+        s.Z.serialize_compressed(&mut *bytes).unwrap();
+        s.Y.serialize_compressed(&mut *bytes).unwrap();
+        s.Y_tilde.serialize_compressed(&mut *bytes).unwrap();
+    }
+
+    //The prepared key is counted unprepared (one G2 element per message block).
+    fn pk_size(pk: &Self::PublicKey) -> usize {
+        pk.0.len() * G2Affine::generator().compressed_size()
+    }
+
+    fn sig_size(s: &Self::Signature) -> usize {
+        s.compressed_size()
     }
 }
\ No newline at end of file
@@ -0,0 +1,201 @@
+use std::fmt;
+use std::sync::Arc;
+use num_bigint::{BigInt, Sign};
+use num_integer::Integer;
+
+//Montgomery context for an odd modulus, on 64-bit limbs (little endian).
+#[derive(Clone, Debug)]
+pub struct Montgomery {
+    n: Vec<u64>,
+    n0: u64,
+    r2: Vec<u64>,
+    m: BigInt
+}
+
+impl Montgomery {
+    pub fn new(m: &BigInt) -> Self {
+        let n = Self::limbs(m, (m.bits() as usize).div_ceil(64));
+        //-n^-1 mod 2^64 by Newton iteration
+        let mut inv: u64 = 1;
+        for _ in 0..6 {
+            inv = inv.wrapping_mul(2u64.wrapping_sub(n[0].wrapping_mul(inv)));
+        }
+        let r2 = (BigInt::from(1) << (128 * n.len())) % m;
+        Montgomery {
+            n: n.clone(),
+            n0: inv.wrapping_neg(),
+            r2: Self::limbs(&r2, n.len()),
+            m: m.clone()
+        }
+    }
+
+    pub fn modulus(&self) -> &BigInt {
+        &self.m
+    }
+
+    //x*R mod m
+    pub fn to_mont(&self, x: &BigInt) -> Vec<u64> {
+        let mut t = vec![0u64; self.n.len() + 2];
+        self.mul(&Self::limbs(&x.mod_floor(&self.m), self.n.len()), &self.r2, &mut t);
+        t.truncate(self.n.len());
+        t
+    }
+
+    //x*R^-1 mod m
+    pub fn to_int(&self, x: &[u64]) -> BigInt {
+        let mut one = vec![0u64; self.n.len()];
+        one[0] = 1;
+        let mut t = vec![0u64; self.n.len() + 2];
+        self.mul(x, &one, &mut t);
+        t.truncate(self.n.len());
+        BigInt::from_slice(Sign::Plus, &t.iter().flat_map(|x| [*x as u32, (*x >> 32) as u32]).collect::<Vec<u32>>())
+    }
+
+    //R mod m, the Montgomery form of 1
+    pub fn one(&self) -> Vec<u64> {
+        self.to_mont(&BigInt::from(1))
+    }
+
+    //CIOS multiplication: t[..s] = a*b*R^-1 mod m, t needs s+2 limbs.
+    pub fn mul(&self, a: &[u64], b: &[u64], t: &mut [u64]) {
+        let s = self.n.len();
+        let n = &self.n;
+        for x in t.iter_mut() {
+            *x = 0;
+        }
+        for bi in b.iter().take(s) {
+            let mut c: u128 = 0;
+            for j in 0..s {
+                let v = t[j] as u128 + (a[j] as u128) * (*bi as u128) + c;
+                t[j] = v as u64;
+                c = v >> 64;
+            }
+            let v = t[s] as u128 + c;
+            t[s] = v as u64;
+            t[s + 1] = (v >> 64) as u64;
+            let k = t[0].wrapping_mul(self.n0);
+            let v = t[0] as u128 + (k as u128) * (n[0] as u128);
+            let mut c = v >> 64;
+            for j in 1..s {
+                let v = t[j] as u128 + (k as u128) * (n[j] as u128) + c;
+                t[j - 1] = v as u64;
+                c = v >> 64;
+            }
+            let v = t[s] as u128 + c;
+            t[s - 1] = v as u64;
+            t[s] = t[s + 1] + (v >> 64) as u64;
+        }
+        let mut ge = t[s] != 0;
+        if !ge {
+            ge = true;
+            for j in (0..s).rev() {
+                if t[j] != n[j] {
+                    ge = t[j] > n[j];
+                    break;
+                }
+            }
+        }
+        if ge {
+            let mut borrow = 0u64;
+            for j in 0..s {
+                let (v, b1) = t[j].overflowing_sub(n[j]);
+                let (v, b2) = v.overflowing_sub(borrow);
+                t[j] = v;
+                borrow = (b1 || b2) as u64;
+            }
+        }
+    }
+
+    fn limbs(x: &BigInt, s: usize) -> Vec<u64> {
+        let (_, mut v) = x.to_u64_digits();
+        v.resize(s, 0);
+        v
+    }
+}
+
+//Windowed fixed-base table for g^e mod m with m prime: table[i][d-1] = g^(d*2^(w*i)).
+//Exponents are reduced mod m-1 when they are larger than the table (Fermat).
+#[derive(Clone)]
+pub struct FixedBase {
+    pub g: BigInt,
+    ctx: Montgomery,
+    window: usize,
+    bits: usize,
+    table: Arc<Vec<Vec<Vec<u64>>>>
+}
+
+impl fmt::Debug for FixedBase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "FixedBase {{ g: {}, m: {}, window: {}, bits: {} }}", self.g, self.ctx.m, self.window, self.bits)
+    }
+}
+
+impl FixedBase {
+    pub fn new(g: &BigInt, m: &BigInt, window: usize) -> Self {
+        let ctx = Montgomery::new(m);
+        let bits = (m - 1u8).bits() as usize;
+        let mut table = Vec::with_capacity(bits.div_ceil(window));
+        let mut t = vec![0u64; ctx.n.len() + 2];
+        let mut base = ctx.to_mont(g);
+        for _ in 0..bits.div_ceil(window) {
+            let mut row: Vec<Vec<u64>> = Vec::with_capacity((1 << window) - 1);
+            row.push(base.clone());
+            for d in 1..(1 << window) - 1 {
+                ctx.mul(&row[d - 1], &base, &mut t);
+                row.push(t[..ctx.n.len()].to_vec());
+            }
+            //next base is g^(2^(w*(i+1))) = row[2^w-1] * base
+            ctx.mul(&row[(1 << window) - 2], &base, &mut t);
+            base = t[..ctx.n.len()].to_vec();
+            table.push(row);
+        }
+        FixedBase {
+            g: g.clone(),
+            ctx,
+            window,
+            bits,
+            table: Arc::new(table)
+        }
+    }
+
+    pub fn modulus(&self) -> &BigInt {
+        self.ctx.modulus()
+    }
+
+    pub fn pow(&self, e: &BigInt) -> BigInt {
+        let e = if e.sign() == Sign::Minus || e.bits() as usize > self.bits {
+            e.mod_floor(&(self.ctx.modulus() - 1u8))
+        } else {
+            e.clone()
+        };
+        let (_, digits) = e.to_u64_digits();
+        let s = self.ctx.n.len();
+        let mut acc = self.ctx.one();
+        let mut t = vec![0u64; s + 2];
+        for i in 0..self.table.len() {
+            let d = Self::window_at(&digits, i * self.window, self.window);
+            if d != 0 {
+                self.ctx.mul(&acc, &self.table[i][d - 1], &mut t);
+                acc.copy_from_slice(&t[..s]);
+            }
+        }
+        self.ctx.to_int(&acc)
+    }
+
+    //g^e mod m through the table when it matches g and m, with modpow otherwise.
+    pub fn pow_or(fb: Option<&FixedBase>, g: &BigInt, e: &BigInt, m: &BigInt) -> BigInt {
+        match fb {
+            Some(fb) if fb.g == *g && fb.modulus() == m => fb.pow(e),
+            _ => g.modpow(e, m),
+        }
+    }
+
+    fn window_at(digits: &[u64], pos: usize, w: usize) -> usize {
+        let (l, o) = (pos / 64, pos % 64);
+        let mut v = if l < digits.len() { digits[l] >> o } else { 0 };
+        if o + w > 64 && l + 1 < digits.len() {
+            v |= digits[l + 1] << (64 - o);
+        }
+        (v & ((1u64 << w) - 1)) as usize
+    }
+}
\ No newline at end of file
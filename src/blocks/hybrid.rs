@@ -0,0 +1,55 @@
+use chacha20poly1305::{aead::{Aead, KeyInit, Payload}, ChaCha20Poly1305, Key, Nonce};
+use hkdf::Hkdf;
+use k256::{ecdh::EphemeralSecret, elliptic_curve::{rand_core::OsRng, sec1::ToEncodedPoint}, PublicKey, SecretKey};
+use sha2::Sha256;
+
+//Encapsulated key (compressed ephemeral point) and AEAD ciphertext.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HybridCiphertext {
+    pub ek: Vec<u8>,
+    pub ct: Vec<u8>
+}
+
+pub struct Hybrid;
+
+impl Hybrid {
+    pub fn k_gen() -> (SecretKey, PublicKey) {
+        let sk = SecretKey::random(&mut OsRng);
+        let pk = sk.public_key();
+        (sk, pk)
+    }
+
+    //ECDH KEM on secp256k1 with HKDF-SHA256, then ChaCha20-Poly1305. The key is fresh for each encapsulation,
+    //so a fixed nonce is used.
+    pub fn encrypt(pk: &PublicKey, m: &[u8], aad: &[u8]) -> HybridCiphertext {
+        let esk = EphemeralSecret::random(&mut OsRng);
+        let ek = esk.public_key().to_encoded_point(true).as_bytes().to_vec();
+        let ss = esk.diffie_hellman(pk);
+        let key = Self::kdf(&ss.raw_secret_bytes()[..], &ek, pk);
+        let cipher = ChaCha20Poly1305::new(&key);
+        let ct = cipher.encrypt(&Nonce::default(), Payload { msg: m, aad }).unwrap();
+        HybridCiphertext { ek, ct }
+    }
+
+    pub fn decrypt(sk: &SecretKey, c: &HybridCiphertext, aad: &[u8]) -> Result<Vec<u8>, String> {
+        let epk = match PublicKey::from_sec1_bytes(&c.ek) {
+            Ok(epk) => epk,
+            Err(_) => return Err("Invalid encapsulated key".to_string()),
+        };
+        let ss = k256::ecdh::diffie_hellman(sk.to_nonzero_scalar(), epk.as_affine());
+        let key = Self::kdf(&ss.raw_secret_bytes()[..], &c.ek, &sk.public_key());
+        let cipher = ChaCha20Poly1305::new(&key);
+        match cipher.decrypt(&Nonce::default(), Payload { msg: &c.ct, aad }) {
+            Ok(m) => Ok(m),
+            Err(_) => Err("Decryption failed".to_string()),
+        }
+    }
+
+    fn kdf(ss: &[u8], ek: &[u8], pk: &PublicKey) -> Key {
+        let mut info = ek.to_vec();
+        info.extend_from_slice(pk.to_encoded_point(true).as_bytes());
+        let mut key = Key::default();
+        Hkdf::<Sha256>::new(Some(b"k-SAN hybrid"), ss).expand(&info, &mut key).unwrap();
+        key
+    }
+}
\ No newline at end of file
@@ -1,47 +1,113 @@
 use mercurial_signature::{Curve, CurveBls12_381, PublicParams, change_representation, SecretKey, PublicKey, Signature};
 use num_bigint::{BigInt, BigUint};
-use rand::rngs::ThreadRng;
+use rand::thread_rng;
+use std::fmt;
+use ark_bls12_381::G1Affine;
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_serialize::CanonicalSerialize;
+use super::eqs::{EQSScheme, BG as EQSBG};
 
 type G1 = <CurveBls12_381 as Curve>::G1;
 type Fr = <CurveBls12_381 as Curve>::Fr;
 
 #[derive(Clone)]
 pub struct BG {
-    pub rng: ThreadRng,
     pub pp: PublicParams,
     pub n: u32
 }
 
+//The types of mercurial-signature do not implement Debug.
+#[derive(Clone)]
+pub struct Opaque<T>(pub T);
+
+impl<T> fmt::Debug for Opaque<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", std::any::type_name::<T>())
+    }
+}
+
+#[derive(Clone, Debug)]
 pub struct Mercurial;
 
 impl Mercurial {
     pub fn setup(n: u32, _dst: &String) -> BG {
-        let mut rng = rand::thread_rng();
-        let pp = PublicParams::new(&mut rng);
+        let pp = PublicParams::new(&mut thread_rng());
         return BG{
-            rng: rng,
             pp: pp,
             n: n
         };
     }
 
-    pub fn k_gen(bg: &mut BG) -> (SecretKey, PublicKey) {
-        let (pk, sk) = bg.pp.key_gen(&mut bg.rng, bg.n);
+    pub fn k_gen(bg: &BG) -> (SecretKey, PublicKey) {
+        let (pk, sk) = bg.pp.key_gen(&mut thread_rng(), bg.n);
         return (sk, pk);
     }
 
-    pub fn sign(bg: &mut BG, sk: &mut SecretKey, m: &Vec<G1>) -> Signature{
-        let sig = sk.sign(&mut bg.rng, &bg.pp, &m);
+    pub fn sign(bg: &BG, sk: &mut SecretKey, m: &Vec<G1>) -> Signature{
+        let sig = sk.sign(&mut thread_rng(), &bg.pp, m);
         return sig;
     }
 
-    pub fn verify(bg: &mut BG, pk: &PublicKey, m: &Vec<G1>, s: &Signature) -> bool {
+    pub fn verify(bg: &BG, pk: &PublicKey, m: &Vec<G1>, s: &Signature) -> bool {
         return pk.verify(&bg.pp, &m, &s);
     }
 
-    pub fn chg_rep(bg: &mut BG, _pk: &PublicKey, m: &mut Vec<G1>, s: &mut Signature, r: &BigInt) -> (Signature, Vec<G1>) {
+    pub fn chg_rep(_bg: &BG, _pk: &PublicKey, m: &mut Vec<G1>, s: &mut Signature, r: &BigInt) -> (Signature, Vec<G1>) {
         let r_ = Fr::from(BigUint::from_bytes_be(&r.to_bytes_be().1));
-        change_representation(&mut bg.rng, m, s, r_);
+        change_representation(&mut thread_rng(), m, s, r_);
         return (s.clone(), m.clone());
     }
+}
+
+//The backend of mercurial-signature, with the generators of bg.
+impl EQSScheme for Mercurial {
+    type Params = Opaque<PublicParams>;
+    type SecretKey = Opaque<SecretKey>;
+    type PublicKey = Opaque<PublicKey>;
+    type Signature = Opaque<Signature>;
+
+    fn setup(bg: &EQSBG) -> Self::Params {
+        Opaque(PublicParams { p1: bg.p1.into_group(), p2: bg.p2.into_group() })
+    }
+
+    fn k_gen(bg: &EQSBG, pp: &Self::Params) -> (Self::SecretKey, Self::PublicKey) {
+        let (pk, sk) = pp.0.key_gen(&mut thread_rng(), bg.n);
+        (Opaque(sk), Opaque(pk))
+    }
+
+    fn sign(_bg: &EQSBG, pp: &Self::Params, sk: &Self::SecretKey, m: &[G1Affine]) -> Self::Signature {
+        let m: Vec<G1> = m.iter().map(|x| x.into_group()).collect();
+        Opaque(sk.0.sign(&mut thread_rng(), &pp.0, &m))
+    }
+
+    fn verify(_bg: &EQSBG, pp: &Self::Params, pk: &Self::PublicKey, m: &[G1Affine], s: &Self::Signature) -> bool {
+        let m: Vec<G1> = m.iter().map(|x| x.into_group()).collect();
+        pk.0.verify(&pp.0, &m, &s.0)
+    }
+
+    fn chg_rep(_bg: &EQSBG, _pp: &Self::Params, _pk: &Self::PublicKey, 
+                m: &[G1Affine], s: &Self::Signature, r: &BigInt
+    ) -> (Self::Signature, Vec<G1Affine>) {
+        let r_ = Fr::from(BigUint::from_bytes_be(&r.to_bytes_be().1));
+        let mut mp: Vec<G1> = m.iter().map(|x| x.into_group()).collect();
+        let mut sp = s.0.clone();
+        change_representation(&mut thread_rng(), &mut mp, &mut sp, r_);
+        (Opaque(sp), G1::normalize_batch(&mp))
+    }
+
+    fn pk_bytes(pk: &Self::PublicKey, bytes: &mut Vec<u8>) {
+        pk.0.serialize_compressed(&mut *bytes).unwrap();
+    }
+
+    fn sig_bytes(s: &Self::Signature, bytes: &mut Vec<u8>) {
+        s.0.serialize_compressed(&mut *bytes).unwrap();
+    }
+
+    fn pk_size(pk: &Self::PublicKey) -> usize {
+        pk.0.compressed_size()
+    }
+
+    fn sig_size(s: &Self::Signature) -> usize {
+        s.0.compressed_size()
+    }
 }
\ No newline at end of file
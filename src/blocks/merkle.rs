@@ -0,0 +1,77 @@
+use sha2::{Sha256, Digest};
+
+//Sibling hashes from the leaf to the root, and the index of the leaf.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MerkleProof {
+    pub index: usize,
+    pub path: Vec<Vec<u8>>
+}
+
+pub struct Merkle;
+
+impl Merkle {
+    //SHA-256 tree, leaves are H(0 || v) and nodes H(1 || left || right). Levels of odd length are padded with
+    //H(2), so that every leaf has a sibling at every level.
+    pub fn root(values: &[String]) -> Vec<u8> {
+        let mut level: Vec<Vec<u8>> = values.iter().map(Self::leaf).collect();
+        if level.is_empty() {
+            return Self::empty();
+        }
+        while level.len() > 1 {
+            level = Self::next(&level);
+        }
+        level[0].clone()
+    }
+
+    //Membership proof of the first occurrence of v, None if v is not in values.
+    pub fn prove(values: &[String], v: &String) -> Option<MerkleProof> {
+        let index = values.iter().position(|x| x == v)?;
+        let mut level: Vec<Vec<u8>> = values.iter().map(Self::leaf).collect();
+        let mut i = index;
+        let mut path: Vec<Vec<u8>> = Vec::new();
+        while level.len() > 1 {
+            path.push(level.get(i ^ 1).cloned().unwrap_or(Self::empty()));
+            level = Self::next(&level);
+            i /= 2;
+        }
+        Some(MerkleProof { index, path })
+    }
+
+    pub fn verify(root: &Vec<u8>, v: &String, p: &MerkleProof) -> bool {
+        if p.path.len() < usize::BITS as usize && p.index >> p.path.len() != 0 {
+            return false;
+        }
+        let mut h = Self::leaf(v);
+        let mut i = p.index;
+        for s in p.path.iter() {
+            h = if i.is_multiple_of(2) { Self::node(&h, s) } else { Self::node(s, &h) };
+            i /= 2;
+        }
+        h == *root
+    }
+
+    fn next(level: &[Vec<u8>]) -> Vec<Vec<u8>> {
+        level.chunks(2).map(|c| Self::node(&c[0], c.get(1).unwrap_or(&Self::empty()))).collect()
+    }
+
+    fn leaf(v: &String) -> Vec<u8> {
+        let mut hasher = Sha256::new();
+        hasher.update([0u8]);
+        hasher.update(v.as_bytes());
+        hasher.finalize().to_vec()
+    }
+
+    fn node(l: &Vec<u8>, r: &Vec<u8>) -> Vec<u8> {
+        let mut hasher = Sha256::new();
+        hasher.update([1u8]);
+        hasher.update(l);
+        hasher.update(r);
+        hasher.finalize().to_vec()
+    }
+
+    fn empty() -> Vec<u8> {
+        let mut hasher = Sha256::new();
+        hasher.update([2u8]);
+        hasher.finalize().to_vec()
+    }
+}
\ No newline at end of file
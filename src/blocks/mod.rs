@@ -5,4 +5,8 @@ pub mod vrs;
 pub mod eqs;
 pub mod mercurial;
 pub mod bls;
+pub mod fixed_base;
+pub mod hybrid;
+pub mod merkle;
+pub mod shamir;
 mod tests;
\ No newline at end of file
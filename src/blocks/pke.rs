@@ -2,6 +2,20 @@ use curv::arithmetic::Converter;
 use kzen_paillier::*;
 use num_bigint::BigInt as nBigInt;
 
+//Paillier ciphertext. It owns its value, so the signatures that carry it have no lifetime parameter.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Ciphertext(BigInt);
+
+impl Ciphertext {
+    pub fn to_str_radix(&self, radix: u8) -> String {
+        self.0.to_str_radix(radix)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.0.to_bytes()
+    }
+}
+
 pub struct PKE;
 
 impl PKE {
@@ -10,20 +24,20 @@ impl PKE {
         return (sk, pk);
     }
 
-    pub fn encrypt<'m, 'd>(pk: &EncryptionKey, m: &nBigInt) -> RawCiphertext<'d> {
+    pub fn encrypt(pk: &EncryptionKey, m: &nBigInt) -> Ciphertext {
         let m_ = BigInt::from_bytes(m.to_bytes_be().1.as_slice());
-        let c= Paillier::encrypt(pk, RawPlaintext::from(m_));
-        return c;
+        let c: RawCiphertext = Paillier::encrypt(pk, RawPlaintext::from(m_));
+        Ciphertext(c.into())
     }
 
-    pub fn decrypt(sk: &DecryptionKey, c: &RawCiphertext) -> nBigInt {
-        let m = Paillier::decrypt(sk, c);
+    pub fn decrypt(sk: &DecryptionKey, c: &Ciphertext) -> nBigInt {
+        let m: RawPlaintext = Paillier::decrypt(sk, RawCiphertext::from(&c.0));
         return nBigInt::from_bytes_be(num_bigint::Sign::Plus, m.0.into_owned().to_bytes().as_slice());
     }
 
-    pub fn multiply<'c, 'm, 'd>(pk: &EncryptionKey, c: &RawCiphertext, s: &nBigInt) -> RawCiphertext<'d> {
+    pub fn multiply(pk: &EncryptionKey, c: &Ciphertext, s: &nBigInt) -> Ciphertext {
         let s_ = BigInt::from_bytes(s.to_bytes_be().1.as_slice());
-        let cp = Paillier::mul(pk, c.clone(), RawPlaintext::from(s_));
-        return cp;
+        let cp: RawCiphertext = Paillier::mul(pk, RawCiphertext::from(&c.0), RawPlaintext::from(s_));
+        Ciphertext(cp.into())
     }
 }
\ No newline at end of file
@@ -0,0 +1,42 @@
+use num_bigint::{BigInt, RandBigInt};
+use num_integer::Integer;
+use rand::thread_rng;
+
+pub struct Shamir;
+
+impl Shamir {
+    //Shares of secret mod q for the points 1..=n, any t of them give back the secret: f(1), ..., f(n) for a random
+    //polynomial f of degree t - 1 with f(0) = secret.
+    pub fn share(q: &BigInt, secret: &BigInt, t: usize, n: usize) -> Vec<BigInt> {
+        let mut rng = thread_rng();
+        let zero = BigInt::from(0);
+        let mut coefs: Vec<BigInt> = vec![secret.mod_floor(q)];
+        for _ in 1..t {
+            coefs.push(rng.gen_bigint_range(&zero, q));
+        }
+        (1..=n).map(|x| {
+            let x = BigInt::from(x);
+            coefs.iter().rev().fold(zero.clone(), |acc, c| (acc * &x + c) % q)
+        }).collect()
+    }
+
+    //Lagrange coefficient at 0 of the point x among the distinct points of xs, so that the secret is the sum of
+    //lagrange(xs, x) * f(x) mod q over xs.
+    pub fn lagrange(q: &BigInt, xs: &[usize], x: usize) -> BigInt {
+        let mut num = BigInt::from(1);
+        let mut den = BigInt::from(1);
+        for xj in xs.iter().filter(|xj| **xj != x) {
+            num = (num * BigInt::from(*xj)) % q;
+            den = (den * (BigInt::from(*xj) - BigInt::from(x))).mod_floor(q);
+        }
+        (num * den.modinv(q).unwrap()) % q
+    }
+
+    pub fn combine(q: &BigInt, xs: &[usize], shares: &[BigInt]) -> BigInt {
+        let mut s = BigInt::from(0);
+        for (x, y) in xs.iter().zip(shares.iter()) {
+            s = (s + Self::lagrange(q, xs, *x) * y) % q;
+        }
+        s
+    }
+}
\ No newline at end of file
@@ -1,8 +1,9 @@
-use k256::{elliptic_curve::rand_core, schnorr::{
+use k256::{elliptic_curve::{rand_core, bigint::U256, ops::Reduce, point::{AffineCoordinates, DecompactPoint}, Field, PrimeField}, schnorr::{
     signature::{Signer, Verifier},
     SigningKey, VerifyingKey
-}, FieldBytes};
+}, AffinePoint, FieldBytes, ProjectivePoint, Scalar};
 use rand_core::OsRng;
+use sha2::{Sha256, Digest};
 
 pub struct SIG;
 
@@ -26,4 +27,60 @@ impl SIG {
             Err(_) => false,
         }
     }
+
+    //Checks all the signatures of the same signer with one random linear combination of the BIP340
+    //equations s*G = R + e*P. Returns false if at least one of the signatures is invalid.
+    pub fn verify_batch(pk: &FieldBytes, ms: &[String], ss: &[k256::schnorr::Signature]) -> bool {
+        let ss: Vec<[u8; 64]> = ss.iter().map(|s| s.to_bytes()).collect();
+        Self::verify_batch_bytes(pk, ms, &ss)
+    }
+
+    //Same as verify_batch on encoded signatures (R.x || s). As in verify, s must be in [1, n-1] and R.x must be
+    //the x-coordinate of a point with an even y.
+    pub fn verify_batch_bytes(pk: &FieldBytes, ms: &[String], ss: &[[u8; 64]]) -> bool {
+        if ms.len() != ss.len() {
+            return false;
+        }
+        let verifying_key = match VerifyingKey::from_bytes(pk) {
+            Ok(verifying_key) => verifying_key,
+            Err(_) => return false,
+        };
+        let tag = Sha256::digest(b"BIP0340/challenge");
+        let mut s_sum = Scalar::ZERO;
+        let mut e_sum = Scalar::ZERO;
+        let mut r_sum = ProjectivePoint::IDENTITY;
+        for i in 0..ss.len() {
+            let mut r_bytes = FieldBytes::default();
+            let mut s_bytes = FieldBytes::default();
+            r_bytes.copy_from_slice(&ss[i][..32]);
+            s_bytes.copy_from_slice(&ss[i][32..]);
+            //decompact rejects R.x >= p and lifts R.x to the point with an even y
+            let r: Option<AffinePoint> = AffinePoint::decompact(&r_bytes).into();
+            let r = match r {
+                Some(r) if !bool::from(r.y_is_odd()) => r,
+                _ => return false,
+            };
+            //from_repr rejects s >= n
+            let s: Option<Scalar> = Scalar::from_repr(s_bytes).into();
+            let s = match s {
+                Some(s) if !bool::from(s.is_zero()) => s,
+                _ => return false,
+            };
+            let e = <Scalar as Reduce<U256>>::reduce_bytes(
+                &Sha256::new()
+                    .chain_update(tag)
+                    .chain_update(tag)
+                    .chain_update(r_bytes)
+                    .chain_update(pk)
+                    .chain_update(Sha256::digest(ms[i].as_bytes()))
+                    .finalize()
+            );
+            let a = if i == 0 { Scalar::ONE } else { Scalar::random(&mut OsRng) };
+            s_sum += a * s;
+            e_sum += a * e;
+            r_sum += ProjectivePoint::from(r) * a;
+        }
+        let p = ProjectivePoint::from(*verifying_key.as_affine());
+        ProjectivePoint::GENERATOR * s_sum == r_sum + p * e_sum
+    }
 }
\ No newline at end of file
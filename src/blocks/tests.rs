@@ -6,9 +6,14 @@ mod tests {
     use crate::blocks::eqs::EQS;
     use crate::blocks::bls::BLS;
     use crate::blocks::vrs::VRS;
+    use crate::blocks::fixed_base::FixedBase;
+    use crate::blocks::merkle::Merkle;
+    use crate::blocks::shamir::Shamir;
     use rand::{Rng, thread_rng};
     use num_bigint::{BigInt, BigUint};
-    use ark_bls12_381::Fr;
+    use ark_bls12_381::{Fr, G1Affine, G1Projective, G2Projective};
+    use ark_ec::CurveGroup;
+    use ark_std::Zero;
 
     #[test]
     fn test_pke() {
@@ -35,6 +40,10 @@ mod tests {
         let cp = PKE::multiply(&pk, &c, &BigInt::from(2));
         let mp = PKE::decrypt(&sk, &cp);
         assert_eq!(mp, r.clone() * 2, "Decrypted of multiplied ciphertext failed");
+
+        //Check that ciphertexts are owned and can be moved to another thread
+        let mp = std::thread::spawn(move || PKE::decrypt(&sk, &cp)).join().unwrap();
+        assert_eq!(mp, r * 2, "Decryption of a ciphertext moved to another thread failed");
     }
 
     #[test]
@@ -56,63 +65,154 @@ mod tests {
     #[test]
     fn test_chash() {
         let (p, q, g) = CHash::setup(2048);
-        let (sk, pk) = CHash::k_gen(&p, &q, &g);
+        let (sk, pk) = CHash::k_gen(&p, &q, &g, None);
         
         let m = BigInt::from(10u32);
-        let (h, r) = CHash::hash(&p, &q, &g, &pk, &m);
+        let (h, r) = CHash::hash(&p, &q, &g, &pk, &m, None);
         
         //Check that the check algorithm returns true for a valid hash
-        let b = CHash::check(&p, &q, &g, &pk, &m, &r, &h);
+        let b = CHash::check(&p, &g, &pk, &m, &r, &h, None);
         assert!(b, "Valid hash check failed");
 
         //Check that the check algorithm returns false for an invalid hash
-        let b = CHash::check(&p, &q, &g, &pk, &BigInt::from(20u32), &r, &h);
+        let b = CHash::check(&p, &g, &pk, &BigInt::from(20u32), &r, &h, None);
         assert!(!b, "Invalid hash check failed");
 
         //Check that the check algorithm returns true for a valid adapted hash
         let mp = BigInt::from(55u32);
         let rp = CHash::adapt(&p, &q, &g, &sk, &m, &r, &mp);
-        let b = CHash::check(&p, &q, &g, &pk, &mp, &rp, &h);
+        let b = CHash::check(&p, &g, &pk, &mp, &rp, &h, None);
         assert!(b, "Hash check after Adapt failed");
     }
 
+    #[test]
+    fn test_chash_kef() {
+        let (p, q, g) = CHash::setup(512);
+        let (sk, pk) = CHash::k_gen(&p, &q, &g, None);
+
+        let m = BigInt::from(10u32);
+        let (h, r, s) = CHash::hash_kef(&p, &q, &g, &pk, &m);
+
+        //Check that the check algorithm returns true for a valid hash and false for another message
+        assert!(CHash::check_kef(&p, &q, &g, &pk, &m, (&r, &s), &h), "Valid hash check failed");
+        let b = CHash::check_kef(&p, &q, &g, &pk, &BigInt::from(20u32), (&r, &s), &h);
+        assert!(!b, "Invalid hash check failed");
+
+        //Check that two collisions open the hash and that they do not give sk as with adapt
+        let (m1, m2) = (BigInt::from(55u32), BigInt::from(56u32));
+        let (r1, s1) = CHash::adapt_kef(&p, &q, &g, &sk, &h, &m1);
+        let (r2, s2) = CHash::adapt_kef(&p, &q, &g, &sk, &h, &m2);
+        assert!(CHash::check_kef(&p, &q, &g, &pk, &m1, (&r1, &s1), &h), "Hash check after Adapt failed");
+        assert!(CHash::check_kef(&p, &q, &g, &pk, &m2, (&r2, &s2), &h), "Hash check after a second Adapt failed");
+        let b = CHash::check_kef(&p, &q, &g, &pk, &m2, (&r1, &s1), &h);
+        assert!(!b, "A collision should not open the hash to another message");
+        let exposed = (&r2 - &r1).modinv(&q).map(|x| (&m1 - &m2) * x % &q).unwrap_or_default();
+        assert!(exposed != sk && (&exposed + &q) != sk, "Two collisions should not expose the trapdoor");
+    }
+
+    #[test]
+    fn test_sig_batch() {
+        let (sk, pk) = SIG::k_gen();
+
+        let ms: Vec<String> = (0..5).map(|i| format!("message {}", i)).collect();
+        let mut ss: Vec<k256::schnorr::Signature> = ms.iter().map(|m| SIG::sign(&sk, m)).collect();
+
+        //Check that the batch verification returns true for valid signatures
+        let b = SIG::verify_batch(&pk, &ms, &ss);
+        assert!(b, "Valid batch verification failed");
+
+        //Check that the batch verification returns false for a non-canonical s (the group order n, reduced to 0)
+        let mut bytes: Vec<[u8; 64]> = ss.iter().map(|s| s.to_bytes()).collect();
+        let n: [u8; 32] = [
+            0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe,
+            0xba, 0xae, 0xdc, 0xe6, 0xaf, 0x48, 0xa0, 0x3b, 0xbf, 0xd2, 0x5e, 0x8c, 0xd0, 0x36, 0x41, 0x41
+        ];
+        bytes[2][32..].copy_from_slice(&n);
+        let b = SIG::verify_batch_bytes(&pk, &ms, &bytes);
+        assert!(!b, "Non-canonical batch verification failed");
+
+        //Check that the batch verification returns false if one of the signatures is invalid
+        ss[3] = SIG::sign(&sk, "other message");
+        let b = SIG::verify_batch(&pk, &ms, &ss);
+        assert!(!b, "Invalid batch verification failed");
+    }
+
+    #[test]
+    fn test_chash_batch() {
+        let (p, q, g) = CHash::setup(512);
+        let mut pks: Vec<BigInt> = Vec::new();
+        let mut ms: Vec<BigInt> = Vec::new();
+        let mut rs: Vec<BigInt> = Vec::new();
+        let mut hs: Vec<BigInt> = Vec::new();
+        for i in 0..5 {
+            let (_sk, pk) = CHash::k_gen(&p, &q, &g, None);
+            let m = BigInt::from(i);
+            let (h, r) = CHash::hash(&p, &q, &g, &pk, &m, None);
+            pks.push(pk);
+            ms.push(m);
+            rs.push(r);
+            hs.push(h);
+        }
+
+        //Check that the batch check returns true for valid hashes
+        let b = CHash::check_batch(&p, &q, &g, &pks, &ms, &rs, &hs);
+        assert!(b, "Valid batch check failed");
+
+        //Check that the batch check returns false if one of the hashes is invalid
+        ms[2] = BigInt::from(20u32);
+        let b = CHash::check_batch(&p, &q, &g, &pks, &ms, &rs, &hs);
+        assert!(!b, "Invalid batch check failed");
+
+        //Check that the batch check returns false for a hash outside of the subgroup
+        ms[2] = BigInt::from(2u32);
+        hs[2] = &p - &hs[2];
+        let b = CHash::check_batch(&p, &q, &g, &pks, &ms, &rs, &hs);
+        assert!(!b, "Batch check outside of the subgroup failed");
+    }
+
     #[test]
     fn test_eqs() {
-        let mut bg = EQS::setup(3, &"k-SAN test".to_string());
-        let (sk, pk) = EQS::k_gen(&mut bg);
+        let bg = EQS::setup(3, &"k-SAN test".to_string());
+        let (sk, pk) = EQS::k_gen(&bg);
 
         let m = vec![bg.p1.clone(), bg.p1.clone(), bg.p1.clone()];
-        let s = EQS::sign(&mut bg, &sk, &m);
+        let s = EQS::sign(&bg, &sk, &m);
 
         //Check that the verify algorithm returns true for a valid signature
-        let b = EQS::verify(&mut bg, &pk, &m, &s);
+        let b = EQS::verify(&bg, &pk, &m, &s);
         assert!(b, "Valid signature verification failed");
 
         //Check that the verify algorithm returns true for a valid signature after changing its representation
         let r = BigInt::from(50);
-        let (sp, mp) = EQS::chg_rep(&mut bg, &pk, &m, &s, &r);
-        let b = EQS::verify(&mut bg, &pk, &mp, &sp);
+        let (sp, mp) = EQS::chg_rep(&bg, &pk, &m, &s, &r);
+        let b = EQS::verify(&bg, &pk, &mp, &sp);
         assert!(b, "Valid signature verification after ChgRep failed");
 
         //Check that the verify algorithm returns false for an invalid signature
-        let b = EQS::verify(&mut bg, &pk, &mp, &s);
+        let b = EQS::verify(&bg, &pk, &mp, &s);
         assert!(!b, "Invalid signature verification failed");
+        //Check that only verify_prefix accepts a signature on a vector shorter than the key
+        let s2 = EQS::sign(&bg, &sk, &m[..2]);
+        let b = EQS::verify(&bg, &pk, &m[..2], &s2);
+        assert!(!b, "Verification of a shorter vector should fail");
+        let b = EQS::verify_prefix(&bg, &pk, &m[..2], &s2);
+        assert!(b, "Prefix verification of a shorter vector failed");
     }
 
     #[test]
     fn test_bls() {
-        let mut bg= EQS::setup(3, &"k-SAN test".to_string());
-        let (_sk1, sk2, pk1, pk2) = BLS::k_gen(&mut bg);
+        let bg= EQS::setup(3, &"k-SAN test".to_string());
+        let (_sk1, sk2, pk1, pk2) = BLS::k_gen(&bg);
 
         let m = "message 1234".to_string();
-        let s = BLS::sign(&mut bg, &sk2, &m);
+        let s = BLS::sign(&bg, &sk2, &m);
 
         //Check that the verify algorithm returns true for a valid signature
-        let b = BLS::verify(&mut bg, &pk1, &pk2, &m, &s);
+        let b = BLS::verify(&bg, &pk1, &pk2, &m, &s);
         assert!(b, "Valid signature verification failed");
 
         //Check that the verify algorithm returns false for an invalid signature
-        let b = BLS::verify(&mut bg, &pk1, &pk2, &"othermessage".to_string(), &s);
+        let b = BLS::verify(&bg, &pk1, &pk2, &"othermessage".to_string(), &s);
         assert!(!b, "Invalid signature verification failed");
         
         //Check that the verify algorithm returns true for a valid signature after randomization
@@ -122,67 +222,254 @@ mod tests {
         let pk1r = BLS::_randomize_g1(&pk1, &r1);
         let pk2r = BLS::_randomize_g1(&pk2, &r3);
         let sr = BLS::randomize_g2(&s, &r2);
-        let b = BLS::verify(&mut bg, &pk1r, &pk2r, &m, &sr);
+        let b = BLS::verify(&bg, &pk1r, &pk2r, &m, &sr);
         assert!(b, "Verification after randomization failed");
 
         //Check that the verify algorithm returns true for a new valid signature generated after keys randomization
-        let ns = BLS::sign(&mut bg, &Fr::from(BigUint::from_bytes_be(&(BigInt::from(sk2.to_string().parse::<BigInt>().unwrap()) * r2.clone()).to_bytes_be().1)), &m);
-        let b = BLS::verify(&mut bg, &pk1r, &pk2r, &m, &ns);
+        let ns = BLS::sign(&bg, &Fr::from(BigUint::from_bytes_be(&(sk2.to_string().parse::<BigInt>().unwrap() * r2.clone()).to_bytes_be().1)), &m);
+        let b = BLS::verify(&bg, &pk1r, &pk2r, &m, &ns);
         assert!(b, "New signature verification after keys randomization failed");
+
+        //Check that the verify algorithm returns false for identity keys, which would accept any message
+        let zero1 = G1Projective::zero();
+        let b = BLS::verify(&bg, &zero1, &zero1, &m, &G2Projective::zero());
+        assert!(!b, "Verification with identity keys should fail");
+        let b = BLS::verify_batch(&bg, &[pk1, zero1], &[pk2, zero1], &[m.clone(), m.clone()], &[s, G2Projective::zero()]);
+        assert!(!b, "Batch verification with identity keys should fail");
+    }
+
+    #[test]
+    fn test_eqs_bls_batch() {
+        let bg = EQS::setup(3, &"k-SAN test".to_string());
+        let (sk, pk) = EQS::k_gen(&bg);
+
+        let mut ms: Vec<Vec<G1Affine>> = Vec::new();
+        let mut ss = Vec::new();
+        let mut pk1s: Vec<G1Projective> = Vec::new();
+        let mut pk2s: Vec<G1Projective> = Vec::new();
+        let mut mjs: Vec<String> = Vec::new();
+        let mut s_bls: Vec<G2Projective> = Vec::new();
+        for i in 0..4 {
+            let mut m: Vec<G1Affine> = Vec::new();
+            for _j in 0..3 {
+                let (_sk1, sk2, pk1, pk2) = BLS::k_gen(&bg);
+                let mj = format!("message {}", i);
+                s_bls.push(BLS::sign(&bg, &sk2, &mj));
+                pk1s.push(pk1);
+                pk2s.push(pk2);
+                mjs.push(mj);
+                m.push(pk1.into_affine());
+            }
+            ss.push(EQS::sign(&bg, &sk, &m));
+            ms.push(m);
+        }
+
+        //Check that the batch verifications return true for valid signatures
+        let b = EQS::verify_batch(&bg, &pk, &ms, &ss);
+        assert!(b, "Valid EQS batch verification failed");
+        let b = BLS::verify_batch(&bg, &pk1s, &pk2s, &mjs, &s_bls);
+        assert!(b, "Valid BLS batch verification failed");
+
+        //Check that the batch verifications return false if one of the signatures is invalid
+        ms.swap(0, 1);
+        let b = EQS::verify_batch(&bg, &pk, &ms, &ss);
+        assert!(!b, "Invalid EQS batch verification failed");
+        mjs[5] = "other message".to_string();
+        let b = BLS::verify_batch(&bg, &pk1s, &pk2s, &mjs, &s_bls);
+        assert!(!b, "Invalid BLS batch verification failed");
     }
 
     #[test]
     fn test_vrs() {
         let (p, q, g) = VRS::setup(2048);
-        let (_sk1, pk1) = VRS::kgen(&p, &q, &g);
-        let (sk2, pk2) = VRS::kgen(&p, &q, &g);
-        let (sk3, pk3) = VRS::kgen(&p, &q, &g);
+        let (_sk1, pk1) = VRS::kgen(&p, &q, &g, None);
+        let (sk2, pk2) = VRS::kgen(&p, &q, &g, None);
+        let (sk3, pk3) = VRS::kgen(&p, &q, &g, None);
         let ring = vec![pk1.clone(), pk2.clone(), pk3.clone()];
 
         let m = "message 1234".to_string();
-        let s = VRS::sign(&p, &q, &g, &sk3, &ring, &m);
+        let s = VRS::sign(&p, &q, &g, &sk3, &ring, &m, None);
 
         //Check that the verify algorithm returns true for a valid signature
-        let b = VRS::verify(&p, &q, &g, &ring, &m, &s);
+        let b = VRS::verify(&p, &q, &g, &ring, &m, &s, None);
         assert!(b, "Valid signature verification failed");
 
         //Check that the verify algorithm returns false for an invalid signature
-        let b = VRS::verify(&p, &q, &g, &ring, &"other message".to_string(), &s);
+        let b = VRS::verify(&p, &q, &g, &ring, "other message", &s, None);
         assert!(!b, "Invalid signature verification failed");
 
         //Check that the judge algorithm returns true for a proof generated by the original signer of the message
-        let pr = VRS::prove(&p, &q, &g, &ring, &m, &s, &pk3, &sk3);
-        let b = VRS::judge(&p, &q, &g, &ring, &m, &s, &pk3, &pr).unwrap();
+        let pr = VRS::prove(&p, &q, &g, &m, &s, &pk3, &sk3);
+        let b = VRS::judge(&p, &q, &g, &m, &s, &pk3, &pr).unwrap();
         assert!(b, "Judge the original signer failed");
 
         //Check that the judge algorithm returns false for a proof generated by a signer other than the original
         //signer of the message
-        let pr = VRS::prove(&p, &q, &g, &ring, &m, &s, &pk2, &sk2);
-        let b = VRS::judge(&p, &q, &g, &ring, &m, &s, &pk2, &pr).unwrap();
+        let pr = VRS::prove(&p, &q, &g, &m, &s, &pk2, &sk2);
+        let b = VRS::judge(&p, &q, &g, &m, &s, &pk2, &pr).unwrap();
         assert!(!b, "Judge not the original signer failed");
     }
 
+    #[test]
+    fn test_vrs_linkable() {
+        let (p, q, g) = VRS::setup(512);
+        let (_sk1, pk1) = VRS::kgen(&p, &q, &g, None);
+        let (sk2, pk2) = VRS::kgen(&p, &q, &g, None);
+        let (sk3, pk3) = VRS::kgen(&p, &q, &g, None);
+        let ring = vec![pk1.clone(), pk2.clone(), pk3.clone()];
+
+        let tag = "tag 1".to_string();
+        let m1 = "message 1".to_string();
+        let m2 = "message 2".to_string();
+        let h = VRS::link_base(&p, &q, &tag);
+        let s1 = VRS::sign_linkable(&q, &g, &sk3, &ring, &h, &m1, None).unwrap();
+        let s2 = VRS::sign_linkable(&q, &g, &sk3, &ring, &h, &m2, None).unwrap();
+
+        //Check that the verify algorithm returns true for a valid linkable signature
+        let b = VRS::verify_linkable(&q, &g, &ring, &h, &m1, &s1, None);
+        assert!(b, "Valid linkable signature verification failed");
+
+        //Check that the verify algorithm returns false for another message or another tag
+        let b = VRS::verify_linkable(&q, &g, &ring, &h, &m2, &s1, None);
+        assert!(!b, "Invalid linkable signature verification failed");
+        let b = VRS::verify_linkable(&q, &g, &ring, &VRS::link_base(&p, &q, &"tag 2".to_string()), &m1, &s1, None);
+        assert!(!b, "Linkable signature verification with another tag failed");
+
+        //Check that two signatures of the same key on the same tag are linked
+        assert!(s1.z == s2.z, "Linkable signatures on the same tag are not linked");
+        let s3 = VRS::sign_linkable(&q, &g, &sk2, &ring, &h, &m1, None).unwrap();
+        assert!(s1.z != s3.z, "Linkable signatures of different keys are linked");
+
+        //Check that two signatures on the same tag and different messages expose the signer
+        let i = VRS::extract(&q, &g, &ring, &s1, &s2);
+        assert!(i == Some(2), "Extraction of the signer failed");
+        let i = VRS::extract(&q, &g, &ring, &s1, &s3);
+        assert!(i.is_none(), "Extraction from unlinked signatures failed");
+
+        //Check that signing the same message again exposes nothing, and that the signer must be in the ring
+        let s4 = VRS::sign_linkable(&q, &g, &sk3, &ring, &h, &m1, None).unwrap();
+        assert!(s1.p.iter().zip(s4.p.iter()).all(|(a, b)| a.c == b.c && a.l == b.l), 
+            "Linkable signatures on the same message differ");
+        let i = VRS::extract(&q, &g, &ring, &s1, &s4);
+        assert!(i.is_none(), "Extraction from signatures on the same message succeeded");
+        let r = VRS::sign_linkable(&q, &g, &sk3, &ring[..2], &h, &m1, None);
+        assert!(r.is_err(), "Linkable signing outside the ring should fail");
+
+        //Check that the judge algorithm identifies the signer of a linkable signature
+        let pr = VRS::prove_linkable(&p, &q, &g, &tag, &pk3, &sk3);
+        let b = VRS::judge_linkable(&p, &q, &g, &tag, &s1, &pk3, &pr).unwrap();
+        assert!(b, "Judge the original linkable signer failed");
+        let pr = VRS::prove_linkable(&p, &q, &g, &tag, &pk2, &sk2);
+        let b = VRS::judge_linkable(&p, &q, &g, &tag, &s1, &pk2, &pr).unwrap();
+        assert!(!b, "Judge not the original linkable signer failed");
+    }
+
+    #[test]
+    fn test_fixed_base() {
+        let (p, q, g) = CHash::setup(512);
+        let fb_p = FixedBase::new(&g, &p, 5);
+        let fb_q = FixedBase::new(&g, &q, 4);
+        let mut rng = thread_rng();
+
+        //Check that the tables give the same result as modpow, including exponents larger than the modulus
+        for e in [BigInt::from(0), BigInt::from(1), &q - 1, &p * &q + 7] {
+            assert_eq!(fb_p.pow(&e), g.modpow(&e, &p), "Fixed-base exponentiation mod p failed");
+            assert_eq!(fb_q.pow(&e), g.modpow(&e, &q), "Fixed-base exponentiation mod q failed");
+        }
+        for _ in 0..20 {
+            let bytes: [u8; 32] = rng.gen();
+            let e = BigInt::from(BigUint::from_bytes_be(&bytes)) * &q;
+            assert_eq!(fb_p.pow(&e), g.modpow(&e, &p), "Fixed-base exponentiation mod p failed");
+            assert_eq!(fb_q.pow(&e), g.modpow(&e, &q), "Fixed-base exponentiation mod q failed");
+        }
+
+        //Check that CHash values computed with the table are checked without it and conversely
+        let (sk, pk) = CHash::k_gen(&p, &q, &g, Some(&fb_p));
+        let m = BigInt::from(10u32);
+        let (h, r) = CHash::hash(&p, &q, &g, &pk, &m, Some(&fb_p));
+        let b = CHash::check(&p, &g, &pk, &m, &r, &h, None);
+        assert!(b, "Valid hash check with fixed-base table failed");
+        let mp = BigInt::from(20u32);
+        let rp = CHash::adapt(&p, &q, &g, &sk, &m, &r, &mp);
+        let b = CHash::check(&p, &g, &pk, &mp, &rp, &h, Some(&fb_p));
+        assert!(b, "Valid adapted hash check with fixed-base table failed");
+
+        //Check that VRS signatures computed with the table are verified without it and conversely
+        let (_sk1, pk1) = VRS::kgen(&p, &q, &g, Some(&fb_q));
+        let (sk2, pk2) = VRS::kgen(&p, &q, &g, None);
+        let ring = vec![pk1.clone(), pk2.clone()];
+        let m = "message 1234".to_string();
+        let s = VRS::sign(&p, &q, &g, &sk2, &ring, &m, Some(&fb_q));
+        let b = VRS::verify(&p, &q, &g, &ring, &m, &s, None);
+        assert!(b, "Valid signature verification with fixed-base table failed");
+        let s = VRS::sign(&p, &q, &g, &sk2, &ring, &m, None);
+        let b = VRS::verify(&p, &q, &g, &ring, &m, &s, Some(&fb_q));
+        assert!(b, "Valid signature verification with fixed-base table failed");
+        let b = VRS::verify(&p, &q, &g, &ring, "other message", &s, Some(&fb_q));
+        assert!(!b, "Invalid signature verification with fixed-base table failed");
+    }
+
     #[test]
     fn test_mercurial() {
-        let mut bg = Mercurial::setup(3, &"k-SAN test".to_string());
-        let (mut sk, pk) = Mercurial::k_gen(&mut bg);
+        let bg = Mercurial::setup(3, &"k-SAN test".to_string());
+        let (mut sk, pk) = Mercurial::k_gen(&bg);
 
         let mut m = vec![bg.pp.p1.clone(), bg.pp.p1.clone(), bg.pp.p1.clone()];
-        let s = Mercurial::sign(&mut bg, &mut sk, &m);
+        let s = Mercurial::sign(&bg, &mut sk, &m);
         let mut s1 = s.clone();
 
         //Check that the verify algorithm returns true for a valid signature
-        let b = Mercurial::verify(&mut bg, &pk, &m, &s);
+        let b = Mercurial::verify(&bg, &pk, &m, &s);
         assert!(b, "Valid signature verification failed");
 
         //Check that the verify algorithm returns true for a valid signature after changing its representation
         let r = BigInt::from(50);
-        let (sp, mp) = Mercurial::chg_rep(&mut bg, &pk, &mut m, &mut s1, &r);
-        let b = Mercurial::verify(&mut bg, &pk, &mp, &sp);
+        let (sp, mp) = Mercurial::chg_rep(&bg, &pk, &mut m, &mut s1, &r);
+        let b = Mercurial::verify(&bg, &pk, &mp, &sp);
         assert!(b, "Valid signature verification after ChgRep failed");
 
         //Check that the verify algorithm returns false for an invalid signature
-        let b = Mercurial::verify(&mut bg, &pk, &mp, &s);
+        let b = Mercurial::verify(&bg, &pk, &mp, &s);
         assert!(!b, "Invalid signature verification failed");
     }
+
+    #[test]
+    fn test_merkle() {
+        let values: Vec<String> = ["draft", "submitted", "approved", "rejected", "archived"]
+            .iter().map(|v| v.to_string()).collect();
+        let root = Merkle::root(&values);
+
+        //Check that every value of the set has a valid membership proof
+        for v in values.iter() {
+            let p = Merkle::prove(&values, v).unwrap();
+            assert!(Merkle::verify(&root, v, &p), "Valid membership proof verification failed");
+        }
+
+        //Check that a value out of the set has no proof and cannot reuse the proof of another value
+        assert!(Merkle::prove(&values, &"deleted".to_string()).is_none(), "A value out of the set should have no proof");
+        let p = Merkle::prove(&values, &"approved".to_string()).unwrap();
+        assert!(!Merkle::verify(&root, &"deleted".to_string(), &p), "Invalid membership proof verification failed");
+        let mut p_false = p.clone();
+        p_false.index = 3;
+        assert!(!Merkle::verify(&root, &"approved".to_string(), &p_false), "A proof with a wrong index should fail");
+        let root_false = Merkle::root(&values[..4]);
+        assert!(!Merkle::verify(&root_false, &"approved".to_string(), &p), "A proof for another set should fail");
+    }
+
+    #[test]
+    fn test_shamir() {
+        let (_p, q, _g) = CHash::setup(512);
+        let secret = BigInt::from(thread_rng().gen::<u64>());
+        let shares = Shamir::share(&q, &secret, 3, 5);
+
+        //Check that any 3 shares give back the secret
+        for xs in [vec![1, 2, 3], vec![2, 4, 5], vec![5, 1, 3]] {
+            let ys: Vec<BigInt> = xs.iter().map(|x| shares[x - 1].clone()).collect();
+            assert_eq!(Shamir::combine(&q, &xs, &ys), secret, "3 shares should give back the secret");
+        }
+
+        //Check that 2 shares do not
+        let ys: Vec<BigInt> = vec![shares[0].clone(), shares[1].clone()];
+        assert_ne!(Shamir::combine(&q, &[1, 2], &ys), secret, "2 shares should not give back the secret");
+    }
 }
\ No newline at end of file
@@ -1,9 +1,10 @@
-use ark_std::iterable::Iterable;
 use num_bigint::{BigInt, RandBigInt, Sign};
 use glass_pumpkin::safe_prime;
 use rand::thread_rng;
 
 use crate::ksan::hash::hash;
+use super::fixed_base::FixedBase;
+use crate::par::{map_range, all_range};
 
 #[derive(Clone, Debug)]
 pub struct LogEqElement{
@@ -55,21 +56,21 @@ impl VRS {
         }
     }
 
-    pub fn kgen(_p: &BigInt, q: &BigInt, g: &BigInt) -> (BigInt, BigInt) {
+    pub fn kgen(_p: &BigInt, q: &BigInt, g: &BigInt, fb: Option<&FixedBase>) -> (BigInt, BigInt) {
         let mut rng = thread_rng();
         let min = BigInt::from(1);
         let sk = rng.gen_bigint_range(&min, &q);
-        let pk = g.modpow(&sk, &q);
+        let pk = FixedBase::pow_or(fb, g, &sk, q);
         return (sk, pk);
     }
 
-    pub fn sign(p: &BigInt, q: &BigInt, g: &BigInt, sk: &BigInt, ring: &Vec<BigInt>, m: &String) -> VRSSignature {
+    pub fn sign(p: &BigInt, q: &BigInt, g: &BigInt, sk: &BigInt, ring: &[BigInt], m: &str, fb: Option<&FixedBase>) -> VRSSignature {
         let mut rng = thread_rng();
         let min = BigInt::from(1);
         let r = rng.gen_bigint_range(&min, &q);
-        let h = Self::vrs_hash(&p, &q, &(m.clone() + r.to_str_radix(36).as_str()));
+        let h = Self::vrs_hash(p, q, &(m.to_owned() + r.to_str_radix(36).as_str()));
         let z = h.modpow(&sk, &q);
-        let pk = g.modpow(&sk, &q);
+        let pk = FixedBase::pow_or(fb, g, sk, q);
         let mut j = 0;
         for i in 0..ring.len() {
             if ring[i] == pk {
@@ -82,40 +83,48 @@ impl VRS {
             z: z.clone(),
             g: g.clone(),
             y: x.clone()
-        }).collect(), &sk, j);
+        }).collect::<Vec<_>>(), sk, j, fb);
         return VRSSignature{r: r, z: z, p: p};
     }
 
-    pub fn verify(p: &BigInt, q: &BigInt, g: &BigInt, ring: &Vec<BigInt>, m: &String, s: &VRSSignature) -> bool {
-        let h = Self::vrs_hash(&p, &q, &(m.clone() + s.r.to_str_radix(36).as_str()));
+    pub fn verify(p: &BigInt, q: &BigInt, g: &BigInt, ring: &[BigInt], m: &str, s: &VRSSignature, fb: Option<&FixedBase>) -> bool {
+        let h = Self::vrs_hash(p, q, &(m.to_owned() + s.r.to_str_radix(36).as_str()));
         return Self::le_verif(&q, &ring.iter().map(|x| LogEqElement{
             h: h.clone(),
             z: s.z.clone(),
             g: g.clone(),
             y: x.clone()
-        }).collect(), &s.p);
+        }).collect::<Vec<_>>(), &s.p, fb);
     }
 
-    pub fn prove(p: &BigInt, q: &BigInt, g: &BigInt, _ring: &Vec<BigInt>, m: &String, s: &VRSSignature, pk: &BigInt, sk: &BigInt) -> VRSProof {
-        let h = Self::vrs_hash(&p, &q, &(m.clone() + s.r.to_str_radix(36).as_str()));
+    pub fn prove(p: &BigInt, q: &BigInt, g: &BigInt, m: &str, s: &VRSSignature, pk: &BigInt, sk: &BigInt) -> VRSProof {
+        let h = Self::vrs_hash(p, q, &(m.to_owned() + s.r.to_str_radix(36).as_str()));
+        Self::prove_base(&h, q, g, pk, sk)
+    }
+
+    pub fn judge(p: &BigInt, q: &BigInt, g: &BigInt, m: &str, s: &VRSSignature, pk: &BigInt, pr: &VRSProof) -> Result<bool, String> {
+        let h = Self::vrs_hash(p, q, &(m.to_owned() + s.r.to_str_radix(36).as_str()));
+        Self::judge_base(&h, q, g, s, pk, pr)
+    }
+
+    fn prove_base(h: &BigInt, q: &BigInt, g: &BigInt, pk: &BigInt, sk: &BigInt) -> VRSProof {
         let z = h.modpow(&sk, &q);
-        let p = Self::le_prove(&q, &vec![LogEqElement{
+        let p = Self::le_prove(q, &[LogEqElement{
             h: h.clone(),
             z: z.clone(),
             g: g.clone(),
             y: pk.clone()
-        }], &sk, 0);
+        }], sk, 0, None);
         return VRSProof{z: z, p: p};
     }
 
-    pub fn judge(p: &BigInt, q: &BigInt, g: &BigInt, _ring: &Vec<BigInt>, m: &String, s: &VRSSignature, pk: &BigInt, pr: &VRSProof) -> Result<bool, String> {
-        let h = Self::vrs_hash(&p, &q, &(m.clone() + s.r.to_str_radix(36).as_str()));
+    fn judge_base(h: &BigInt, q: &BigInt, g: &BigInt, s: &VRSSignature, pk: &BigInt, pr: &VRSProof) -> Result<bool, String> {
         let b = Self::le_verif(&q, &vec![LogEqElement{
             h: h.clone(),
             z: pr.z.clone(),
             g: g.clone(),
             y: pk.clone()
-        }], &pr.p);
+        }], &pr.p, None);
         if !b {
             return Err("Verification failed".to_string());
         }
@@ -125,66 +134,189 @@ impl VRS {
         return Ok(true);
     }
 
-    fn le_prove(q: &BigInt, d: &Vec<LogEqElement>, x: &BigInt, j: usize) -> Vec<LogEqProof> {
-        let mut rng = thread_rng();
+    //Base H(tag) of the linkable signatures on tag.
+    pub fn link_base(p: &BigInt, q: &BigInt, tag: &String) -> BigInt {
+        Self::vrs_hash(p, q, tag)
+    }
+
+    //Linkable variant of sign: the base h = link_base(tag) is hashed from the tag only, so z = h^sk is the same for
+    //all the signatures of sk on the tag, and m is bound through the challenge. The nonce of the signer's proof is
+    //derived from sk and h, so two signatures of the same key on the same tag and different messages expose the
+    //secret key (extract). The simulated proofs of the other members are derived from sk, h, the ring and m, so
+    //signing the same message again gives the same signature. Fails if pk is not in the ring.
+    pub fn sign_linkable(
+        q: &BigInt, g: &BigInt, sk: &BigInt, ring: &[BigInt], h: &BigInt, m: &str, fb: Option<&FixedBase>
+    ) -> Result<VRSSignature, String> {
+        let z = h.modpow(sk, q);
+        let pk = FixedBase::pow_or(fb, g, sk, q);
+        let j = match ring.iter().position(|x| *x == pk) {
+            Some(j) => j,
+            None => return Err("The signer is not in the ring".to_string()),
+        };
+        let tag = h.to_str_radix(36);
+        let rand = Self::nonce(q, sk, &tag);
+        let mut seed = tag;
+        for y in ring.iter() {
+            seed.push(':');
+            seed.push_str(y.to_str_radix(36).as_str());
+        }
+        seed.push(':');
+        seed.push_str(m);
+        let p = Self::le_prove_with(q, &ring.iter().map(|x| LogEqElement{
+            h: h.clone(),
+            z: z.clone(),
+            g: g.clone(),
+            y: x.clone()
+        }).collect::<Vec<_>>(), sk, j, fb, Some((&rand, &seed)), m);
+        Ok(VRSSignature{r: BigInt::from(0), z, p})
+    }
+
+    pub fn verify_linkable(
+        q: &BigInt, g: &BigInt, ring: &[BigInt], h: &BigInt, m: &str, s: &VRSSignature, fb: Option<&FixedBase>
+    ) -> bool {
+        Self::le_verif_with(q, &ring.iter().map(|x| LogEqElement{
+            h: h.clone(),
+            z: s.z.clone(),
+            g: g.clone(),
+            y: x.clone()
+        }).collect::<Vec<_>>(), &s.p, fb, m)
+    }
+
+    //Linking value z of the linkable signatures of sk on tag.
+    pub fn link(p: &BigInt, q: &BigInt, sk: &BigInt, tag: &String) -> BigInt {
+        Self::vrs_hash(p, q, tag).modpow(sk, q)
+    }
+
+    //Same as prove and judge for a linkable signature on tag.
+    pub fn prove_linkable(p: &BigInt, q: &BigInt, g: &BigInt, tag: &String, pk: &BigInt, sk: &BigInt) -> VRSProof {
+        Self::prove_base(&Self::vrs_hash(p, q, tag), q, g, pk, sk)
+    }
+
+    pub fn judge_linkable(
+        p: &BigInt, q: &BigInt, g: &BigInt, tag: &String, s: &VRSSignature, pk: &BigInt, pr: &VRSProof
+    ) -> Result<bool, String> {
+        Self::judge_base(&Self::vrs_hash(p, q, tag), q, g, s, pk, pr)
+    }
+
+    //Index in ring of the signer of two linkable signatures on the same tag and different messages, found by
+    //recovering its secret key from the two responses of its proofs.
+    pub fn extract(q: &BigInt, g: &BigInt, ring: &[BigInt], s1: &VRSSignature, s2: &VRSSignature) -> Option<usize> {
+        let zero = BigInt::from(0);
+        if s1.z != s2.z || s1.p.len() != ring.len() || s2.p.len() != ring.len() {
+            return None;
+        }
+        for (i, pk) in ring.iter().enumerate() {
+            let dc = &s1.p[i].c - &s2.p[i].c;
+            let dl = &s1.p[i].l - &s2.p[i].l;
+            if dc == zero || &dl % &dc != zero {
+                continue;
+            }
+            let x = dl / dc;
+            if x > zero && g.modpow(&x, q) == *pk {
+                return Some(i);
+            }
+        }
+        None
+    }
+
+    //Nonce of the proofs of sk on tag, SHA-256 in counter mode reduced into [1, q-1].
+    fn nonce(q: &BigInt, sk: &BigInt, tag: &str) -> BigInt {
+        let mut n = BigInt::from(0);
+        let mut c = 0u32;
+        while n.bits() < q.bits() + 64 {
+            n = (n << 256) + hash(&(c.to_string() + ":" + sk.to_str_radix(36).as_str() + ":" + tag));
+            c += 1;
+        }
+        n % (q - 1u8) + 1u8
+    }
+
+    fn le_prove(q: &BigInt, d: &[LogEqElement], x: &BigInt, j: usize, fb: Option<&FixedBase>) -> Vec<LogEqProof> {
+        Self::le_prove_with(q, d, x, j, fb, None, "")
+    }
+
+    //Proof for the member j, whose challenge also covers ctx. With nonce = (rand, seed), rand is the nonce of the
+    //member j and the simulated proofs of the other members are derived from x and seed, otherwise all are random.
+    fn le_prove_with(
+        q: &BigInt, d: &[LogEqElement], x: &BigInt, j: usize, fb: Option<&FixedBase>, nonce: Option<(&BigInt, &str)>,
+        ctx: &str
+    ) -> Vec<LogEqProof> {
         let min = BigInt::from(1);
-        let mut pr: Vec<LogEqProof> = Vec::with_capacity(d.len());
-        let mut cp = BigInt::from(1);
-        let rand = rng.gen_bigint_range(&min, &q);
-        for (i, v) in d.iter().enumerate() {
+        let rand = match nonce {
+            Some((rand, _)) => rand.clone(),
+            None => thread_rng().gen_bigint_range(&min, q),
+        };
+        let mut pr: Vec<LogEqProof> = map_range(d.len(), |i| {
+            let v = &d[i];
             if j == i {
-                let r = v.g.modpow(&rand, &q);
+                let r = FixedBase::pow_or(fb, &v.g, &rand, q);
                 let s = v.h.modpow(&rand, &q);
-                pr.push(LogEqProof{
+                return LogEqProof{
                     r: r,
                     s: s,
                     c: BigInt::from(0),
                     l: BigInt::from(0)
-                });
-            } else {
-                let c = rng.gen_bigint_range(&min, &q);
-                let l = rng.gen_bigint_range(&min, &q);
-                let r = (v.g.modpow(&l, &q) * v.y.modpow(&c, &q).modinv(&q).unwrap()) % q;
-                let s = (v.h.modpow(&l, &q) * v.z.modpow(&c, &q).modinv(&q).unwrap()) % q;
-                pr.push(LogEqProof{
-                    r: r,
-                    s: s,
-                    c: c.clone(),
-                    l: l
-                });
-                cp = (cp * c) % q;
+                };
+            }
+            let (c, l) = match nonce {
+                Some((_, seed)) => (
+                    Self::nonce(q, x, &format!("{}:c{}", seed, i)), Self::nonce(q, x, &format!("{}:l{}", seed, i))
+                ),
+                None => {
+                    let mut rng = thread_rng();
+                    (rng.gen_bigint_range(&min, q), rng.gen_bigint_range(&min, q))
+                },
+            };
+            let r = (FixedBase::pow_or(fb, &v.g, &l, q) * v.y.modpow(&c, q).modinv(q).unwrap()) % q;
+            let s = (v.h.modpow(&l, q) * v.z.modpow(&c, q).modinv(q).unwrap()) % q;
+            LogEqProof{
+                r,
+                s,
+                c,
+                l
+            }
+        });
+        let mut cp = BigInt::from(1);
+        for (i, pi) in pr.iter().enumerate() {
+            if i != j {
+                cp = (cp * &pi.c) % q;
             }
         }
-        let c = Self::le_hash(&q, &pr);
+        let c = Self::le_hash(ctx, &pr);
         pr[j].c = (c * cp.modinv(&q).unwrap()) % q;
         pr[j].l = rand.clone() + (pr[j].c.clone() * x);
         return pr;
     }
 
-    fn le_verif(q: &BigInt, d: &Vec<LogEqElement>, pr: &Vec<LogEqProof>) -> bool {
+    fn le_verif(q: &BigInt, d: &[LogEqElement], pr: &Vec<LogEqProof>, fb: Option<&FixedBase>) -> bool {
+        Self::le_verif_with(q, d, pr, fb, "")
+    }
+
+    fn le_verif_with(q: &BigInt, d: &[LogEqElement], pr: &Vec<LogEqProof>, fb: Option<&FixedBase>, ctx: &str) -> bool {
+        if pr.len() != d.len() {
+            return false;
+        }
+        let b = all_range(pr.len(), |i| {
+            FixedBase::pow_or(fb, &d[i].g, &pr[i].l, q) == 
+                    ((pr[i].r.clone() * d[i].y.modpow(&pr[i].c, q)) % q)
+                && d[i].h.modpow(&pr[i].l, q) == 
+                    ((pr[i].s.clone() * d[i].z.modpow(&pr[i].c, q)) % q)
+        });
+        if !b {
+            return false;
+        }
         let mut cp = BigInt::from(1);
         for i in 0..pr.len() {
-            if  (
-                    d[i].g.modpow(&pr[i].l, &q) != 
-                    ((pr[i].r.clone() * d[i].y.modpow(&pr[i].c, &q)) % q)
-                ) || (
-                    d[i].h.modpow(&pr[i].l, &q) != 
-                    ((pr[i].s.clone() * d[i].z.modpow(&pr[i].c, &q)) % q)
-                ) 
-            {
-                return false;
-            }
             cp = (cp * pr[i].c.clone()) % q;
         }
-        let c = Self::le_hash(&q, &pr);
+        let c = Self::le_hash(ctx, pr);
         if cp != c {
             return false;
         }
         return true;
     }
 
-    fn le_hash(_p: &BigInt, pr: &Vec<LogEqProof>) -> BigInt {
-        let mut m = String::new();
+    fn le_hash(ctx: &str, pr: &Vec<LogEqProof>) -> BigInt {
+        let mut m = ctx.to_owned();
         for v in pr {
             m.push_str(&v.r.to_string());
             m.push_str(&v.s.to_string());
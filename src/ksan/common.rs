@@ -0,0 +1,135 @@
+use crate::blocks::merkle::{Merkle, MerkleProof};
+
+//Operations that the admissible sanitizers of a block may perform on it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Policy{
+    pub modify: bool,
+    pub redact: bool
+}
+
+//Merkle root of the values allowed for a block, bound into the signature, and membership proof of its value.
+#[derive(Clone, Debug)]
+pub struct ValueSet{
+    pub root: Vec<u8>,
+    pub membership: MerkleProof
+}
+
+//Times at which a sanitizer may sanitize: from <= t <= until, in the unit of the caller's clock.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Window{
+    pub from: u64,
+    pub until: u64
+}
+
+//Sanitizers (indexes in san_pks) of a threshold block, t of which must cooperate to modify it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Group{
+    pub t: usize,
+    pub members: Vec<usize>
+}
+
+//Value sets of a new signature on m: Merkle root of values[j] and membership proof of m[j].
+pub fn value_sets(m: &[String], values: &[Option<Vec<String>>]) -> Result<Vec<Option<ValueSet>>, String> {
+    if values.len() != m.len() {
+        return Err("values must have one element per block".to_string());
+    }
+    let mut value_sets: Vec<Option<ValueSet>> = Vec::with_capacity(m.len());
+    for j in 0..m.len() {
+        value_sets.push(match &values[j] {
+            Some(set) => match Merkle::prove(set, &m[j]) {
+                Some(p) => Some(ValueSet { root: Merkle::root(set), membership: p }),
+                None => return Err("The value of a block is not in its value set".to_string()),
+            },
+            None => None,
+        });
+    }
+    Ok(value_sets)
+}
+
+//Value sets of a sanitized signature with the value sets value_sets: the membership proofs of the modified blocks
+//are computed again from the sets given by the sanitizer, which must match the roots.
+pub fn update_value_sets(
+    value_sets: &[Option<ValueSet>], m: &[String], mp: &[String], values: &[Option<Vec<String>>]
+) -> Result<Vec<Option<ValueSet>>, String> {
+    if value_sets.len() != m.len() {
+        return Err("The signature does not match the length of m".to_string());
+    }
+    let mut value_sets = value_sets.to_vec();
+    for j in 0..m.len() {
+        let vs = match &mut value_sets[j] {
+            Some(vs) if mp[j] != m[j] => vs,
+            _ => continue,
+        };
+        let set = match values.get(j) {
+            Some(Some(set)) => set,
+            _ => return Err("The value set of a modified block is missing".to_string()),
+        };
+        if Merkle::root(set) != vs.root {
+            return Err("The value set does not match the signature".to_string());
+        }
+        vs.membership = match Merkle::prove(set, &mp[j]) {
+            Some(p) => p,
+            None => return Err("The modification is not in the value set of the block".to_string()),
+        };
+    }
+    Ok(value_sets)
+}
+
+//Policy of the signatures without one: the blocks may be modified and not redacted.
+pub fn default_policy(n: usize) -> Vec<Policy> {
+    vec![Policy { modify: true, redact: false }; n]
+}
+
+//Signed input of block j: j || m_j, or a redaction or unfilled slot marker that does not start with a digit.
+pub fn block_input(j: usize, mj: &str, redacted: bool, unfilled: bool) -> String {
+    if redacted {
+        return "redacted:".to_string() + j.to_string().as_str();
+    }
+    if unfilled {
+        return "slot:".to_string() + j.to_string().as_str();
+    }
+    j.to_string() + mj
+}
+
+//adm of a new signature on l blocks for k sanitizers: one row per sanitizer, one element per block.
+pub fn check_adm(adm: &[Vec<bool>], k: usize, l: usize) -> Result<(), String> {
+    if adm.len() != k || adm.iter().any(|a| a.len() != l) {
+        return Err("adm must have one row per sanitizer and one element per block".to_string());
+    }
+    Ok(())
+}
+
+//Windows of a new signature for k sanitizers: one per sanitizer, none ending before it starts.
+pub fn check_windows(windows: &[Option<Window>], k: usize) -> Result<(), String> {
+    if windows.len() != k {
+        return Err("windows must have one element per sanitizer".to_string());
+    }
+    if windows.iter().flatten().any(|w| w.from > w.until) {
+        return Err("A window must not end before it starts".to_string());
+    }
+    Ok(())
+}
+
+pub fn in_window(w: &Option<Window>, t: u64) -> bool {
+    match w {
+        Some(w) => w.from <= t && t <= w.until,
+        None => true,
+    }
+}
+
+//Groups of a new signature for k sanitizers: thresholds between 1 and the size of the group, distinct members.
+pub fn check_groups(groups: &[Option<Group>], k: usize) -> Result<(), String> {
+    for g in groups.iter().flatten() {
+        if g.t == 0 || g.t > g.members.len() {
+            return Err("The threshold of a group must be between 1 and its size".to_string());
+        }
+        if !members_valid(&g.members, k) {
+            return Err("The members of a group must be distinct sanitizers of san_pks".to_string());
+        }
+    }
+    Ok(())
+}
+
+pub fn members_valid(members: &[usize], k: usize) -> bool {
+    members.iter().enumerate().all(|(x, i)| *i < k && !members[..x].contains(i))
+}
@@ -1,5 +1,6 @@
-use kzen_paillier::RawCiphertext;
-use num_bigint::BigInt;
+use num_bigint::{BigInt, RandBigInt};
+use num_integer::Integer;
+use rand::{thread_rng, Rng};
 use curv::arithmetic::traits::Converter;
 
 use crate::ksan::fsv::params::*;
@@ -7,8 +8,36 @@ use crate::blocks::pke::*;
 use crate::blocks::sig::*;
 use crate::blocks::chash::*;
 use crate::blocks::vrs::*;
+use crate::blocks::fixed_base::FixedBase;
+use crate::blocks::hybrid::*;
+use crate::blocks::merkle::Merkle;
+use crate::ksan::common::{value_sets, update_value_sets, default_policy, block_input, check_windows, in_window,
+    check_groups, members_valid, check_adm};
+use crate::blocks::shamir::Shamir;
 use crate::ksan::hash::hash;
 use crate::ksan::hash::encode;
+use crate::par::{map_range, all_range};
+use crate::ksan::size::*;
+
+//New randomness (r, s) of each threshold block adapted by a threshold sanitization.
+type Adaptations = Vec<(usize, (BigInt, BigInt))>;
+
+//Byte length of the seeds of the trapdoors in compact signatures.
+const SEED_LEN: usize = 32;
+
+//Values of a signature covered by the Schnorr signature of the signer (KSan::generate_ms).
+struct Signed<'a> {
+    hashes: &'a [CHashPubValues],
+    secrets: &'a [Vec<Ciphertext>],
+    hybrid: Option<&'a Vec<HybridCiphertext>>,
+    pub_adm: &'a [bool],
+    value_sets: &'a [Option<ValueSet>],
+    policy: &'a [Policy],
+    slots: &'a [usize],
+    limits: &'a [Option<usize>],
+    windows: &'a [Option<Window>],
+    thresholds: &'a [Option<Threshold>]
+}
 
 pub struct KSan;
 
@@ -26,13 +55,25 @@ impl KSan {
         }
         let pp = PublicParams {
             secp: secp.clone(),
-            p: p,
-            q: q,
-            g: g
+            p,
+            q,
+            g,
+            fb_chash: None,
+            fb_vrs: None
         };
         return Ok(pp);
     }
 
+    //Builds the fixed-base tables for g mod p (CHash) and g mod q (VRS), with windows of w bits.
+    pub fn precompute(pp: &mut PublicParams, w: usize) -> Result<(), String> {
+        if w == 0 || w > 16 {
+            return Err("Window size must be between 1 and 16".to_string());
+        }
+        pp.fb_chash = Some(FixedBase::new(&pp.g, &pp.p, w));
+        pp.fb_vrs = Some(FixedBase::new(&pp.g, &pp.q, w));
+        Ok(())
+    }
+
     pub fn kgen_s(_pp: &PublicParams) -> Result<(SignerSecretKey, SignerPublicKey), String> {
         let (sk, pk) = SIG::k_gen();
         let sk_s = SignerSecretKey {
@@ -47,159 +88,1069 @@ impl KSan {
     pub fn kgen_z(pp: &PublicParams) -> Result<(SanitizerSecretKey, SanitizerPublicKey), String> {
         //let (pke, ske) = PKE::k_gen(&pp.kp);
         let (ske, pke) = PKE::k_gen(pp.secp.bits_pke);
-        let (skp, pkp) = VRS::kgen(&pp.p, &pp.q, &pp.g);
+        let (skp, pkp) = VRS::kgen(&pp.p, &pp.q, &pp.g, pp.fb_vrs.as_ref());
+        let (skk, pkk) = Hybrid::k_gen();
         let sk_z = SanitizerSecretKey {
-            ske: ske,
-            skp: skp
+            ske,
+            skp,
+            skk
         };
         let pk_z = SanitizerPublicKey {
-            pke: pke,
-            pkp: pkp
+            pke,
+            pkp,
+            pkk
         };
         return Ok((sk_z, pk_z));
     }
 
-    pub fn sign<'d>(
+    pub fn sign(
+            pp: &PublicParams, sk_s: &SignerSecretKey, pk_s: &SignerPublicKey,
+            san_pks: &[SanitizerPublicKey], m: &[String], adm: &[Vec<bool>]
+        ) -> Result<Signature, String> {
+        let (sig, _td) = Self::sign_with_trapdoors(pp, sk_s, pk_s, san_pks, m, adm)?;
+        Ok(sig)
+    }
+
+    //Same as sign, also returns the chameleon trapdoors and adm, kept by the signer for extend_sanitizers.
+    pub fn sign_with_trapdoors(
+            pp: &PublicParams, sk_s: &SignerSecretKey, pk_s: &SignerPublicKey,
+            san_pks: &[SanitizerPublicKey], m: &[String], adm: &[Vec<bool>]
+        ) -> Result<(Signature, Trapdoors), String> {
+        Self::sign_full(pp, sk_s, pk_s, san_pks, m, adm, &SignOptions::new(m.len()))
+    }
+
+    //Same as sign_with_trapdoors, with any combination of the options below.
+    pub fn sign_with_options(
+            pp: &PublicParams, sk_s: &SignerSecretKey, pk_s: &SignerPublicKey,
+            san_pks: &[SanitizerPublicKey], m: &[String], adm: &[Vec<bool>], opts: &SignOptions
+        ) -> Result<(Signature, Trapdoors), String> {
+        Self::sign_full(pp, sk_s, pk_s, san_pks, m, adm, opts)
+    }
+
+    //Same as sign, with slots[i] empty blocks appended to m for san_pks[i] to fill (append).
+    pub fn sign_with_slots(
+            pp: &PublicParams, sk_s: &SignerSecretKey, pk_s: &SignerPublicKey,
+            san_pks: &[SanitizerPublicKey], m: &[String], adm: &[Vec<bool>], slots: &[usize]
+        ) -> Result<Signature, String> {
+        let opts = SignOptions { slots: slots.to_vec(), ..SignOptions::new(m.len()) };
+        let (sig, _td) = Self::sign_full(pp, sk_s, pk_s, san_pks, m, adm, &opts)?;
+        Ok(sig)
+    }
+
+    //Same as sign, policy[j] says whether the admissible sanitizers of block j may modify it, redact it or both.
+    pub fn sign_with_policy(
+            pp: &PublicParams, sk_s: &SignerSecretKey, pk_s: &SignerPublicKey,
+            san_pks: &[SanitizerPublicKey], m: &[String], adm: &[Vec<bool>], policy: &[Policy]
+        ) -> Result<Signature, String> {
+        let opts = SignOptions { policy: policy.to_vec(), ..SignOptions::new(m.len()) };
+        let (sig, _td) = Self::sign_full(pp, sk_s, pk_s, san_pks, m, adm, &opts)?;
+        Ok(sig)
+    }
+
+    //Same as sign, values[j] is the set of the values allowed for block j (None for any value).
+    pub fn sign_with_values(
+            pp: &PublicParams, sk_s: &SignerSecretKey, pk_s: &SignerPublicKey,
+            san_pks: &[SanitizerPublicKey], m: &[String], adm: &[Vec<bool>], values: &[Option<Vec<String>>]
+        ) -> Result<Signature, String> {
+        let opts = SignOptions { values: values.to_vec(), ..SignOptions::new(m.len()) };
+        let (sig, _td) = Self::sign_full(pp, sk_s, pk_s, san_pks, m, adm, &opts)?;
+        Ok(sig)
+    }
+
+    //Same as sign, limits[j] is the number of times each sanitizer may sanitize block j (None for no limit).
+    pub fn sign_with_limits(
+            pp: &PublicParams, sk_s: &SignerSecretKey, pk_s: &SignerPublicKey,
+            san_pks: &[SanitizerPublicKey], m: &[String], adm: &[Vec<bool>], limits: &[Option<usize>]
+        ) -> Result<Signature, String> {
+        let opts = SignOptions { limits: limits.to_vec(), ..SignOptions::new(m.len()) };
+        let (sig, _td) = Self::sign_full(pp, sk_s, pk_s, san_pks, m, adm, &opts)?;
+        Ok(sig)
+    }
+
+    //Same as sign, windows[i] is the validity window of the rights of san_pks[i] (None for no window).
+    pub fn sign_with_windows(
+            pp: &PublicParams, sk_s: &SignerSecretKey, pk_s: &SignerPublicKey,
+            san_pks: &[SanitizerPublicKey], m: &[String], adm: &[Vec<bool>], windows: &[Option<Window>]
+        ) -> Result<Signature, String> {
+        check_windows(windows, san_pks.len())?;
+        let opts = SignOptions { windows: windows.to_vec(), ..SignOptions::new(m.len()) };
+        let (sig, _td) = Self::sign_full(pp, sk_s, pk_s, san_pks, m, adm, &opts)?;
+        Ok(sig)
+    }
+
+    //Same as sign, groups[j] is the group of sanitizers t of which must cooperate to modify block j, if any.
+    pub fn sign_with_thresholds(
+            pp: &PublicParams, sk_s: &SignerSecretKey, pk_s: &SignerPublicKey,
+            san_pks: &[SanitizerPublicKey], m: &[String], adm: &[Vec<bool>], groups: &[Option<Group>]
+        ) -> Result<Signature, String> {
+        let opts = SignOptions { groups: groups.to_vec(), ..SignOptions::new(m.len()) };
+        let (sig, _td) = Self::sign_full(pp, sk_s, pk_s, san_pks, m, adm, &opts)?;
+        Ok(sig)
+    }
+
+    fn sign_full(
             pp: &PublicParams, sk_s: &SignerSecretKey, pk_s: &SignerPublicKey,
-            san_pks: &Vec<SanitizerPublicKey>, m: &Vec<String>, adm: &Vec<Vec<bool>>
-        ) -> Result<Signature<'d>, String> {
+            san_pks: &[SanitizerPublicKey], m: &[String], adm: &[Vec<bool>], opts: &SignOptions
+        ) -> Result<(Signature, Trapdoors), String> {
         let k = san_pks.len();
-        let n = m.len();
+        let l = m.len();
+        check_adm(adm, k, l)?;
+        let value_sets = value_sets(m, &opts.values)?;
+        if opts.policy.len() != l {
+            return Err("policy must have one element per block".to_string());
+        }
+        if !opts.slots.is_empty() && opts.slots.len() != k {
+            return Err("slots must have one element per sanitizer".to_string());
+        }
+        if opts.limits.len() != l {
+            return Err("limits must have one element per block".to_string());
+        }
+        if opts.limits.contains(&Some(0)) {
+            return Err("A limit must be at least 1".to_string());
+        }
+        if !opts.windows.is_empty() {
+            check_windows(&opts.windows, k)?;
+        }
+        if opts.groups.len() != l {
+            return Err("groups must have one element per block".to_string());
+        }
+        check_groups(&opts.groups, k)?;
+        let owners: Vec<usize> = opts.slots.iter().enumerate().flat_map(|(i, c)| vec![i; *c]).collect();
+        let n = l + owners.len();
+        let m: Vec<String> = m.iter().cloned().chain(vec![String::new(); owners.len()]).collect();
+        let mut policy = opts.policy.clone();
+        policy.extend(vec![Policy { modify: true, redact: false }; owners.len()]);
+        let mut value_sets = value_sets;
+        value_sets.extend(vec![None; owners.len()]);
+        let mut limits = opts.limits.clone();
+        limits.extend(vec![None; owners.len()]);
+        let mut groups = opts.groups.clone();
+        groups.extend(vec![None; owners.len()]);
+        let adm: Vec<Vec<bool>> = adm.iter().enumerate()
+            .map(|(i, a)| (0..n).map(|j| if j < l {
+                a[j] && groups[j].is_none() && (policy[j].modify || policy[j].redact)
+            } else {
+                owners[j - l] == i
+            }).collect())
+            .collect();
         let mut hashes: Vec<CHashPubValues> = Vec::with_capacity(n);
         let zero = BigInt::from(0);
-        let mut secrets: Vec<Vec<RawCiphertext<'d>>> = vec![Vec::with_capacity(n); k];
-        let mut proofs: Vec<Proof> = Vec::with_capacity(n);
+        let mut secrets: Vec<Vec<Ciphertext>> = vec![Vec::with_capacity(n); k];
+        let mut skchs: Vec<BigInt> = Vec::with_capacity(n);
         let mut pub_adm: Vec<bool> = Vec::with_capacity(n);
-        for j in 0..n {
-            let (skch, pkch) = CHash::k_gen(&pp.p, &pp.q, &pp.g);
-            let mj = j.to_string() + m[j].as_str();
+        let mut thresholds: Vec<Option<Threshold>> = Vec::with_capacity(n);
+        let blocks: Vec<(CHashPubValues, Vec<Ciphertext>, BigInt, Option<Threshold>)> = map_range(n, |j| {
+            let (skch, pkch) = CHash::k_gen(&pp.p, &pp.q, &pp.g, pp.fb_chash.as_ref());
+            let mj = block_input(j, &m[j], false, j >= l);
+            let cs = map_range(k, |i| {
+                if adm[i][j] {
+                    return PKE::encrypt(&san_pks[i].pke, &skch);
+                }
+                PKE::encrypt(&san_pks[i].pke, &zero)
+            });
+            //Two adaptations of the discrete logarithm chameleon hash give its trapdoor, which would undo the
+            //threshold after the first threshold sanitization: threshold blocks use the key-exposure-free hash.
+            let (h, r, th) = match &groups[j] {
+                Some(g) => {
+                    let (h, r, s) = CHash::hash_kef(&pp.p, &pp.q, &pp.g, &pkch, &hash(&mj));
+                    let shares = Shamir::share(&pp.q, &skch, g.t, g.members.len());
+                    let th = Threshold {
+                        group: g.clone(),
+                        shares: g.members.iter().zip(shares.iter()).map(|(i, y)| PKE::encrypt(&san_pks[*i].pke, y)).collect(),
+                        s
+                    };
+                    (h, r, Some(th))
+                },
+                None => {
+                    let (h, r) = CHash::hash(&pp.p, &pp.q, &pp.g, &pkch, &hash(&mj), pp.fb_chash.as_ref());
+                    (h, r, None)
+                },
+            };
+            (CHashPubValues { h, r, pkch }, cs, skch, th)
+        });
+        for (j, (hv, cs, skch, th)) in blocks.into_iter().enumerate() {
+            hashes.push(hv);
+            for (i, c) in cs.into_iter().enumerate() {
+                secrets[i].push(c);
+            }
+            skchs.push(skch);
+            pub_adm.push(j < l && ((0..k).any(|i| adm[i][j]) || th.is_some()) && policy[j].modify);
+            thresholds.push(th);
+        }
+        let v = Signed {
+            hashes: &hashes,
+            secrets: &secrets,
+            hybrid: None,
+            pub_adm: &pub_adm,
+            value_sets: &value_sets,
+            policy: &policy,
+            slots: &owners,
+            limits: &limits,
+            windows: &opts.windows,
+            thresholds: &thresholds
+        };
+        let sig = Self::sign_blocks(sk_s, pk_s, san_pks, &m, &v);
+        let td = Trapdoors {
+            skch: skchs,
+            adm
+        };
+        Ok((sig, td))
+    }
+
+    //Grants the sanitizer ext.pk_z the blocks of ext.adm_z on a signature that has not been sanitized yet, without
+    //computing new chameleon hashes: the trapdoors are encrypted under pk_z and the signature is computed again
+    //for the sanitizers san_pks followed by pk_z. Compact signatures are not supported.
+    pub fn extend_sanitizers(
+        pp: &PublicParams, sk_s: &SignerSecretKey, pk_s: &SignerPublicKey, san_pks: &[SanitizerPublicKey],
+        m: &[String], sig: &Signature, ext: &Extension
+    ) -> Result<(Signature, Trapdoors), String> {
+        let Extension { pk_z, adm_z, td } = ext;
+        let n = m.len();
+        Self::check_trapdoors(pp, san_pks, m, sig, td)?;
+        if adm_z.len() != n {
+            return Err("adm_z must have one element per block".to_string());
+        }
+        if san_pks.iter().any(|x| x.pkp == pk_z.pkp) {
+            return Err("The sanitizer is already in san_pks".to_string());
+        }
+        let zero = BigInt::from(0);
+        let adm_z: Vec<bool> = (0..n).map(|j| adm_z[j] && (sig.policy[j].modify || sig.policy[j].redact)).collect();
+        let cs = map_range(n, |j| {
+            if adm_z[j] {
+                return PKE::encrypt(&pk_z.pke, &td.skch[j]);
+            }
+            PKE::encrypt(&pk_z.pke, &zero)
+        });
+        let mut san_pks_p = san_pks.to_owned();
+        san_pks_p.push(pk_z.clone());
+        let mut secrets = sig.secrets.clone();
+        secrets.push(cs);
+        let pub_adm: Vec<bool> = (0..n).map(|j| sig.pub_adm[j] || (adm_z[j] && sig.policy[j].modify)).collect();
+        let mut windows = sig.windows.clone();
+        if !windows.is_empty() {
+            windows.push(None);
+        }
+        let v = Signed { secrets: &secrets, pub_adm: &pub_adm, windows: &windows, ..Self::signed(sig) };
+        let sigp = Self::sign_blocks(sk_s, pk_s, &san_pks_p, m, &v);
+        let mut adm = td.adm.clone();
+        adm.push(adm_z.to_vec());
+        let tdp = Trapdoors {
+            skch: td.skch.clone(),
+            adm
+        };
+        Ok((sigp, tdp))
+    }
+
+    //Removes the sanitizer rev.pk_z from a signature that has not been sanitized yet. The blocks that were admissible
+    //for pk_z get a new chameleon key pair and hash, whose trapdoor is encrypted for the other sanitizers that have
+    //rights on them, the other blocks are kept. The signature is computed again for san_pks without pk_z. Sanitized
+    //signatures are rejected since the signer cannot compute the VRS proofs of the sanitizers again, it has to sign
+    //the current message instead.
+    pub fn revoke(
+        pp: &PublicParams, sk_s: &SignerSecretKey, pk_s: &SignerPublicKey, san_pks: &[SanitizerPublicKey],
+        m: &[String], sig: &Signature, rev: &Revocation
+    ) -> Result<(Signature, Trapdoors), String> {
+        let Revocation { pk_z, td } = rev;
+        let n = m.len();
+        Self::check_trapdoors(pp, san_pks, m, sig, td)?;
+        let ir = match san_pks.iter().position(|x| x.pkp == pk_z.pkp) {
+            Some(ir) => ir,
+            None => return Err("The sanitizer is not in san_pks".to_string()),
+        };
+        let mut san_pks_p = san_pks.to_owned();
+        san_pks_p.remove(ir);
+        let mut adm = td.adm.clone();
+        adm.remove(ir);
+        let mut secrets = sig.secrets.clone();
+        secrets.remove(ir);
+        let k = san_pks_p.len();
+        let zero = BigInt::from(0);
+        let mut hashes = sig.hashes.clone();
+        let mut skchs = td.skch.clone();
+        let blocks: Vec<Option<(CHashPubValues, Vec<Ciphertext>, BigInt)>> = map_range(n, |j| {
+            if !td.adm[ir][j] {
+                return None;
+            }
+            let (skch, pkch) = CHash::k_gen(&pp.p, &pp.q, &pp.g, pp.fb_chash.as_ref());
+            let mj = Self::input(sig, j, &m[j]);
             let (h, r) = CHash::hash(&pp.p, &pp.q, &pp.g, &pkch, 
-                &hash(&mj));
-            hashes.push(CHashPubValues { h: h, r: r, pkch: pkch });
-            let mut pa = false;
+                &hash(&mj), pp.fb_chash.as_ref());
+            let cs = map_range(k, |i| {
+                if adm[i][j] {
+                    return PKE::encrypt(&san_pks_p[i].pke, &skch);
+                }
+                PKE::encrypt(&san_pks_p[i].pke, &zero)
+            });
+            Some((CHashPubValues { h, r, pkch }, cs, skch))
+        });
+        for (j, b) in blocks.into_iter().enumerate() {
+            if let Some((hv, cs, skch)) = b {
+                hashes[j] = hv;
+                for (i, c) in cs.into_iter().enumerate() {
+                    secrets[i][j] = c;
+                }
+                skchs[j] = skch;
+            }
+        }
+        let pub_adm: Vec<bool> = (0..n).map(|j| (0..k).any(|i| adm[i][j]) && sig.policy[j].modify).collect();
+        let mut windows = sig.windows.clone();
+        if !windows.is_empty() {
+            windows.remove(ir);
+        }
+        let v = Signed { hashes: &hashes, secrets: &secrets, pub_adm: &pub_adm, windows: &windows, ..Self::signed(sig) };
+        let sigp = Self::sign_blocks(sk_s, pk_s, &san_pks_p, m, &v);
+        let tdp = Trapdoors {
+            skch: skchs,
+            adm
+        };
+        Ok((sigp, tdp))
+    }
+
+    //Revocation list of the signer for the given epoch, signed with its Schnorr key.
+    pub fn revocation_list(
+        _pp: &PublicParams, sk_s: &SignerSecretKey, pk_s: &SignerPublicKey, epoch: u64, revoked: &[SanitizerPublicKey]
+    ) -> Result<RevocationList, String> {
+        let revoked: Vec<BigInt> = revoked.iter().map(|x| x.pkp.clone()).collect();
+        let t = Self::revocation_message(pk_s, epoch, &revoked);
+        let rl = RevocationList {
+            epoch,
+            revoked,
+            s: SIG::sign(&sk_s.sk, &t)
+        };
+        Ok(rl)
+    }
+
+    pub fn verify_revocation_list(_pp: &PublicParams, pk_s: &SignerPublicKey, rl: &RevocationList) -> Result<bool, String> {
+        let t = Self::revocation_message(pk_s, rl.epoch, &rl.revoked);
+        Ok(SIG::verify(&pk_s.pk, &t, &rl.s))
+    }
+
+    //verify, and checks that rl is a revocation list of the signer in which none of the sanitizers of the
+    //signature appear.
+    pub fn verify_with_revocation(
+        pp: &PublicParams, pk_s: &SignerPublicKey,
+        san_pks: &[SanitizerPublicKey], m: &[String], sig: &Signature, rl: &RevocationList
+    ) -> Result<bool, String> {
+        if !Self::verify_revocation_list(pp, pk_s, rl)? {
+            return Ok(false);
+        }
+        if san_pks.iter().any(|x| rl.revoked.contains(&x.pkp)) {
+            return Ok(false);
+        }
+        Self::verify(pp, pk_s, san_pks, m, sig)
+    }
+
+    //Offline phase of sign: precomputes size blocks (chameleon key pair, randomness, encryptions of the trapdoor
+    //and of zero for every sanitizer) that do not depend on the message.
+    pub fn sign_offline(
+            pp: &PublicParams, san_pks: &[SanitizerPublicKey], size: usize
+        ) -> Result<SignPool, String> {
+        let k = san_pks.len();
+        let zero = BigInt::from(0);
+        let blocks: Vec<PreBlock> = map_range(size, |_| {
+            let (skch, pkch) = CHash::k_gen(&pp.p, &pp.q, &pp.g, pp.fb_chash.as_ref());
+            let (r, pkr) = CHash::hash_offline(&pp.p, &pp.q, &pkch);
+            let c_trap = map_range(k, |i| PKE::encrypt(&san_pks[i].pke, &skch));
+            let c_zero = map_range(k, |i| PKE::encrypt(&san_pks[i].pke, &zero));
+            PreBlock { skch, pkch, r, pkr, c_trap, c_zero }
+        });
+        let pool = SignPool {
+            san_pks: san_pks.to_owned(),
+            blocks
+        };
+        Ok(pool)
+    }
+
+    //Online phase of sign: same output as sign, the n blocks are taken out of the pool so they are never reused.
+    pub fn sign_online(
+            pp: &PublicParams, sk_s: &SignerSecretKey, pk_s: &SignerPublicKey,
+            san_pks: &[SanitizerPublicKey], m: &[String], adm: &[Vec<bool>], pool: &mut SignPool
+        ) -> Result<Signature, String> {
+        let k = san_pks.len();
+        let n = m.len();
+        check_adm(adm, k, n)?;
+        if pool.san_pks.len() != k || 
+            (0..k).any(|i| pool.san_pks[i].pkp != san_pks[i].pkp || pool.san_pks[i].pke.n != san_pks[i].pke.n) {
+            return Err("The pool was computed for another set of sanitizers".to_string());
+        }
+        if pool.blocks.len() < n {
+            return Err("Not enough precomputed blocks in the pool".to_string());
+        }
+        let blocks: Vec<PreBlock> = pool.blocks.drain(pool.blocks.len() - n..).collect();
+        let hashes: Vec<CHashPubValues> = map_range(n, |j| {
+            let mj = block_input(j, &m[j], false, false);
+            let (h, r) = CHash::hash_online(&pp.p, &pp.g, &hash(&mj), &blocks[j].r, &blocks[j].pkr, 
+                pp.fb_chash.as_ref());
+            CHashPubValues { h, r, pkch: blocks[j].pkch.clone() }
+        });
+        let mut secrets: Vec<Vec<Ciphertext>> = vec![Vec::with_capacity(n); k];
+        let mut pub_adm: Vec<bool> = Vec::with_capacity(n);
+        for j in 0..n {
             for i in 0..k {
                 if adm[i][j] {
-                    secrets[i].push(PKE::encrypt(&san_pks[i].pke, &skch));
-                    pa = true;
+                    secrets[i].push(blocks[j].c_trap[i].clone());
                 } else {
-                    secrets[i].push(PKE::encrypt(&san_pks[i].pke, &zero));
+                    secrets[i].push(blocks[j].c_zero[i].clone());
                 }
             }
-            pub_adm.push(pa);
+            pub_adm.push((0..k).any(|i| adm[i][j]));
         }
-        let ms = Self::generate_ms(&hashes, &secrets, &pub_adm, &pk_s, &san_pks);
-        let s = SIG::sign(&sk_s.sk, &ms);
-        for j in 0..n {
-            let mut t = "".to_string();
-            t = t + j.to_string().as_str() + m[j].as_str();
-            t = t + encode(&s.to_bytes()).as_str();
-            proofs.push(Proof {ps: Some(SIG::sign(&sk_s.sk, &t)), pz: None});
+        let v = Signed {
+            hashes: &hashes,
+            secrets: &secrets,
+            hybrid: None,
+            pub_adm: &pub_adm,
+            value_sets: &vec![None; n],
+            policy: &default_policy(n),
+            slots: &[],
+            limits: &vec![None; n],
+            windows: &[],
+            thresholds: &vec![None; n]
+        };
+        Ok(Self::sign_blocks(sk_s, pk_s, san_pks, m, &v))
+    }
+
+    //Same as sign, but the k x n Paillier ciphertexts are replaced by one hybrid ciphertext (KEM + AEAD) per
+    //sanitizer. The trapdoor of block j is derived from a 32-byte seed, and sanitizer i's plaintext is the vector
+    //of the n seeds with zeros for the blocks that are not admissible for i, so all plaintexts have the same length.
+    pub fn sign_compact(
+            pp: &PublicParams, sk_s: &SignerSecretKey, pk_s: &SignerPublicKey,
+            san_pks: &[SanitizerPublicKey], m: &[String], adm: &[Vec<bool>]
+        ) -> Result<Signature, String> {
+        let k = san_pks.len();
+        let n = m.len();
+        check_adm(adm, k, n)?;
+        let mut rng = thread_rng();
+        let seeds: Vec<[u8; SEED_LEN]> = (0..n).map(|_| rng.gen()).collect();
+        let hashes: Vec<CHashPubValues> = map_range(n, |j| {
+            let (_skch, pkch) = CHash::k_gen_from_seed(&pp.p, &pp.q, &pp.g, &seeds[j], pp.fb_chash.as_ref());
+            let mj = block_input(j, &m[j], false, false);
+            let (h, r) = CHash::hash(&pp.p, &pp.q, &pp.g, &pkch, 
+                &hash(&mj), pp.fb_chash.as_ref());
+            CHashPubValues { h, r, pkch }
+        });
+        let hybrid: Vec<HybridCiphertext> = map_range(k, |i| {
+            let mut pt = vec![0u8; n * SEED_LEN];
+            for j in 0..n {
+                if adm[i][j] {
+                    pt[j * SEED_LEN..(j + 1) * SEED_LEN].copy_from_slice(&seeds[j]);
+                }
+            }
+            Hybrid::encrypt(&san_pks[i].pkk, &pt, n.to_string().as_bytes())
+        });
+        let pub_adm: Vec<bool> = (0..n).map(|j| (0..k).any(|i| adm[i][j])).collect();
+        let v = Signed {
+            hashes: &hashes,
+            secrets: &[],
+            hybrid: Some(&hybrid),
+            pub_adm: &pub_adm,
+            value_sets: &vec![None; n],
+            policy: &default_policy(n),
+            slots: &[],
+            limits: &vec![None; n],
+            windows: &[],
+            thresholds: &vec![None; n]
+        };
+        Ok(Self::sign_blocks(sk_s, pk_s, san_pks, m, &v))
+    }
+
+    pub fn sanitize(
+        pp: &PublicParams, sk_z: &SanitizerSecretKey, pk_s: &SignerPublicKey, pk_z: &SanitizerPublicKey,
+        san_pks: &[SanitizerPublicKey], m: &[String], modif: &[Mod], sig: &Signature
+    ) -> Result<Signature, String> {
+        Self::sanitize_with_options(pp, sk_z, pk_s, pk_z, san_pks, &SanitizeOptions::new(m, modif), sig)
+    }
+
+    //Original indexes of the slots of pk_z that have not been filled yet.
+    pub fn unfilled_slots(san_pks: &[SanitizerPublicKey], pk_z: &SanitizerPublicKey, sig: &Signature) -> Vec<usize> {
+        let ip = match san_pks.iter().position(|x| x.pkp == pk_z.pkp) {
+            Some(ip) => ip,
+            None => return Vec::new(),
+        };
+        let l = sig.n - sig.slots.len().min(sig.n);
+        (0..sig.slots.len()).filter(|x| sig.slots[*x] == ip).map(|x| l + x)
+            .filter(|j| Self::unfilled(sig, *j)).collect()
+    }
+
+    //Original indexes of the blocks of sig that pk_z may modify: the blocks whose trapdoor it can decrypt from its
+    //secrets (or its delegations) and that may be modified by the policy, without producing a signature. The
+    //validity windows, the limits and the threshold blocks are not taken into account.
+    pub fn admissible_blocks(
+        pp: &PublicParams, sk_z: &SanitizerSecretKey, pk_z: &SanitizerPublicKey, san_pks: &[SanitizerPublicKey],
+        sig: &Signature
+    ) -> Result<Vec<usize>, String> {
+        let k = san_pks.len();
+        let n = sig.n;
+        let ip = san_pks.iter().position(|x| x.pkp == pk_z.pkp);
+        if ip.is_none() && !sig.delegations.iter().any(|d| d.pk_d.pkp == pk_z.pkp) {
+            return Err("The sanitizer is neither in san_pks nor a delegate".to_string());
+        }
+        if sig.hashes.len() != n || sig.policy.len() != n || !Self::secrets_well_formed(sig, k, n) {
+            return Err("The signature is not well formed".to_string());
+        }
+        let seeds = Self::open_seeds(sk_z, ip, k, sig)?;
+        let zero = BigInt::from(0);
+        let admissible: Vec<bool> = map_range(n, |j| {
+            if !sig.policy[j].modify || sig.redacted.contains(&j) {
+                return false;
+            }
+            Self::trapdoor(pp, sk_z, pk_z, ip, seeds.as_ref(), sig, j) != zero
+        });
+        Ok((0..n).filter(|j| admissible[*j]).collect())
+    }
+
+    //First round of a threshold sanitization: nonces of pk_z for the threshold blocks modified by opts.modif, for the
+    //sanitization in which the sanitizers signers (indexes in san_pks, pk_z included) cooperate. pk_z must be in the
+    //group of each of these blocks. The nonces stay with pk_z and the commitment goes to the other signers, whose
+    //commitments are put in opts.commitments, in the order of signers.
+    pub fn sanitize_commit(
+        pp: &PublicParams, pk_z: &SanitizerPublicKey, san_pks: &[SanitizerPublicKey], opts: &SanitizeOptions,
+        signers: &[usize], sig: &Signature
+    ) -> Result<(Nonces, NonceCommitment), String> {
+        let (ip, blocks) = Self::threshold_blocks(san_pks, pk_z, &opts.modif, signers, sig)?;
+        let mut rng = thread_rng();
+        let one = BigInt::from(1);
+        let d: Vec<BigInt> = blocks.iter().map(|_| rng.gen_bigint_range(&one, &pp.q)).collect();
+        let e: Vec<BigInt> = blocks.iter().map(|_| rng.gen_bigint_range(&one, &pp.q)).collect();
+        let commitment = NonceCommitment {
+            member: ip,
+            signers: signers.to_vec(),
+            blocks: blocks.clone(),
+            d: d.iter().map(|x| FixedBase::pow_or(pp.fb_chash.as_ref(), &pp.g, x, &pp.p)).collect(),
+            e: e.iter().map(|x| FixedBase::pow_or(pp.fb_chash.as_ref(), &pp.g, x, &pp.p)).collect()
+        };
+        let nonces = Nonces {
+            member: ip,
+            signers: signers.to_vec(),
+            blocks,
+            d,
+            e
+        };
+        Ok((nonces, commitment))
+    }
+
+    //Second round of a threshold sanitization: partial adaptation by pk_z of the threshold blocks modified by
+    //opts.modif, with the nonces of its commitment in opts.commitments. The new randomness of block j is a
+    //threshold Schnorr signature (CHash::collision_kef) on the group commitment R = prod D_i * E_i^b_i, and the
+    //partial adaptation of pk_z is d + e * b - c * lagrange(signers, x) * share_x mod q, c being the challenge and x
+    //the point of pk_z in the group. The binding factors b_i depend on the block, the new value and all the
+    //commitments, as in FROST, so that partial adaptations of concurrent sanitizations cannot be combined. The
+    //nonces hide share_x, so the partial adaptations can be sent to any sanitizer, which combines them in
+    //sanitize_with_options, and the nonces are consumed so that they cannot sign twice.
+    pub fn sanitize_partial(
+        pp: &PublicParams, sk_z: &SanitizerSecretKey, pk_z: &SanitizerPublicKey, san_pks: &[SanitizerPublicKey],
+        opts: &SanitizeOptions, nonces: Nonces, sig: &Signature
+    ) -> Result<PartialAdaptation, String> {
+        let signers = &nonces.signers;
+        let (ip, blocks) = Self::threshold_blocks(san_pks, pk_z, &opts.modif, signers, sig)?;
+        if nonces.member != ip || nonces.blocks != blocks || !Self::commitments_valid(&opts.commitments, signers, &blocks) {
+            return Err("The nonce commitments do not match the nonces of the sanitizer".to_string());
+        }
+        let own = signers.iter().position(|i| *i == ip).unwrap();
+        let pow = |x: &BigInt| FixedBase::pow_or(pp.fb_chash.as_ref(), &pp.g, x, &pp.p);
+        if (0..blocks.len()).any(|e| pow(&nonces.d[e]) != opts.commitments[own].d[e] || 
+            pow(&nonces.e[e]) != opts.commitments[own].e[e]) {
+            return Err("The nonce commitments do not match the nonces of the sanitizer".to_string());
+        }
+        let mut rho: Vec<BigInt> = Vec::with_capacity(blocks.len());
+        for (e, j) in blocks.iter().enumerate() {
+            let th = sig.thresholds[*j].as_ref().unwrap();
+            let xs: Vec<usize> = signers.iter()
+                .map(|i| th.group.members.iter().position(|x| x == i).unwrap() + 1)
+                .collect();
+            let x = th.group.members.iter().position(|x| *x == ip).unwrap() + 1;
+            let y = match th.shares.get(x - 1) {
+                Some(c) => PKE::decrypt(&sk_z.ske, c),
+                None => return Err("The share of the sanitizer is missing".to_string()),
+            };
+            let mpj = Self::modified_input(&opts.modif, *j).unwrap();
+            let (gc, bs) = Self::group_commitment(pp, sig, *j, e, &mpj, &opts.commitments);
+            let (_, c) = CHash::collision_kef(&pp.q, &sig.hashes[*j].h, &hash(&mpj), &gc);
+            let a = Shamir::lagrange(&pp.q, &xs, x) * y % &pp.q;
+            rho.push((&nonces.d[e] + &nonces.e[e] * &bs[own] - c * a).mod_floor(&pp.q));
         }
-        let sig = Signature {
-            s: s,
-            hashes: hashes,
-            secrets: secrets,
-            pub_adm: pub_adm,
-            n: n,
-            proofs: proofs
+        let partial = PartialAdaptation {
+            member: ip,
+            signers: signers.clone(),
+            blocks,
+            rho
         };
-        return Ok(sig);
+        Ok(partial)
     }
 
-    pub fn sanitize<'d>(
+    //Index of pk_z in san_pks and threshold blocks modified by modif, in order, checking that signers are distinct
+    //sanitizers of san_pks, pk_z included, and t members of the group of each of these blocks.
+    fn threshold_blocks(
+        san_pks: &[SanitizerPublicKey], pk_z: &SanitizerPublicKey, modif: &[Mod], signers: &[usize], sig: &Signature
+    ) -> Result<(usize, Vec<usize>), String> {
+        let ip = match san_pks.iter().position(|x| x.pkp == pk_z.pkp) {
+            Some(ip) => ip,
+            None => return Err("The sanitizer is not in san_pks".to_string()),
+        };
+        if !signers.contains(&ip) || !members_valid(signers, san_pks.len()) {
+            return Err("The signers must be distinct sanitizers of san_pks, the sanitizer included".to_string());
+        }
+        if sig.thresholds.len() != sig.n || sig.hashes.len() != sig.n {
+            return Err("The signature is not well formed".to_string());
+        }
+        let mut blocks: Vec<usize> = Vec::new();
+        for modif in modif.iter() {
+            let th = match sig.thresholds.get(modif.i) {
+                Some(Some(th)) => th,
+                _ => continue,
+            };
+            if signers.len() < th.group.t || signers.iter().any(|i| !th.group.members.contains(i)) {
+                return Err("The signers must be t members of the group of the block".to_string());
+            }
+            if !blocks.contains(&modif.i) {
+                blocks.push(modif.i);
+            }
+        }
+        if blocks.is_empty() {
+            return Err("The modification does not change any threshold block".to_string());
+        }
+        Ok((ip, blocks))
+    }
+
+    //There is one commitment by each signer, in the order of signers, for the given blocks.
+    fn commitments_valid(commitments: &[NonceCommitment], signers: &[usize], blocks: &[usize]) -> bool {
+        commitments.len() == signers.len() && commitments.iter().zip(signers.iter()).all(|(c, i)| {
+            c.member == *i && c.signers == signers && c.blocks == blocks && 
+                c.d.len() == blocks.len() && c.e.len() == blocks.len()
+        })
+    }
+
+    //Input of the new value of block j, if modif modifies it.
+    fn modified_input(modif: &[Mod], j: usize) -> Option<String> {
+        modif.iter().rev().find(|x| x.i == j).map(|x| block_input(j, &x.m, false, false))
+    }
+
+    //Group commitment R = prod D_i * E_i^b_i mod p of the threshold sanitization of block j (the e-th block of the
+    //commitments) to the input mpj, and the binding factors b_i = H(i, sig, j, mpj, commitments), in the order of
+    //the commitments.
+    fn group_commitment(
+        pp: &PublicParams, sig: &Signature, j: usize, e: usize, mpj: &str, commitments: &[NonceCommitment]
+    ) -> (BigInt, Vec<BigInt>) {
+        let mut t = encode(&sig.s.to_bytes());
+        t.push(':');
+        t.push_str(j.to_string().as_str());
+        t.push(':');
+        t.push_str(sig.hashes[j].h.to_str_radix(36).as_str());
+        t.push(':');
+        t.push_str(mpj);
+        for c in commitments.iter() {
+            t.push(':');
+            t.push_str(c.member.to_string().as_str());
+            for (d, e) in c.d.iter().zip(c.e.iter()) {
+                t.push(',');
+                t.push_str(d.to_str_radix(36).as_str());
+                t.push(',');
+                t.push_str(e.to_str_radix(36).as_str());
+            }
+        }
+        let bs: Vec<BigInt> = commitments.iter()
+            .map(|c| CHash::trapdoor_from_seed(&pp.q, (t.clone() + ":b" + c.member.to_string().as_str()).as_bytes()))
+            .collect();
+        let gc = commitments.iter().zip(bs.iter())
+            .fold(BigInt::from(1), |acc, (c, b)| acc * &c.d[e] % &pp.p * c.e[e].modpow(b, &pp.p) % &pp.p);
+        (gc, bs)
+    }
+
+    //Combines the nonce commitments and the partial adaptations of all the signers of a threshold sanitization
+    //(SanitizeOptions::commitments and partials) into the new randomness (r, s) of each threshold block, which
+    //opens its chameleon hash to the modification if at least t of them cooperated.
+    fn combine_partials(
+        pp: &PublicParams, opts: &SanitizeOptions, sig: &Signature
+    ) -> Result<Adaptations, String> {
+        let SanitizeOptions { modif, commitments, partials, .. } = opts;
+        let first = match partials.first() {
+            Some(x) => x,
+            None => return Ok(Vec::new()),
+        };
+        let mut members: Vec<usize> = partials.iter().map(|x| x.member).collect();
+        members.sort();
+        members.dedup();
+        let mut signers = first.signers.clone();
+        signers.sort();
+        if members != signers || partials.len() != signers.len() || partials.iter().any(|x| {
+            x.signers != first.signers || x.blocks != first.blocks || x.rho.len() != x.blocks.len()
+        }) {
+            return Err("There must be one partial adaptation of the same blocks by each signer".to_string());
+        }
+        if !Self::commitments_valid(commitments, &first.signers, &first.blocks) {
+            return Err("There must be one nonce commitment for the same blocks by each signer".to_string());
+        }
+        let mut adapted: Adaptations = Vec::with_capacity(first.blocks.len());
+        for (e, j) in first.blocks.iter().enumerate() {
+            let mpj = match (sig.thresholds.get(*j), Self::modified_input(modif, *j)) {
+                (Some(Some(_)), Some(mpj)) if *j < sig.hashes.len() => mpj,
+                _ => return Err("The partial adaptations are not for this modification".to_string()),
+            };
+            let (gc, _) = Self::group_commitment(pp, sig, *j, e, &mpj, commitments);
+            let (rp, _) = CHash::collision_kef(&pp.q, &sig.hashes[*j].h, &hash(&mpj), &gc);
+            let sp = partials.iter().fold(BigInt::from(0), |acc, x| (acc + &x.rho[e]) % &pp.q);
+            adapted.push((*j, (rp, sp)));
+        }
+        Ok(adapted)
+    }
+
+    //Same as sanitize, with the message, the modifications, the redacted and appended blocks, the value sets of the
+    //modified blocks given by the signer, the time and the partial adaptations of the threshold blocks in opts.
+    pub fn sanitize_with_options(
         pp: &PublicParams, sk_z: &SanitizerSecretKey, _pk_s: &SignerPublicKey, pk_z: &SanitizerPublicKey,
-        san_pks: &Vec<SanitizerPublicKey>, m: &Vec<String>, modif: &Vec<Mod>, sig: &Signature<'d>
-    ) -> Result<Signature<'d>, String> {
+        san_pks: &[SanitizerPublicKey], opts: &SanitizeOptions, sig: &Signature
+    ) -> Result<Signature, String> {
+        let SanitizeOptions { m, modif, redact, append, values, now, .. } = opts;
+        let now = *now;
+        let free = Self::unfilled_slots(san_pks, pk_z, sig);
+        if append.len() > free.len() {
+            return Err("Not enough unfilled slots for the sanitizer".to_string());
+        }
+        let modif: Vec<Mod> = modif.iter().cloned()
+            .chain(append.iter().zip(free.iter()).map(|(b, j)| Mod { i: *j, m: b.clone() }))
+            .collect();
+        let adapted = Self::combine_partials(pp, opts, sig)?;
         let k = san_pks.len();
         let n = sig.n;
         let zero = BigInt::from(0);
+        let m = match Self::expand(m, sig) {
+            Some(m) => m,
+            None => return Err("The signature does not match the length of m".to_string()),
+        };
+        for modif in modif.iter() {
+            if modif.i >= n || sig.redacted.contains(&modif.i) {
+                return Err("The modification is out of the message".to_string());
+            }
+            if !sig.policy[modif.i].modify {
+                return Err("The block may not be modified".to_string());
+            }
+        }
+        let mut redacted = sig.redacted.clone();
+        for j in redact.iter() {
+            if *j >= n || redacted.contains(j) {
+                return Err("The redacted blocks must be blocks of the message".to_string());
+            }
+            if !sig.policy[*j].redact {
+                return Err("The block may not be redacted".to_string());
+            }
+            redacted.push(*j);
+        }
+        redacted.sort();
+        let mut filled = sig.filled.clone();
+        for modif in modif.iter() {
+            if Self::unfilled(sig, modif.i) && !filled.contains(&modif.i) {
+                filled.push(modif.i);
+            }
+        }
+        filled.sort();
         let mut hashes_p: Vec<CHashPubValues> = Vec::with_capacity(n);
         let mut proofs_p: Vec<Proof> = Vec::with_capacity(n);
         let mut mp: Vec<String> = Vec::with_capacity(n);
-        let ring: Vec<BigInt> = san_pks.iter().map(|x| x.pkp.clone()).collect();
         for j in 0..n {
             mp.push(m[j].clone());
         }
         for modif in modif.iter() {
             mp[modif.i] = modif.m.clone();
         }
-        let mut ip: usize = 0;
-        for i in 0..k {
-            if san_pks[i].pkp == pk_z.pkp {
-                ip = i;
-                break;
+        let ip = san_pks.iter().position(|x| x.pkp == pk_z.pkp);
+        if ip.is_none() && !sig.delegations.iter().any(|d| d.pk_d.pkp == pk_z.pkp) {
+            return Err("The sanitizer is neither in san_pks nor a delegate".to_string());
+        }
+        if !sig.windows.is_empty() {
+            let now = match now {
+                Some(now) => now,
+                None => return Err("The signature has validity windows, the sanitization needs a time".to_string()),
+            };
+            if sig.windows.len() != k || !ip.is_some_and(|i| in_window(&sig.windows[i], now)) {
+                return Err("The rights of the sanitizer are not valid at this time".to_string());
             }
         }
-        for j in 0..n {
-            if mp[j] != m[j] {
-                let skch = PKE::decrypt(&sk_z.ske, &sig.secrets[ip][j]);
-                if skch == zero {
-                    return Err("The modification is not admissible for the chosen sanitizer".to_string());
-                }
-                let mj = j.to_string() + m[j].as_str();
-                let mpj = j.to_string() + mp[j].as_str();
-                let rp = CHash::adapt(&pp.p, &pp.q, &pp.g, &skch, 
-                    &hash(&mj), &sig.hashes[j].r, &hash(&mpj));
-                let mut t = "".to_string();
-                t = t + j.to_string().as_str() + mp[j].as_str();
-                t = t + encode(&sig.s.to_bytes()).as_str();
-                let pz = VRS::sign(&pp.p, &pp.q, &pp.g, &sk_z.skp, &ring, &t);
-                proofs_p.push(Proof { ps: None, pz: Some(pz) });
-                hashes_p.push(CHashPubValues { h: sig.hashes[j].h.clone(), r: rp.clone(), pkch: sig.hashes[j].pkch.clone() });
-            } else {
-                proofs_p.push(sig.proofs[j].clone());
-                hashes_p.push(CHashPubValues { h: sig.hashes[j].h.clone(), r: sig.hashes[j].r.clone(), 
-                    pkch: sig.hashes[j].pkch.clone() });
+        let seeds = Self::open_seeds(sk_z, ip, k, sig)?;
+        let value_sets = update_value_sets(&sig.value_sets, &m, &mp, values)?;
+        let changed: Vec<usize> = (0..n).filter(|j| {
+            let unfilled = Self::unfilled(sig, *j) && !filled.contains(j);
+            block_input(*j, &mp[*j], redacted.contains(j), unfilled) != Self::input(sig, *j, &m[*j])
+        }).collect();
+        //The counts are only taken from the log of sig: sanitizing another version of the document that does not
+        //hold this log reuses a count, and two proofs on the same count and different messages expose sk_z.
+        let tags = Self::next_tags(pp, sk_z, sig, &changed)?;
+        let mut times = sig.times.clone();
+        for j in changed.iter() {
+            times[*j] = now;
+        }
+        let active = Self::active(san_pks, &sig.windows, now);
+        let blocks: Vec<Result<(Proof, CHashPubValues), String>> = map_range(n, |j| {
+            let mj = Self::input(sig, j, &m[j]);
+            let unfilled = Self::unfilled(sig, j) && !filled.contains(&j);
+            let mpj = block_input(j, &mp[j], redacted.contains(&j), unfilled);
+            if mpj != mj {
+                let rp = match (&sig.thresholds[j], adapted.iter().find(|(b, _)| *b == j)) {
+                    (Some(_), Some((_, (rp, sp)))) => {
+                        if !CHash::check_kef(&pp.p, &pp.q, &pp.g, &sig.hashes[j].pkch, &hash(&mpj), (rp, sp), 
+                            &sig.hashes[j].h) {
+                            return Err("The partial adaptations do not open the block to the modification".to_string());
+                        }
+                        rp.clone()
+                    },
+                    (Some(_), None) => {
+                        return Err("A threshold block needs the partial adaptations of its group".to_string());
+                    },
+                    (None, _) => {
+                        let skch = Self::trapdoor(pp, sk_z, pk_z, ip, seeds.as_ref(), sig, j);
+                        if skch == zero {
+                            return Err("The modification is not admissible for the chosen sanitizer".to_string());
+                        }
+                        CHash::adapt(&pp.p, &pp.q, &pp.g, &skch, &hash(&mj), &sig.hashes[j].r, &hash(&mpj))
+                    },
+                };
+                let mut t = mpj.clone();
+                t += encode(&sig.s.to_bytes()).as_str();
+                t += Self::delegation_chain(&sig.delegations, j).as_str();
+                t += Self::time_message(now).as_str();
+                let ring = Self::block_ring(&active, &sig.delegations, j);
+                let pz = match sig.limits[j] {
+                    Some(_) => {
+                        let e = tags.iter().rposition(|x| x.block == j).unwrap();
+                        let t = t + Self::tags_message(&tags[..e + 1]).as_str();
+                        let h = VRS::link_base(&pp.p, &pp.q, &Self::limit_tag(&sig.s, j, tags[e].c));
+                        VRS::sign_linkable(&pp.q, &pp.g, &sk_z.skp, &ring, &h, &t, pp.fb_vrs.as_ref())?
+                    },
+                    None => VRS::sign(&pp.p, &pp.q, &pp.g, &sk_z.skp, &ring, &t, pp.fb_vrs.as_ref()),
+                };
+                return Ok((Proof { ps: None, pz: Some(pz) }, 
+                    CHashPubValues { h: sig.hashes[j].h.clone(), r: rp.clone(), pkch: sig.hashes[j].pkch.clone() }));
+            }
+            Ok((sig.proofs[j].clone(), CHashPubValues { h: sig.hashes[j].h.clone(), r: sig.hashes[j].r.clone(), 
+                pkch: sig.hashes[j].pkch.clone() }))
+        });
+        for b in blocks {
+            let (proof, hv) = b?;
+            proofs_p.push(proof);
+            hashes_p.push(hv);
+        }
+        let mut thresholds = sig.thresholds.clone();
+        for (j, (_, sp)) in adapted.iter().filter(|(j, _)| changed.contains(j)) {
+            if let Some(th) = thresholds[*j].as_mut() {
+                th.s = sp.clone();
             }
         }
         let sigp = Signature {
             s: sig.s,
             hashes: hashes_p,
             secrets: sig.secrets.clone(),
+            hybrid: sig.hybrid.clone(),
             pub_adm: sig.pub_adm.clone(),
             n: sig.n,
-            proofs: proofs_p
+            proofs: proofs_p,
+            delegations: sig.delegations.clone(),
+            value_sets,
+            policy: sig.policy.clone(),
+            redacted,
+            slots: sig.slots.clone(),
+            filled,
+            limits: sig.limits.clone(),
+            tags,
+            windows: sig.windows.clone(),
+            times,
+            thresholds
         };
         return Ok(sigp);
     }
 
+    //Hands the rights of the sanitizer pk_z (a sanitizer of san_pks or a delegate) over the given blocks to pk_d,
+    //who is not in san_pks: the trapdoors of the blocks are encrypted under pk_d and the delegation is certified
+    //with a VRS ring signature over san_pks and the previous delegates, which also covers the previous
+    //delegations. The sanitization proofs of a block cover the certificates of its delegations, so a block must be
+    //delegated before it is sanitized.
+    pub fn delegate(
+        pp: &PublicParams, sk_z: &SanitizerSecretKey, pk_z: &SanitizerPublicKey, san_pks: &[SanitizerPublicKey],
+        pk_d: &SanitizerPublicKey, blocks: &[usize], sig: &Signature
+    ) -> Result<Signature, String> {
+        let k = san_pks.len();
+        let n = sig.n;
+        if blocks.is_empty() || blocks.iter().any(|j| *j >= n) {
+            return Err("The delegated blocks must be blocks of the message".to_string());
+        }
+        if san_pks.iter().any(|x| x.pkp == pk_d.pkp) {
+            return Err("The delegate is already in san_pks".to_string());
+        }
+        if !sig.windows.is_empty() {
+            return Err("Signatures with validity windows are not supported".to_string());
+        }
+        let ip = san_pks.iter().position(|x| x.pkp == pk_z.pkp);
+        if ip.is_none() && !sig.delegations.iter().any(|d| d.pk_d.pkp == pk_z.pkp) {
+            return Err("The sanitizer is neither in san_pks nor a delegate".to_string());
+        }
+        let seeds = Self::open_seeds(sk_z, ip, k, sig)?;
+        let zero = BigInt::from(0);
+        let skchs: Vec<BigInt> = map_range(blocks.len(), |x| Self::trapdoor(pp, sk_z, pk_z, ip, seeds.as_ref(), sig, blocks[x]));
+        if skchs.contains(&zero) {
+            return Err("The delegated blocks are not admissible for the chosen sanitizer".to_string());
+        }
+        let secrets: Vec<Ciphertext> = map_range(blocks.len(), |x| PKE::encrypt(&pk_d.pke, &skchs[x]));
+        let mut ring: Vec<BigInt> = san_pks.iter().map(|x| x.pkp.clone()).collect();
+        ring.extend(sig.delegations.iter().map(|d| d.pk_d.pkp.clone()));
+        let t = Self::delegation_message(&sig.s, &sig.delegations, pk_d, blocks, &secrets);
+        let cert = VRS::sign(&pp.p, &pp.q, &pp.g, &sk_z.skp, &ring, &t, pp.fb_vrs.as_ref());
+        let mut sigp = sig.clone();
+        sigp.delegations.push(Delegation {
+            pk_d: pk_d.clone(),
+            blocks: blocks.to_owned(),
+            secrets,
+            cert
+        });
+        Ok(sigp)
+    }
+
     pub fn verify(
         pp: &PublicParams, pk_s: &SignerPublicKey,
-        san_pks: &Vec<SanitizerPublicKey>, m: &Vec<String>, sig: &Signature
+        san_pks: &[SanitizerPublicKey], m: &[String], sig: &Signature
     ) -> Result<bool, String> {
+        let m = match Self::expand(m, sig) {
+            Some(m) => m,
+            None => return Ok(false),
+        };
         let n = m.len();
-        let ms = Self::generate_ms(&sig.hashes, &sig.secrets, &sig.pub_adm, &pk_s, &san_pks);
+        if sig.hashes.len() != n || sig.proofs.len() != n || sig.pub_adm.len() != n || 
+            !Self::secrets_well_formed(sig, san_pks.len(), n) || !Self::windows_well_formed(sig, san_pks.len()) || 
+            !Self::thresholds_well_formed(sig, san_pks.len()) {
+            return Ok(false);
+        }
+        if !Self::values_valid(sig, &m) || !Self::tags_valid(sig, n) {
+            return Ok(false);
+        }
+        let ms = Self::generate_ms(&Self::signed(sig), pk_s, san_pks);
         if !SIG::verify(&pk_s.pk, &ms, &sig.s) {
             return Ok(false);
         }
-        let ring: Vec<BigInt> = san_pks.iter().map(|x| x.pkp.clone()).collect();
-        for j in 0..n {
-            let mj = j.to_string() + m[j].as_str();
-            if !CHash::check(&pp.p, &pp.q, &pp.g, &sig.hashes[j].pkch, &hash(&mj), 
-                &sig.hashes[j].r, &sig.hashes[j].h) {
-                return Ok(false);
-            }
-            let mut t = "".to_string();
-            t = t + j.to_string().as_str() + m[j].as_str();
-            t = t + encode(&sig.s.to_bytes()).as_str();
-            if sig.pub_adm[j] {
-                if sig.proofs[j].pz.is_none() || 
-                    !VRS::verify(&pp.p, &pp.q, &pp.g, &ring, &t, sig.proofs[j].pz.as_ref().unwrap()) {
-                    return Ok(false);
+        if !Self::verify_delegations(pp, san_pks, sig, n) {
+            return Ok(false);
+        }
+        let b = all_range(n, |j| {
+            let mj = Self::input(sig, j, &m[j]);
+            if !Self::chash_valid(pp, sig, j, &mj) {
+                return false;
+            }
+            let mut t = mj.clone();
+            t += encode(&sig.s.to_bytes()).as_str();
+            if Self::sanitized(sig, j) {
+                if !Self::verify_pz(pp, san_pks, sig, j, t) {
+                    return false;
                 }
             } else {
                 if sig.proofs[j].ps.is_none() || 
                     !SIG::verify(&pk_s.pk, &t, sig.proofs[j].ps.as_ref().unwrap()) {
-                    return Ok(false);
+                    return false;
+                }
+            }
+            true
+        });
+        Ok(b)
+    }
+
+    //verify, and checks that no sanitization of sig is dated after the time now of the caller's clock.
+    pub fn verify_at(
+        pp: &PublicParams, pk_s: &SignerPublicKey,
+        san_pks: &[SanitizerPublicKey], m: &[String], sig: &Signature, now: u64
+    ) -> Result<bool, String> {
+        if sig.times.iter().flatten().any(|t| *t > now) {
+            return Ok(false);
+        }
+        Self::verify(pp, pk_s, san_pks, m, sig)
+    }
+
+    //Verifies many signatures of the same signer. The Schnorr signatures and the chameleon hashes of all the
+    //signatures are checked in two aggregated equations, the VRS proofs and the chameleon hashes of the threshold
+    //blocks are checked one by one. If the aggregated
+    //check fails, each signature is verified on its own to find the invalid ones.
+    //Returns whether all the signatures are valid and the indexes of the invalid ones.
+    pub fn verify_batch(
+        pp: &PublicParams, pk_s: &SignerPublicKey,
+        san_pks: &[Vec<SanitizerPublicKey>], m: &[Vec<String>], sigs: &[Signature]
+    ) -> Result<(bool, Vec<usize>), String> {
+        if san_pks.len() != sigs.len() || m.len() != sigs.len() {
+            return Err("san_pks, m and sigs must have the same length".to_string());
+        }
+        let mut invalid: Vec<usize> = Vec::new();
+        let mut candidates: Vec<usize> = Vec::with_capacity(sigs.len());
+        let mut ts: Vec<String> = Vec::new();
+        let mut ss: Vec<k256::schnorr::Signature> = Vec::new();
+        let mut pks: Vec<BigInt> = Vec::new();
+        let mut hms: Vec<BigInt> = Vec::new();
+        let mut rs: Vec<BigInt> = Vec::new();
+        let mut hs: Vec<BigInt> = Vec::new();
+        for x in 0..sigs.len() {
+            let sig = &sigs[x];
+            let mx = match Self::expand(&m[x], sig) {
+                Some(mx) => mx,
+                None => {
+                    invalid.push(x);
+                    continue;
+                }
+            };
+            let n = mx.len();
+            if sig.hashes.len() != n || sig.proofs.len() != n || sig.pub_adm.len() != n || 
+                !Self::secrets_well_formed(sig, san_pks[x].len(), n) || !Self::verify_delegations(pp, &san_pks[x], sig, n) || 
+                !Self::windows_well_formed(sig, san_pks[x].len()) || !Self::values_valid(sig, &mx) || 
+                !Self::tags_valid(sig, n) || !Self::thresholds_well_formed(sig, san_pks[x].len()) {
+                invalid.push(x);
+                continue;
+            }
+            let mut b = true;
+            let mut ts_x: Vec<String> = Vec::with_capacity(n + 1);
+            let mut ss_x: Vec<k256::schnorr::Signature> = Vec::with_capacity(n + 1);
+            ts_x.push(Self::generate_ms(&Self::signed(sig), pk_s, &san_pks[x]));
+            ss_x.push(sig.s);
+            for (j, mj) in mx.iter().enumerate() {
+                let mut t = Self::input(sig, j, mj);
+                if Self::is_threshold(sig, j) && !Self::chash_valid(pp, sig, j, &t) {
+                    b = false;
+                    break;
+                }
+                t += encode(&sig.s.to_bytes()).as_str();
+                if Self::sanitized(sig, j) {
+                    if !Self::verify_pz(pp, &san_pks[x], sig, j, t) {
+                        b = false;
+                        break;
+                    }
+                } else {
+                    if sig.proofs[j].ps.is_none() {
+                        b = false;
+                        break;
+                    }
+                    ts_x.push(t);
+                    ss_x.push(*sig.proofs[j].ps.as_ref().unwrap());
                 }
             }
+            if !b {
+                invalid.push(x);
+                continue;
+            }
+            candidates.push(x);
+            ts.extend(ts_x);
+            ss.extend(ss_x);
+            for (j, mj) in mx.iter().enumerate().filter(|(j, _)| !Self::is_threshold(sig, *j)) {
+                let mj = Self::input(sig, j, mj);
+                pks.push(sig.hashes[j].pkch.clone());
+                hms.push(hash(&mj));
+                rs.push(sig.hashes[j].r.clone());
+                hs.push(sig.hashes[j].h.clone());
+            }
         }
-        return Ok(true);
+        if !SIG::verify_batch(&pk_s.pk, &ts, &ss) || !CHash::check_batch(&pp.p, &pp.q, &pp.g, &pks, &hms, &rs, &hs) {
+            for x in candidates {
+                if !Self::verify(pp, pk_s, &san_pks[x], &m[x], &sigs[x])? {
+                    invalid.push(x);
+                }
+            }
+            invalid.sort();
+        }
+        Ok((invalid.is_empty(), invalid))
     }
 
     pub fn judge(
-        _pp: &PublicParams, _pk_s: &SignerPublicKey, _san_pks: &Vec<SanitizerPublicKey>,
-        _m: &Vec<String>, sig: &Signature, _p: Option<&Proof>, j: Option<&usize>
+        _pp: &PublicParams, _pk_s: &SignerPublicKey, _san_pks: &[SanitizerPublicKey],
+        _m: &[String], sig: &Signature, _p: Option<&Proof>, j: Option<&usize>
     ) -> Result<char, String> {
         if j == None {
             for pa in sig.pub_adm.iter() {
@@ -207,19 +1158,484 @@ impl KSan {
                     return Ok('Z');
                 }
             }
+            if !sig.redacted.is_empty() || !sig.filled.is_empty() {
+                return Ok('Z');
+            }
             return Ok('S');
         } else {
-            if sig.pub_adm[*j.unwrap()] {
+            if Self::sanitized(sig, *j.unwrap()) {
                 return Ok('Z');
             }
             return Ok('S');
         }
     }
 
-    fn generate_ms<'d>(
-        hashes: &Vec<CHashPubValues>, secrets: &Vec<Vec<RawCiphertext<'d>>>, pub_adm: &Vec<bool>,
-        pk_s: &SignerPublicKey, san_pks: &Vec<SanitizerPublicKey>
+    //Identifies a sanitizer that went over the limit of a block: sig_a and sig_b are versions of the same signature
+    //whose proofs of a limited block use the same count of the same sanitizer on different messages, which exposes
+    //its VRS key. None if no such block is found.
+    pub fn judge_overuse(
+        pp: &PublicParams, san_pks: &[SanitizerPublicKey], sig_a: &Signature, sig_b: &Signature
+    ) -> Result<Option<SanitizerPublicKey>, String> {
+        let n = sig_a.n;
+        if [sig_b.n, sig_a.proofs.len(), sig_b.proofs.len(), sig_a.limits.len(), sig_b.limits.len(), 
+            sig_a.times.len(), sig_b.times.len()].iter().any(|x| *x != n) {
+            return Err("The signatures are not versions of the same signature".to_string());
+        }
+        if sig_a.s.to_bytes() != sig_b.s.to_bytes() || sig_a.limits != sig_b.limits {
+            return Err("The signatures are not versions of the same signature".to_string());
+        }
+        for j in 0..n {
+            let (pa, pb) = match (&sig_a.proofs[j].pz, &sig_b.proofs[j].pz) {
+                (Some(pa), Some(pb)) if sig_a.limits[j].is_some() && pa.z == pb.z => (pa, pb),
+                _ => continue,
+            };
+            //Both proofs must be over the same ring for the tags to identify the same sanitizer
+            let mut pks = Self::active(san_pks, &sig_a.windows, sig_a.times[j]);
+            let ring = Self::block_ring(&pks, &sig_a.delegations, j);
+            let pks_b = Self::active(san_pks, &sig_b.windows, sig_b.times[j]);
+            if Self::block_ring(&pks_b, &sig_b.delegations, j) != ring {
+                continue;
+            }
+            pks.extend(sig_a.delegations.iter().filter(|d| d.blocks.contains(&j)).map(|d| d.pk_d.clone()));
+            if let Some(i) = VRS::extract(&pp.q, &pp.g, &ring, pa, pb) {
+                return Ok(Some(pks[i].clone()));
+            }
+        }
+        Ok(None)
+    }
+
+    //Encoded size of a signature: main Schnorr signature, chameleon hash values, ciphertexts of the trapdoors
+    //(Paillier or hybrid), admissibility bits and per-block proofs (Schnorr or VRS ring signature).
+    pub fn signature_size(sig: &Signature) -> SizeReport {
+        let mut r = SizeReport::new();
+        r.add("schnorr", sig.s.to_bytes().len());
+        for hv in sig.hashes.iter() {
+            r.add("chameleon", bigint_size(&hv.h) + bigint_size(&hv.r) + bigint_size(&hv.pkch));
+        }
+        for c in sig.secrets.iter().flatten() {
+            r.add("ciphertexts", ciphertext_size(c));
+        }
+        for c in sig.hybrid.iter().flatten() {
+            r.add("ciphertexts", hybrid_size(c));
+        }
+        r.add("pub_adm", sig.pub_adm.len().div_ceil(8));
+        r.add("redaction", (2 * sig.policy.len()).div_ceil(8) + 8 * sig.redacted.len());
+        r.add("slots", 8 * (sig.slots.len() + sig.filled.len()));
+        r.add("limits", 8 * sig.limits.iter().flatten().count() + sig.limits.len().div_ceil(8));
+        for x in sig.tags.iter() {
+            r.add("limits", 16 + bigint_size(&x.z));
+        }
+        r.add("windows", 16 * sig.windows.iter().flatten().count() + sig.windows.len().div_ceil(8));
+        r.add("windows", 8 * sig.times.iter().flatten().count() + sig.times.len().div_ceil(8));
+        r.add("thresholds", sig.thresholds.len().div_ceil(8));
+        for th in sig.thresholds.iter().flatten() {
+            r.add("thresholds", 8 + 8 * th.group.members.len() + 
+                th.shares.iter().map(ciphertext_size).sum::<usize>() + bigint_size(&th.s));
+        }
+        for vs in sig.value_sets.iter().flatten() {
+            r.add("value_sets", vs.root.len() + 8 + vs.membership.path.iter().map(|x| x.len()).sum::<usize>());
+        }
+        for d in sig.delegations.iter() {
+            r.add("delegations", Self::sanitizer_pk_size(&d.pk_d).total() + 8 * d.blocks.len() + 
+                d.secrets.iter().map(ciphertext_size).sum::<usize>() + vrs_signature_size(&d.cert));
+        }
+        for p in sig.proofs.iter() {
+            if let Some(ps) = &p.ps {
+                r.add("block_schnorr", ps.to_bytes().len());
+            }
+            if let Some(pz) = &p.pz {
+                r.add("vrs_ring_proof", vrs_signature_size(pz));
+            }
+        }
+        r
+    }
+
+    pub fn signer_pk_size(pk_s: &SignerPublicKey) -> SizeReport {
+        let mut r = SizeReport::new();
+        r.add("schnorr", pk_s.pk.len());
+        r
+    }
+
+    pub fn sanitizer_pk_size(pk_z: &SanitizerPublicKey) -> SizeReport {
+        let mut r = SizeReport::new();
+        r.add("paillier", pk_z.pke.n.to_bytes().len());
+        r.add("vrs", bigint_size(&pk_z.pkp));
+        r.add("kem", pk_z.pkk.to_sec1_bytes().len());
+        r
+    }
+
+    fn sign_blocks(
+        sk_s: &SignerSecretKey, pk_s: &SignerPublicKey, san_pks: &[SanitizerPublicKey], m: &[String], v: &Signed
+    ) -> Signature {
+        let n = m.len();
+        let ms = Self::generate_ms(v, pk_s, san_pks);
+        let s = SIG::sign(&sk_s.sk, &ms);
+        let proofs: Vec<Proof> = map_range(n, |j| {
+            let mut t = block_input(j, &m[j], false, j + v.slots.len() >= n);
+            t += encode(&s.to_bytes()).as_str();
+            Proof {ps: Some(SIG::sign(&sk_s.sk, &t)), pz: None}
+        });
+        Signature {
+            s,
+            hashes: v.hashes.to_vec(),
+            secrets: v.secrets.to_vec(),
+            hybrid: v.hybrid.cloned(),
+            pub_adm: v.pub_adm.to_vec(),
+            n,
+            proofs,
+            delegations: Vec::new(),
+            value_sets: v.value_sets.to_vec(),
+            policy: v.policy.to_vec(),
+            redacted: Vec::new(),
+            slots: v.slots.to_vec(),
+            filled: Vec::new(),
+            limits: v.limits.to_vec(),
+            tags: Vec::new(),
+            windows: v.windows.to_vec(),
+            times: vec![None; n],
+            thresholds: v.thresholds.to_vec()
+        }
+    }
+
+    fn signed(sig: &Signature) -> Signed<'_> {
+        Signed {
+            hashes: &sig.hashes,
+            secrets: &sig.secrets,
+            hybrid: sig.hybrid.as_ref(),
+            pub_adm: &sig.pub_adm,
+            value_sets: &sig.value_sets,
+            policy: &sig.policy,
+            slots: &sig.slots,
+            limits: &sig.limits,
+            windows: &sig.windows,
+            thresholds: &sig.thresholds
+        }
+    }
+
+    fn values_valid(sig: &Signature, m: &[String]) -> bool {
+        sig.value_sets.len() == m.len() && (0..m.len()).all(|j| match &sig.value_sets[j] {
+            Some(vs) => sig.redacted.contains(&j) || Merkle::verify(&vs.root, &m[j], &vs.membership),
+            None => true,
+        })
+    }
+
+    //Message of length sig.n from the remaining blocks m, the redacted blocks are empty. None if the lengths do
+    //not match or if the redacted blocks are not sorted or not redactable.
+    fn expand(m: &[String], sig: &Signature) -> Option<Vec<String>> {
+        let n = sig.n;
+        if sig.slots.len() > n || sig.filled.windows(2).any(|w| w[0] >= w[1]) || 
+            sig.filled.iter().any(|j| *j >= n || *j + sig.slots.len() < n) {
+            return None;
+        }
+        let unfilled = sig.slots.len() - sig.filled.len();
+        if m.len() + sig.redacted.len() + unfilled != n || sig.policy.len() != n || sig.limits.len() != n || 
+            sig.times.len() != n || sig.thresholds.len() != n || 
+            sig.redacted.windows(2).any(|w| w[0] >= w[1]) || sig.redacted.iter().any(|j| *j >= n || !sig.policy[*j].redact) {
+            return None;
+        }
+        let mut blocks = m.iter();
+        Some((0..n).map(|j| {
+            if sig.redacted.contains(&j) || Self::unfilled(sig, j) {
+                return String::new();
+            }
+            blocks.next().unwrap().clone()
+        }).collect())
+    }
+
+    fn input(sig: &Signature, j: usize, mj: &str) -> String {
+        block_input(j, mj, sig.redacted.contains(&j), Self::unfilled(sig, j))
+    }
+
+    fn unfilled(sig: &Signature, j: usize) -> bool {
+        j < sig.n && j + sig.slots.len() >= sig.n && !sig.filled.contains(&j)
+    }
+
+    //Whether block j is covered by a VRS proof of the sanitizers instead of the signer's Schnorr proof.
+    fn sanitized(sig: &Signature, j: usize) -> bool {
+        sig.pub_adm[j] || sig.redacted.contains(&j) || sig.filled.contains(&j)
+    }
+
+    //Checks the VRS proof of the sanitized block j on t. The proof of a limited block is linkable on the count of
+    //the last log entry of the block with its linking value, and also covers the log up to this entry.
+    fn verify_pz(pp: &PublicParams, san_pks: &[SanitizerPublicKey], sig: &Signature, j: usize, t: String) -> bool {
+        let pz = match &sig.proofs[j].pz {
+            Some(pz) => pz,
+            None => return false,
+        };
+        if !sig.windows.is_empty() && sig.times[j].is_none() {
+            return false;
+        }
+        let ring = Self::block_ring(&Self::active(san_pks, &sig.windows, sig.times[j]), &sig.delegations, j);
+        let t = t + Self::delegation_chain(&sig.delegations, j).as_str() + Self::time_message(sig.times[j]).as_str();
+        if sig.limits[j].is_none() {
+            return VRS::verify(&pp.p, &pp.q, &pp.g, &ring, &t, pz, pp.fb_vrs.as_ref());
+        }
+        let e = match sig.tags.iter().rposition(|x| x.block == j && x.z == pz.z) {
+            Some(e) => e,
+            None => return false,
+        };
+        let t = t + Self::tags_message(&sig.tags[..e + 1]).as_str();
+        let h = VRS::link_base(&pp.p, &pp.q, &Self::limit_tag(&sig.s, j, sig.tags[e].c));
+        VRS::verify_linkable(&pp.q, &pp.g, &ring, &h, &t, pz, pp.fb_vrs.as_ref())
+    }
+
+    //Every log entry is on a limited block with a count within its limit, and no count of a sanitizer (same
+    //linking value) is used twice on a block.
+    fn tags_valid(sig: &Signature, n: usize) -> bool {
+        sig.tags.iter().enumerate().all(|(e, x)| {
+            let l = match sig.limits.get(x.block) {
+                Some(Some(l)) if x.block < n => *l,
+                _ => return false,
+            };
+            x.c >= 1 && x.c <= l && !sig.tags[..e].contains(x)
+        })
+    }
+
+    //Log of sig followed by an entry for each limited block of changed, with the smallest count for which the
+    //sanitizer has no entry on the block yet.
+    fn next_tags(
+        pp: &PublicParams, sk_z: &SanitizerSecretKey, sig: &Signature, changed: &[usize]
+    ) -> Result<Vec<LimitTag>, String> {
+        let mut tags = sig.tags.clone();
+        for j in changed.iter() {
+            let l = match sig.limits[*j] {
+                Some(l) => l,
+                None => continue,
+            };
+            let next = (1..=l)
+                .map(|c| LimitTag { block: *j, c, z: VRS::link(&pp.p, &pp.q, &sk_z.skp, &Self::limit_tag(&sig.s, *j, c)) })
+                .find(|x| !sig.tags.contains(x));
+            match next {
+                Some(x) => tags.push(x),
+                None => return Err("The sanitization limit of the block is reached".to_string()),
+            }
+        }
+        Ok(tags)
+    }
+
+    //Sanitizers whose rights are valid at time t: all of them without windows, none without a time.
+    fn active(
+        san_pks: &[SanitizerPublicKey], windows: &[Option<Window>], t: Option<u64>
+    ) -> Vec<SanitizerPublicKey> {
+        if windows.is_empty() {
+            return san_pks.to_vec();
+        }
+        let t = match t {
+            Some(t) => t,
+            None => return Vec::new(),
+        };
+        san_pks.iter().zip(windows.iter())
+            .filter(|(_, w)| in_window(w, t))
+            .map(|(x, _)| x.clone())
+            .collect()
+    }
+
+    fn windows_well_formed(sig: &Signature, k: usize) -> bool {
+        sig.windows.is_empty() || sig.windows.len() == k
+    }
+
+    //Every threshold block has a threshold between 1 and the size of its group, distinct members in san_pks and a
+    //share per member.
+    fn thresholds_well_formed(sig: &Signature, k: usize) -> bool {
+        sig.thresholds.iter().flatten().all(|th| {
+            th.group.t >= 1 && th.group.t <= th.group.members.len() &&
+                members_valid(&th.group.members, k) && th.shares.len() == th.group.members.len()
+        })
+    }
+
+    fn is_threshold(sig: &Signature, j: usize) -> bool {
+        matches!(sig.thresholds.get(j), Some(Some(_)))
+    }
+
+    //Checks the chameleon hash of block j on the input mj, the key-exposure-free one for a threshold block.
+    fn chash_valid(pp: &PublicParams, sig: &Signature, j: usize, mj: &String) -> bool {
+        let hv = &sig.hashes[j];
+        match sig.thresholds.get(j) {
+            Some(Some(th)) => CHash::check_kef(&pp.p, &pp.q, &pp.g, &hv.pkch, &hash(mj), (&hv.r, &th.s), &hv.h),
+            _ => CHash::check(&pp.p, &pp.g, &hv.pkch, &hash(mj), &hv.r, &hv.h, pp.fb_chash.as_ref()),
+        }
+    }
+
+    fn time_message(t: Option<u64>) -> String {
+        match t {
+            Some(t) => ":t".to_string() + t.to_string().as_str(),
+            None => String::new(),
+        }
+    }
+
+    //Tag of the linkable proof of the c-th sanitization of block j.
+    fn limit_tag(s: &k256::schnorr::Signature, j: usize, c: usize) -> String {
+        let t = "limit:".to_string() + encode(&s.to_bytes()).as_str();
+        t + ":" + j.to_string().as_str() + ":" + c.to_string().as_str()
+    }
+
+    fn tags_message(tags: &[LimitTag]) -> String {
+        let mut t = String::new();
+        for x in tags.iter() {
+            t.push(':');
+            t.push_str(x.block.to_string().as_str());
+            t.push(',');
+            t.push_str(x.c.to_string().as_str());
+            t.push(',');
+            t.push_str(x.z.to_str_radix(36).as_str());
+        }
+        t
+    }
+
+    //Seeds of the trapdoors of a compact signature for the sanitizer ip of san_pks.
+    fn open_seeds(sk_z: &SanitizerSecretKey, ip: Option<usize>, k: usize, sig: &Signature) -> Result<Option<Vec<u8>>, String> {
+        let (hybrid, ip) = match (&sig.hybrid, ip) {
+            (Some(hybrid), Some(ip)) => (hybrid, ip),
+            _ => return Ok(None),
+        };
+        if ip >= hybrid.len() || hybrid.len() != k {
+            return Err("Missing hybrid ciphertext for the chosen sanitizer".to_string());
+        }
+        let pt = Hybrid::decrypt(&sk_z.skk, &hybrid[ip], sig.n.to_string().as_bytes())?;
+        if pt.len() != sig.n * SEED_LEN {
+            return Err("Invalid trapdoor vector".to_string());
+        }
+        Ok(Some(pt))
+    }
+
+    //Trapdoor of block j for the sanitizer ip of san_pks, or for the delegate pk_z when ip is None (the last
+    //delegation of block j to pk_z). Zero when the block is not admissible for the sanitizer.
+    fn trapdoor(
+        pp: &PublicParams, sk_z: &SanitizerSecretKey, pk_z: &SanitizerPublicKey, ip: Option<usize>,
+        seeds: Option<&Vec<u8>>, sig: &Signature, j: usize
+    ) -> BigInt {
+        let zero = BigInt::from(0);
+        if let Some(ip) = ip {
+            return match seeds {
+                Some(x) if x[j * SEED_LEN..(j + 1) * SEED_LEN].iter().all(|b| *b == 0) => zero,
+                Some(x) => CHash::trapdoor_from_seed(&pp.q, &x[j * SEED_LEN..(j + 1) * SEED_LEN]),
+                None => PKE::decrypt(&sk_z.ske, &sig.secrets[ip][j]),
+            };
+        }
+        match sig.delegations.iter().rev().find(|d| d.pk_d.pkp == pk_z.pkp && d.blocks.contains(&j)) {
+            Some(d) => PKE::decrypt(&sk_z.ske, &d.secrets[d.blocks.iter().position(|b| *b == j).unwrap()]),
+            None => zero,
+        }
+    }
+
+    //Ring of the sanitization proofs of block j: the sanitizers, then the delegates of block j.
+    fn block_ring(san_pks: &[SanitizerPublicKey], delegations: &[Delegation], j: usize) -> Vec<BigInt> {
+        let mut ring: Vec<BigInt> = san_pks.iter().map(|x| x.pkp.clone()).collect();
+        for d in delegations.iter().filter(|d| d.blocks.contains(&j)) {
+            ring.push(d.pk_d.pkp.clone());
+        }
+        ring
+    }
+
+    //Certificates of the delegations of block j, covered by its sanitization proof.
+    fn delegation_chain(delegations: &[Delegation], j: usize) -> String {
+        let mut t = String::new();
+        for d in delegations.iter().filter(|d| d.blocks.contains(&j)) {
+            t.push(':');
+            t.push_str(d.cert.r.to_str_radix(36).as_str());
+            t.push_str(d.cert.z.to_str_radix(36).as_str());
+        }
+        t
+    }
+
+    fn delegation_message(
+        s: &k256::schnorr::Signature, prev: &[Delegation], pk_d: &SanitizerPublicKey, blocks: &[usize],
+        secrets: &[Ciphertext]
     ) -> String {
+        let mut t = "delegation".to_string();
+        t.push_str(encode(&s.to_bytes()).as_str());
+        for d in prev.iter() {
+            t.push(':');
+            t.push_str(d.cert.r.to_str_radix(36).as_str());
+            t.push_str(d.cert.z.to_str_radix(36).as_str());
+        }
+        t.push(':');
+        t.push_str(pk_d.pkp.to_str_radix(36).as_str());
+        t.push_str(pk_d.pke.n.to_str_radix(36).as_str());
+        for (j, c) in blocks.iter().zip(secrets.iter()) {
+            t.push(':');
+            t.push_str(j.to_string().as_str());
+            t.push_str(c.to_str_radix(36).as_str());
+        }
+        t
+    }
+
+    //Checks the certificates of the delegations of sig, each one over san_pks and the previous delegates.
+    fn verify_delegations(pp: &PublicParams, san_pks: &[SanitizerPublicKey], sig: &Signature, n: usize) -> bool {
+        let mut ring: Vec<BigInt> = san_pks.iter().map(|x| x.pkp.clone()).collect();
+        for (x, d) in sig.delegations.iter().enumerate() {
+            if d.blocks.is_empty() || d.blocks.iter().any(|j| *j >= n) || d.secrets.len() != d.blocks.len() || 
+                san_pks.iter().any(|y| y.pkp == d.pk_d.pkp) {
+                return false;
+            }
+            let t = Self::delegation_message(&sig.s, &sig.delegations[..x], &d.pk_d, &d.blocks, &d.secrets);
+            if !VRS::verify(&pp.p, &pp.q, &pp.g, &ring, &t, &d.cert, pp.fb_vrs.as_ref()) {
+                return false;
+            }
+            ring.push(d.pk_d.pkp.clone());
+        }
+        true
+    }
+
+    fn revocation_message(pk_s: &SignerPublicKey, epoch: u64, revoked: &[BigInt]) -> String {
+        let mut t = "revocation".to_string();
+        t.push_str(encode(&pk_s.pk).as_str());
+        t.push(':');
+        t.push_str(epoch.to_string().as_str());
+        for pkp in revoked.iter() {
+            t.push(':');
+            t.push_str(pkp.to_str_radix(36).as_str());
+        }
+        t
+    }
+
+    //Checks that td are the trapdoors of sig, a signature on m that has not been sanitized yet.
+    fn check_trapdoors(
+        pp: &PublicParams, san_pks: &[SanitizerPublicKey], m: &[String], sig: &Signature, td: &Trapdoors
+    ) -> Result<(), String> {
+        let k = san_pks.len();
+        let n = m.len();
+        if sig.hybrid.is_some() {
+            return Err("Compact signatures are not supported".to_string());
+        }
+        if sig.proofs.iter().any(|p| p.pz.is_some()) {
+            return Err("The signature has already been sanitized".to_string());
+        }
+        if !sig.delegations.is_empty() {
+            return Err("The signature has delegations".to_string());
+        }
+        if !sig.slots.is_empty() {
+            return Err("Signatures with append slots are not supported".to_string());
+        }
+        if sig.thresholds.iter().any(|th| th.is_some()) {
+            return Err("Signatures with threshold blocks are not supported".to_string());
+        }
+        if sig.hashes.len() != n || sig.pub_adm.len() != n || sig.policy.len() != n || 
+            !Self::secrets_well_formed(sig, k, n) || td.skch.len() != n || td.adm.len() != k || 
+            td.adm.iter().any(|a| a.len() != n) {
+            return Err("The trapdoors do not match the signature".to_string());
+        }
+        let b = all_range(n, |j| {
+            let mj = Self::input(sig, j, &m[j]);
+            FixedBase::pow_or(pp.fb_chash.as_ref(), &pp.g, &td.skch[j], &pp.p) == sig.hashes[j].pkch && 
+                CHash::check(&pp.p, &pp.g, &sig.hashes[j].pkch, &hash(&mj), 
+                    &sig.hashes[j].r, &sig.hashes[j].h, pp.fb_chash.as_ref())
+        });
+        if !b {
+            return Err("The trapdoors do not match the signature".to_string());
+        }
+        Ok(())
+    }
+
+    fn secrets_well_formed(sig: &Signature, k: usize, n: usize) -> bool {
+        match &sig.hybrid {
+            Some(hybrid) => hybrid.len() == k && sig.secrets.is_empty(),
+            None => sig.secrets.len() == k && sig.secrets.iter().all(|c| c.len() == n),
+        }
+    }
+
+    fn generate_ms(v: &Signed, pk_s: &SignerPublicKey, san_pks: &[SanitizerPublicKey]) -> String {
+        let Signed { hashes, secrets, hybrid, pub_adm, value_sets, policy, slots, limits, windows, thresholds } = *v;
         let k = san_pks.len();
         let n = hashes.len();
         let mut ms = String::new();
@@ -227,11 +1643,53 @@ impl KSan {
             ms.push_str(hashes[j].h.to_str_radix(36).as_str());
             ms.push_str(hashes[j].pkch.to_str_radix(36).as_str());
             ms.push_str(if pub_adm[j] { "1" } else { "0" });
+            ms.push_str(if policy[j].modify { ":m" } else { ":-" });
+            ms.push_str(if policy[j].redact { "r" } else { "-" });
+            if let Some(l) = limits[j] {
+                ms.push_str(":l");
+                ms.push_str(l.to_string().as_str());
+            }
+            if let Some(vs) = &value_sets[j] {
+                ms.push(':');
+                ms.push_str(encode(&vs.root).as_str());
+            }
+            if let Some(th) = &thresholds[j] {
+                ms.push_str(":t");
+                ms.push_str(th.group.t.to_string().as_str());
+                for (i, c) in th.group.members.iter().zip(th.shares.iter()) {
+                    ms.push(',');
+                    ms.push_str(i.to_string().as_str());
+                    ms.push(',');
+                    ms.push_str(c.to_str_radix(36).as_str());
+                }
+            }
             for i in 0..k {
                 ms.push_str(san_pks[i].pkp.to_str_radix(36).as_str());
                 ms.push_str(san_pks[i].pke.n.to_str_radix(36).as_str());
                 ms.push_str(san_pks[i].pke.nn.to_str_radix(36).as_str());
-                ms.push_str(secrets[i][j].0.as_ref().to_str_radix(36).as_str());
+                if hybrid.is_none() {
+                    ms.push_str(secrets[i][j].to_str_radix(36).as_str());
+                }
+            }
+        }
+        if let Some(hybrid) = hybrid {
+            for (pk_z, c) in san_pks.iter().zip(hybrid.iter()) {
+                ms.push_str(encode(&pk_z.pkk.to_sec1_bytes()).as_str());
+                ms.push_str(encode(&c.ek).as_str());
+                ms.push_str(encode(&c.ct).as_str());
+            }
+            ms.push_str(hybrid.len().to_string().as_str());
+        }
+        for i in slots.iter() {
+            ms.push_str(":s");
+            ms.push_str(i.to_string().as_str());
+        }
+        for w in windows.iter() {
+            ms.push_str(":w");
+            if let Some(w) = w {
+                ms.push_str(w.from.to_string().as_str());
+                ms.push('-');
+                ms.push_str(w.until.to_string().as_str());
             }
         }
         ms.push_str(encode(&pk_s.pk).as_str());
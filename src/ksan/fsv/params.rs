@@ -1,6 +1,10 @@
 use num_bigint::BigInt;
-use kzen_paillier::RawCiphertext;
+use crate::blocks::pke::Ciphertext;
+use crate::blocks::fixed_base::FixedBase;
+use crate::blocks::hybrid::HybridCiphertext;
 use crate::blocks::vrs::VRSSignature;
+pub use crate::ksan::common::{ValueSet, Policy, Window, Group};
+use crate::ksan::common::default_policy;
 
 #[derive(Clone, Debug)]
 pub struct SecParams{
@@ -13,7 +17,9 @@ pub struct PublicParams{
     pub secp: SecParams,
     pub p: BigInt,
     pub q: BigInt,
-    pub g: BigInt
+    pub g: BigInt,
+    pub fb_chash: Option<FixedBase>,
+    pub fb_vrs: Option<FixedBase>
 }
 
 #[derive(Clone, Debug)]
@@ -29,13 +35,15 @@ pub struct SignerSecretKey{
 #[derive(Clone, Debug)]
 pub struct SanitizerPublicKey{
     pub pke: kzen_paillier::EncryptionKey,
-    pub pkp: BigInt
+    pub pkp: BigInt,
+    pub pkk: k256::PublicKey
 }
 
 #[derive(Clone, Debug)]
 pub struct SanitizerSecretKey{
     pub ske: kzen_paillier::DecryptionKey,
-    pub skp: BigInt
+    pub skp: BigInt,
+    pub skk: k256::SecretKey
 }
 
 #[derive(Clone, Debug)]
@@ -58,11 +66,213 @@ pub struct Mod {
 }
 
 #[derive(Clone, Debug)]
-pub struct Signature<'d>{
+pub struct Signature{
     pub s: k256::schnorr::Signature,
     pub hashes: Vec<CHashPubValues>,
-    pub secrets: Vec<Vec<RawCiphertext<'d>>>,
+    pub secrets: Vec<Vec<Ciphertext>>,
+    //Compact encoding (KSan::sign_compact): one hybrid ciphertext of the trapdoor vector per sanitizer, secrets is empty.
+    pub hybrid: Option<Vec<HybridCiphertext>>,
     pub pub_adm: Vec<bool>,
     pub n: usize,
-    pub proofs: Vec<Proof>
+    pub proofs: Vec<Proof>,
+    //Delegations of sanitization rights to keys that are not in san_pks (KSan::delegate), in order.
+    pub delegations: Vec<Delegation>,
+    //Allowed values of each block (KSan::sign_with_values), None for any value.
+    pub value_sets: Vec<Option<ValueSet>>,
+    //Operations allowed on each block (KSan::sign_with_policy), and the original indexes of the redacted blocks.
+    pub policy: Vec<Policy>,
+    pub redacted: Vec<usize>,
+    //Owner (index in san_pks) of each append slot (KSan::sign_with_slots), the slots are the last blocks of the
+    //message. filled holds the original indexes of the slots that have been filled.
+    pub slots: Vec<usize>,
+    pub filled: Vec<usize>,
+    //Maximum number of sanitizations of each block by each sanitizer (KSan::sign_with_limits), None for no limit.
+    //tags logs the counts used by the sanitizations of the limited blocks, in order.
+    pub limits: Vec<Option<usize>>,
+    pub tags: Vec<LimitTag>,
+    //Validity window of the rights of each sanitizer (KSan::sign_with_windows), empty for no windows, and time of
+    //the last sanitization of each block (SanitizeOptions::now).
+    pub windows: Vec<Option<Window>>,
+    pub times: Vec<Option<u64>>,
+    //Group and encrypted trapdoor shares of each threshold block (KSan::sign_with_thresholds), None for the others.
+    pub thresholds: Vec<Option<Threshold>>
+}
+
+//Shamir shares mod q of the chameleon trapdoor of a threshold block, shares[x] is encrypted under the sanitizer
+//group.members[x] and is the value of the sharing polynomial at x + 1. A threshold block uses the key-exposure-free
+//chameleon hash (CHash::hash_kef), whose randomness is the r of the block and s, which is not signed.
+#[derive(Clone, Debug)]
+pub struct Threshold{
+    pub group: Group,
+    pub shares: Vec<Ciphertext>,
+    pub s: BigInt
+}
+
+//Secret nonces (d, e) of the sanitizer member for the given threshold blocks (KSan::sanitize_commit). They are
+//consumed by the partial adaptation of member, and must never be used for another one.
+#[derive(Debug)]
+pub struct Nonces{
+    pub member: usize,
+    pub signers: Vec<usize>,
+    pub blocks: Vec<usize>,
+    pub d: Vec<BigInt>,
+    pub e: Vec<BigInt>
+}
+
+//Commitments g^d, g^e mod p to the nonces of the sanitizer member (KSan::sanitize_commit), sent to the other
+//signers of the threshold sanitization.
+#[derive(Clone, Debug)]
+pub struct NonceCommitment{
+    pub member: usize,
+    pub signers: Vec<usize>,
+    pub blocks: Vec<usize>,
+    pub d: Vec<BigInt>,
+    pub e: Vec<BigInt>
+}
+
+//Share of the sanitizer member (index in san_pks) of the threshold Schnorr signatures that give the new randomness
+//of the given threshold blocks, for a sanitization in which the sanitizers signers cooperate
+//(KSan::sanitize_partial).
+#[derive(Clone, Debug)]
+pub struct PartialAdaptation{
+    pub member: usize,
+    pub signers: Vec<usize>,
+    pub blocks: Vec<usize>,
+    pub rho: Vec<BigInt>
+}
+
+//Count c used by a sanitization of a limited block, with the linking value z of the sanitizer on (block, c).
+#[derive(Clone, Debug, PartialEq)]
+pub struct LimitTag{
+    pub block: usize,
+    pub c: usize,
+    pub z: BigInt
+}
+
+//Rights of a sanitizer over some blocks handed to pk_d: the trapdoors of the blocks encrypted under pk_d and a
+//certificate of the delegating sanitizer (VRS ring signature over san_pks and the previous delegates).
+#[derive(Clone, Debug)]
+pub struct Delegation{
+    pub pk_d: SanitizerPublicKey,
+    pub blocks: Vec<usize>,
+    pub secrets: Vec<Ciphertext>,
+    pub cert: VRSSignature
+}
+
+//Chameleon trapdoors of the blocks of a signature and its admissibility matrix, returned by
+//KSan::sign_with_trapdoors. They stay with the signer.
+#[derive(Clone, Debug)]
+pub struct Trapdoors{
+    pub skch: Vec<BigInt>,
+    pub adm: Vec<Vec<bool>>
+}
+
+//Sanitizer added to a signature by KSan::extend_sanitizers: its keys, the blocks granted to it and the trapdoors
+//returned with the signature.
+#[derive(Clone, Debug)]
+pub struct Extension{
+    pub pk_z: SanitizerPublicKey,
+    pub adm_z: Vec<bool>,
+    pub td: Trapdoors
+}
+
+//Sanitizer removed from a signature by KSan::revoke: its keys and the trapdoors returned with the signature.
+#[derive(Clone, Debug)]
+pub struct Revocation{
+    pub pk_z: SanitizerPublicKey,
+    pub td: Trapdoors
+}
+
+//Sanitizers revoked by a signer (VRS public keys) at a given epoch, signed by the signer
+//(KSan::revocation_list).
+#[derive(Clone, Debug)]
+pub struct RevocationList{
+    pub epoch: u64,
+    pub revoked: Vec<BigInt>,
+    pub s: k256::schnorr::Signature
+}
+
+//Message independent material of one block, computed by KSan::sign_offline.
+#[derive(Clone, Debug)]
+pub struct PreBlock{
+    pub skch: BigInt,
+    pub pkch: BigInt,
+    pub r: BigInt,
+    pub pkr: BigInt,
+    pub c_trap: Vec<Ciphertext>,
+    pub c_zero: Vec<Ciphertext>
+}
+
+//Pool of precomputed blocks for a given set of sanitizers. KSan::sign_online consumes one block per message block.
+#[derive(Clone, Debug)]
+pub struct SignPool{
+    pub san_pks: Vec<SanitizerPublicKey>,
+    pub blocks: Vec<PreBlock>
+}
+
+//Options of a signature on n blocks (KSan::sign_with_options): the allowed values (None for any value), policy,
+//limit and threshold group of each block, and the append slots and validity window of each sanitizer (empty for
+//none). SignOptions::new(n) has no option set.
+#[derive(Clone, Debug)]
+pub struct SignOptions{
+    pub values: Vec<Option<Vec<String>>>,
+    pub policy: Vec<Policy>,
+    pub slots: Vec<usize>,
+    pub limits: Vec<Option<usize>>,
+    pub windows: Vec<Option<Window>>,
+    pub groups: Vec<Option<Group>>
+}
+
+impl SignOptions {
+    pub fn new(n: usize) -> Self {
+        SignOptions {
+            values: vec![None; n],
+            policy: default_policy(n),
+            slots: Vec::new(),
+            limits: vec![None; n],
+            windows: Vec::new(),
+            groups: vec![None; n]
+        }
+    }
+}
+
+//Inputs of a sanitization of the message m (KSan::sanitize_with_options): the modifications, the blocks to remove,
+//which must be redactable by the policy of the signature, the blocks that fill the next unfilled slots of the
+//sanitizer in order, and the value sets given by the signer for the modified blocks (empty if the blocks have none).
+//m is the current message (without the unfilled slots), the filled slots are at the end of the message. A redacted
+//block gets a chameleon hash collision with a redaction marker and a VRS proof over it. After a redaction, m is the
+//list of the remaining blocks while the block indexes (Mod::i, redact) keep referring to the original positions.
+//SanitizeOptions::new(m, modif) redacts and appends nothing and has no value set, time, nonce commitment or
+//partial adaptation.
+#[derive(Clone, Debug)]
+pub struct SanitizeOptions{
+    pub m: Vec<String>,
+    pub modif: Vec<Mod>,
+    pub redact: Vec<usize>,
+    pub append: Vec<String>,
+    pub values: Vec<Option<Vec<String>>>,
+    //Time of the sanitization on the caller's clock, needed with windows and checked against the validity window
+    //of the sanitizer. This only enforces expiry against an honest clock: the sanitizer picks now and can backdate
+    //it into its window.
+    pub now: Option<u64>,
+    //Nonce commitments (KSan::sanitize_commit) and partial adaptations (KSan::sanitize_partial) of the threshold
+    //blocks modified by modif, one of each by every sanitizer that cooperates, empty if no threshold block is
+    //modified.
+    pub commitments: Vec<NonceCommitment>,
+    pub partials: Vec<PartialAdaptation>
+}
+
+impl SanitizeOptions {
+    pub fn new(m: &[String], modif: &[Mod]) -> Self {
+        SanitizeOptions {
+            m: m.to_vec(),
+            modif: modif.to_vec(),
+            redact: Vec::new(),
+            append: Vec::new(),
+            values: Vec::new(),
+            now: None,
+            commitments: Vec::new(),
+            partials: Vec::new()
+        }
+    }
 }
\ No newline at end of file
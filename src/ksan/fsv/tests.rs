@@ -1,12 +1,27 @@
 #[cfg(test)]
 mod tests {
     use crate::blocks::chash::CHash;
+    use crate::blocks::pke::PKE;
+    use crate::blocks::shamir::Shamir;
     use crate::blocks::sig::SIG;
     use crate::ksan::fsv::ksan::KSan;
-    use crate::ksan::fsv::params::{SecParams, Mod, Proof};
+    use crate::ksan::fsv::params::{SecParams, Mod, Policy, Proof, Window, Group, SignOptions, SanitizeOptions, Extension,
+        Revocation, PublicParams, SignerSecretKey, SignerPublicKey, SanitizerSecretKey, SanitizerPublicKey};
     use crate::blocks::vrs::VRS;
+    use crate::blocks::merkle::Merkle;
     use crate::ksan::hash::{encode, hash};
+    use crate::ksan::common::block_input;
     use num_bigint::BigInt;
+    use num_integer::Integer;
+
+    //512-bit public parameters, a signer key pair and N sanitizer key pairs.
+    fn fixture<const N: usize>() -> (PublicParams, SignerSecretKey, SignerPublicKey, [(SanitizerSecretKey, SanitizerPublicKey); N]) {
+        let secp = SecParams { bits_chash_vrs: 512, bits_pke: 520 };
+        let pp = KSan::setup(&secp).unwrap();
+        let (sk_s, pk_s) = KSan::kgen_s(&pp).unwrap();
+        let sans = std::array::from_fn(|_| KSan::kgen_z(&pp).unwrap());
+        (pp, sk_s, pk_s, sans)
+    }
 
     #[test]
     fn test_fsv_ksan() {
@@ -90,7 +105,7 @@ mod tests {
         t = t + "0" + m[0].as_str();
         t = t + encode(&sig.s.to_bytes()).as_str();
         ring.push(pk_z4.pkp.clone());
-        let temp = VRS::sign(&pp.p, &pp.q, &pp.g, &sk_z4.skp, &ring, &t);
+        let temp = VRS::sign(&pp.p, &pp.q, &pp.g, &sk_z4.skp, &ring, &t, pp.fb_vrs.as_ref());
         ring.pop();
         sig_false.proofs[1] = Proof {
             ps: None,
@@ -105,7 +120,7 @@ mod tests {
         let mut t = "".to_string();
         t = t + "0" + m[0].as_str();
         t = t + encode(&sig.s.to_bytes()).as_str();
-        let proof = VRS::sign(&pp.p, &pp.q, &pp.g, &sk_z1.skp, &ring, &t);
+        let proof = VRS::sign(&pp.p, &pp.q, &pp.g, &sk_z1.skp, &ring, &t, pp.fb_vrs.as_ref());
         sig_false.proofs[0] = Proof {
             ps: None,
             pz: Some(proof)
@@ -122,7 +137,7 @@ mod tests {
         //Test modification of a chameleon hash that is correct over the same message
         let mut sig_false = sigp2.clone();
         let mj: String = "0".to_string() + m[0].as_str();
-        let (h, r) = CHash::hash(&pp.p, &pp.q, &pp.g, &sig.hashes[0].pkch, &hash(&mj));
+        let (h, r) = CHash::hash(&pp.p, &pp.q, &pp.g, &sig.hashes[0].pkch, &hash(&mj), pp.fb_chash.as_ref());
         sig_false.hashes[0].h = h.clone();
         sig_false.hashes[0].r = r.clone();
         let b = KSan::verify(&pp, &pk_s, &san_pks, &m, &sig_false).unwrap();
@@ -168,4 +183,762 @@ mod tests {
         let b = KSan::verify(&pp, &pk_s, &vec![pk_z1.clone(), pk_z2.clone()], &m, &sig_false).unwrap();
         assert!(!b, "Verification should fail if an adversary removes a sanitizer public key");
     }
+
+    #[test]
+    fn test_fsv_verify_batch() {
+        let (mut pp, sk_s, pk_s, [(sk_z1, pk_z1), (_sk_z2, pk_z2)]) = fixture();
+        KSan::precompute(&mut pp, 5).unwrap();
+        let san_pks = vec![pk_z1.clone(), pk_z2.clone()];
+        let mut adm = vec![vec![false; 2]; 2];
+        adm[0][1] = true;
+
+        let mut ms: Vec<Vec<String>> = Vec::new();
+        let mut sigs = Vec::new();
+        for i in 0..3 {
+            let m = vec![format!("not_adm{}", i), format!("adm{}", i)];
+            let sig = KSan::sign(&pp, &sk_s, &pk_s, &san_pks, &m, &adm).unwrap();
+            let modif = vec![Mod { i: 1, m: format!("modadm{}", i) }];
+            let sigp = KSan::sanitize(&pp, &sk_z1, &pk_s, &pk_z1, 
+                &san_pks, &m, &modif, &sig).unwrap();
+            ms.push(vec![m[0].clone(), format!("modadm{}", i)]);
+            sigs.push(sigp);
+        }
+        let san_pks_v = vec![san_pks.clone(); 3];
+
+        //Test batch verification of valid signatures
+        let (b, invalid) = KSan::verify_batch(&pp, &pk_s, &san_pks_v, &ms, &sigs).unwrap();
+        assert!(b, "Batch verification should succeed for valid signatures");
+        assert!(invalid.is_empty(), "Batch verification should not report valid signatures");
+
+        //Test batch verification with a modified message block
+        ms[1][0] = "testverfalse".to_string();
+        let (b, invalid) = KSan::verify_batch(&pp, &pk_s, &san_pks_v, &ms, &sigs).unwrap();
+        assert!(!b, "Batch verification should fail if an adversary modifies a message block");
+        assert_eq!(invalid, vec![1], "Batch verification should report the signature with a modified block");
+        ms[1][0] = "not_adm1".to_string();
+
+        //Test batch verification with a non-sanitized signature
+        let m = vec!["not_adm2".to_string(), "adm2".to_string()];
+        sigs[2] = KSan::sign(&pp, &sk_s, &pk_s, &san_pks, &m, &adm).unwrap();
+        ms[2] = m;
+        let (b, invalid) = KSan::verify_batch(&pp, &pk_s, &san_pks_v, &ms, &sigs).unwrap();
+        assert!(!b, "Batch verification should fail for non-sanitized signatures");
+        assert_eq!(invalid, vec![2], "Batch verification should report the non-sanitized signature");
+    }
+
+    #[test]
+    fn test_fsv_sign_online() {
+        let (mut pp, sk_s, pk_s, [(sk_z1, pk_z1), (sk_z2, pk_z2)]) = fixture();
+        KSan::precompute(&mut pp, 5).unwrap();
+        let san_pks = vec![pk_z1.clone(), pk_z2.clone()];
+        let mut adm = vec![vec![false; 3]; 2];
+        adm[0][1] = true;
+        adm[1][2] = true;
+        let m = vec!["not_adm".to_string(), "adm1".to_string(), "adm2".to_string()];
+        let mut pool = KSan::sign_offline(&pp, &san_pks, 7).unwrap();
+
+        //Test that a signature computed from the pool is sanitized and verified like a regular one
+        let sig = KSan::sign_online(&pp, &sk_s, &pk_s, &san_pks, &m, &adm, &mut pool).unwrap();
+        assert_eq!(pool.blocks.len(), 4, "Online signing should consume one block per message block");
+        let modif = vec![Mod { i: 1, m: "modadm1".to_string() }];
+        let sigp = KSan::sanitize(&pp, &sk_z1, &pk_s, &pk_z1, &san_pks, &m, &modif, &sig).unwrap();
+        let mp = vec!["not_adm".to_string(), "modadm1".to_string(), "adm2".to_string()];
+        let modif = vec![Mod { i: 2, m: "modadm2".to_string() }];
+        let sigp = KSan::sanitize(&pp, &sk_z2, &pk_s, &pk_z2, &san_pks, &mp, &modif, &sigp).unwrap();
+        let mp = vec!["not_adm".to_string(), "modadm1".to_string(), "modadm2".to_string()];
+        let b = KSan::verify(&pp, &pk_s, &san_pks, &mp, &sigp).unwrap();
+        assert!(b, "Verification of a sanitized online signature failed");
+
+        //Test that the admissible blocks are respected
+        let modif = vec![Mod { i: 2, m: "modadm2".to_string() }];
+        let r = KSan::sanitize(&pp, &sk_z1, &pk_s, &pk_z1, &san_pks, &m, &modif, &sig);
+        assert!(r.is_err(), "Sanitizing a non-admissible block of an online signature should fail");
+
+        //Test that the blocks are not reused
+        let sig2 = KSan::sign_online(&pp, &sk_s, &pk_s, &san_pks, &m, &adm, &mut pool).unwrap();
+        assert!(sig2.hashes.iter().all(|x| sig.hashes.iter().all(|y| x.pkch != y.pkch)), 
+            "Online signing should not reuse precomputed blocks");
+        let r = KSan::sign_online(&pp, &sk_s, &pk_s, &san_pks, &m, &adm, &mut pool);
+        assert!(r.is_err(), "Online signing should fail when the pool is exhausted");
+
+        //Test that the pool is bound to its set of sanitizers
+        let mut pool = KSan::sign_offline(&pp, &san_pks, 3).unwrap();
+        let r = KSan::sign_online(&pp, &sk_s, &pk_s, &[pk_z2.clone(), pk_z1.clone()], &m, &adm, &mut pool);
+        assert!(r.is_err(), "Online signing should fail for another set of sanitizers");
+
+        //Test that adm must have one row per sanitizer and one element per block
+        let r = KSan::sign_online(&pp, &sk_s, &pk_s, &san_pks, &m, &adm[..1], &mut pool);
+        assert!(r.is_err(), "Online signing should fail for a missing row of adm");
+        let r = KSan::sign_online(&pp, &sk_s, &pk_s, &san_pks, &m, &[vec![true; 2], vec![false; 2]], &mut pool);
+        assert!(r.is_err(), "Online signing should fail for a short row of adm");
+        let r = KSan::sign_with_trapdoors(&pp, &sk_s, &pk_s, &san_pks, &m, &[vec![true; 2], vec![false; 2]]);
+        assert!(r.is_err(), "Signing should fail for a short row of adm");
+        assert_eq!(pool.blocks.len(), 3, "A failed online signing should not consume the pool");
+    }
+
+    #[test]
+    fn test_fsv_sign_compact() {
+        let (pp, sk_s, pk_s, [(sk_z1, pk_z1), (sk_z2, pk_z2)]) = fixture();
+        let san_pks = vec![pk_z1.clone(), pk_z2.clone()];
+        let mut adm = vec![vec![false; 3]; 2];
+        adm[0][1] = true;
+        adm[1][2] = true;
+        let m = vec!["not_adm".to_string(), "adm1".to_string(), "adm2".to_string()];
+        let sig = KSan::sign_compact(&pp, &sk_s, &pk_s, &san_pks, &m, &adm).unwrap();
+
+        //Test that the compact signature has one hybrid ciphertext per sanitizer, all of the same length
+        let hybrid = sig.hybrid.clone().unwrap();
+        assert!(sig.secrets.is_empty(), "Compact signatures should not contain Paillier ciphertexts");
+        assert_eq!(hybrid.len(), 2, "Compact signatures should contain one hybrid ciphertext per sanitizer");
+        assert_eq!(hybrid[0].ct.len(), hybrid[1].ct.len(), "Hybrid ciphertexts should not reveal admissible blocks");
+
+        //Test that the compact encoding of the trapdoors is smaller than the Paillier one
+        let sig_paillier = KSan::sign(&pp, &sk_s, &pk_s, &san_pks, &m, &adm).unwrap();
+        let size = KSan::signature_size(&sig);
+        let size_paillier = KSan::signature_size(&sig_paillier);
+        assert!(size.get("ciphertexts") * 2 < size_paillier.get("ciphertexts"), "Compact ciphertexts should be smaller");
+        assert_eq!(size.get("chameleon"), size.components.iter().find(|c| c.0 == "chameleon").unwrap().1, 
+            "Size components should be accumulated");
+        assert_eq!(size.total(), size.components.iter().map(|c| c.1).sum::<usize>(), "Total size should sum the components");
+        assert_eq!(KSan::sanitizer_pk_size(&pk_z1).get("kem"), 33, "KEM public keys should be compressed points");
+
+        //Test sanitization and verification of a compact signature
+        let modif = vec![Mod { i: 1, m: "modadm1".to_string() }];
+        let sigp = KSan::sanitize(&pp, &sk_z1, &pk_s, &pk_z1, &san_pks, &m, &modif, &sig).unwrap();
+        let mp = vec!["not_adm".to_string(), "modadm1".to_string(), "adm2".to_string()];
+        let modif = vec![Mod { i: 2, m: "modadm2".to_string() }];
+        let sigp = KSan::sanitize(&pp, &sk_z2, &pk_s, &pk_z2, &san_pks, &mp, &modif, &sigp).unwrap();
+        let mp = vec!["not_adm".to_string(), "modadm1".to_string(), "modadm2".to_string()];
+        let b = KSan::verify(&pp, &pk_s, &san_pks, &mp, &sigp).unwrap();
+        assert!(b, "Verification of a sanitized compact signature failed");
+
+        //Test that the admissible blocks are respected
+        let modif = vec![Mod { i: 2, m: "modadm2".to_string() }];
+        let r = KSan::sanitize(&pp, &sk_z1, &pk_s, &pk_z1, &san_pks, &m, &modif, &sig);
+        assert!(r.is_err(), "Sanitizing a non-admissible block of a compact signature should fail");
+
+        //Test that the hybrid ciphertexts are bound to the signature
+        let mut sig_false = sigp.clone();
+        sig_false.hybrid.as_mut().unwrap().swap(0, 1);
+        let b = KSan::verify(&pp, &pk_s, &san_pks, &mp, &sig_false).unwrap();
+        assert!(!b, "Verification should fail if an adversary swaps hybrid ciphertexts");
+        let mut sig_false = sigp.clone();
+        sig_false.hybrid.as_mut().unwrap().pop();
+        let b = KSan::verify(&pp, &pk_s, &san_pks, &mp, &sig_false).unwrap();
+        assert!(!b, "Verification should fail if an adversary removes a hybrid ciphertext");
+    }
+
+    #[test]
+    fn test_fsv_extend_sanitizers() {
+        let (pp, sk_s, pk_s, [(sk_z1, pk_z1), (sk_z2, pk_z2), (_sk_z3, pk_z3)]) = fixture();
+        let san_pks = vec![pk_z1.clone()];
+        let mut adm = vec![vec![false; 3]; 1];
+        adm[0][1] = true;
+        let m = vec!["not_adm".to_string(), "adm1".to_string(), "adm2".to_string()];
+        let (sig, td) = KSan::sign_with_trapdoors(&pp, &sk_s, &pk_s, &san_pks, &m, &adm).unwrap();
+
+        //Test that a new sanitizer can be granted a block that was not admissible
+        let ext = Extension { pk_z: pk_z2.clone(), adm_z: vec![false, false, true], td: td.clone() };
+        let (sige, tde) = KSan::extend_sanitizers(&pp, &sk_s, &pk_s, &san_pks, &m, &sig, &ext).unwrap();
+        assert_eq!(tde.adm.len(), 2, "The trapdoors should record the admissible blocks of the new sanitizer");
+        let san_pks_e = vec![pk_z1.clone(), pk_z2.clone()];
+        let modif = vec![Mod { i: 1, m: "modadm1".to_string() }];
+        let sigp1 = KSan::sanitize(&pp, &sk_z1, &pk_s, &pk_z1, &san_pks_e, &m, &modif, &sige).unwrap();
+        let modif = vec![Mod { i: 2, m: "modadm2".to_string() }];
+        let mp1 = vec!["not_adm".to_string(), "modadm1".to_string(), "adm2".to_string()];
+        let sigp2 = KSan::sanitize(&pp, &sk_z2, &pk_s, &pk_z2, &san_pks_e, &mp1, &modif, &sigp1).unwrap();
+        let mp2 = vec!["not_adm".to_string(), "modadm1".to_string(), "modadm2".to_string()];
+        let b = KSan::verify(&pp, &pk_s, &san_pks_e, &mp2, &sigp2).unwrap();
+        assert!(b, "Verification of a signature sanitized by an added sanitizer failed");
+        let b = KSan::verify(&pp, &pk_s, &san_pks, &mp2, &sigp2).unwrap();
+        assert!(!b, "Verification should fail for the sanitizers of the original signature");
+
+        //Test that the new sanitizer only gets the granted blocks
+        let modif = vec![Mod { i: 1, m: "modadm1".to_string() }];
+        let r = KSan::sanitize(&pp, &sk_z2, &pk_s, &pk_z2, &san_pks_e, &m, &modif, &sige);
+        assert!(r.is_err(), "Sanitizing a block that was not granted to the added sanitizer should fail");
+
+        //Test that the trapdoors must match an unsanitized signature
+        let ext = Extension { pk_z: pk_z3.clone(), adm_z: vec![true; 3], td: tde.clone() };
+        let r = KSan::extend_sanitizers(&pp, &sk_s, &pk_s, &san_pks_e, &mp1, &sigp1, &ext);
+        assert!(r.is_err(), "Extending a sanitized signature should fail");
+        let (sig2, _td2) = KSan::sign_with_trapdoors(&pp, &sk_s, &pk_s, &san_pks, &m, &adm).unwrap();
+        let ext = Extension { pk_z: pk_z2.clone(), adm_z: vec![true; 3], td: td.clone() };
+        let r = KSan::extend_sanitizers(&pp, &sk_s, &pk_s, &san_pks, &m, &sig2, &ext);
+        assert!(r.is_err(), "Extending a signature with the trapdoors of another signature should fail");
+        let ext = Extension { pk_z: pk_z1.clone(), adm_z: vec![true; 3], td: td.clone() };
+        let r = KSan::extend_sanitizers(&pp, &sk_s, &pk_s, &san_pks, &m, &sig, &ext);
+        assert!(r.is_err(), "Adding a sanitizer that is already in the set should fail");
+    }
+
+    #[test]
+    fn test_fsv_revoke() {
+        let (pp, sk_s, pk_s, [(sk_z1, pk_z1), (sk_z2, pk_z2)]) = fixture();
+        let san_pks = vec![pk_z1.clone(), pk_z2.clone()];
+        let mut adm = vec![vec![false; 3]; 2];
+        adm[0][1] = true;
+        adm[0][2] = true;
+        adm[1][2] = true;
+        let m = vec!["not_adm".to_string(), "adm1".to_string(), "adm1+2".to_string()];
+        let (sig, td) = KSan::sign_with_trapdoors(&pp, &sk_s, &pk_s, &san_pks, &m, &adm).unwrap();
+
+        //Test that only the blocks of the revoked sanitizer get new chameleon keys
+        let rev = Revocation { pk_z: pk_z1.clone(), td: td.clone() };
+        let (sigr, tdr) = KSan::revoke(&pp, &sk_s, &pk_s, &san_pks, &m, &sig, &rev).unwrap();
+        assert_eq!(sigr.hashes[0].pkch, sig.hashes[0].pkch, "A block of no revoked sanitizer should be kept");
+        assert_ne!(sigr.hashes[2].pkch, sig.hashes[2].pkch, "A block of the revoked sanitizer should get a new key");
+        assert!(!sigr.pub_adm[1] && sigr.pub_adm[2], "The admissibility bits should follow the remaining sanitizers");
+        assert_eq!(tdr.adm, vec![adm[1].clone()], "The trapdoors should drop the revoked sanitizer");
+
+        //Test that the remaining sanitizers keep their rights and the revoked one lost them
+        let san_pks_r = vec![pk_z2.clone()];
+        let modif = vec![Mod { i: 2, m: "modadm1+2".to_string() }];
+        let sigp = KSan::sanitize(&pp, &sk_z2, &pk_s, &pk_z2, &san_pks_r, &m, &modif, &sigr).unwrap();
+        let mp = vec!["not_adm".to_string(), "adm1".to_string(), "modadm1+2".to_string()];
+        let b = KSan::verify(&pp, &pk_s, &san_pks_r, &mp, &sigp).unwrap();
+        assert!(b, "Verification of a signature sanitized by a remaining sanitizer failed");
+        let r = KSan::sanitize(&pp, &sk_z1, &pk_s, &pk_z1, &san_pks_r, &m, &modif, &sigr);
+        assert!(r.is_err(), "A revoked sanitizer should not be able to sanitize the new signature");
+        let r = KSan::sanitize(&pp, &sk_z1, &pk_s, &pk_z1, &san_pks, &m, &modif, &sigr);
+        assert!(!r.is_ok_and(|sigf| KSan::verify(&pp, &pk_s, &san_pks_r, &mp, &sigf).unwrap()), 
+            "A revoked sanitizer should not be able to sanitize the new signature with the old sanitizers");
+
+        //Test the revocation list
+        let rl = KSan::revocation_list(&pp, &sk_s, &pk_s, 1, std::slice::from_ref(&pk_z1)).unwrap();
+        assert!(KSan::verify_revocation_list(&pp, &pk_s, &rl).unwrap(), "Verification of a revocation list failed");
+        let mut rl_false = rl.clone();
+        rl_false.revoked.clear();
+        assert!(!KSan::verify_revocation_list(&pp, &pk_s, &rl_false).unwrap(), 
+            "Verification should fail if an adversary removes a key from the revocation list");
+        let b = KSan::verify_with_revocation(&pp, &pk_s, &san_pks_r, &mp, &sigp, &rl).unwrap();
+        assert!(b, "Verification against the revocation list should succeed for the new signature");
+        let modif = vec![Mod { i: 1, m: "modadm1".to_string() }, Mod { i: 2, m: "modadm1+2".to_string() }];
+        let sigo = KSan::sanitize(&pp, &sk_z1, &pk_s, &pk_z1, &san_pks, &m, &modif, &sig).unwrap();
+        let mo = vec!["not_adm".to_string(), "modadm1".to_string(), "modadm1+2".to_string()];
+        assert!(KSan::verify(&pp, &pk_s, &san_pks, &mo, &sigo).unwrap(), "Verification of the old signature failed");
+        let b = KSan::verify_with_revocation(&pp, &pk_s, &san_pks, &mo, &sigo, &rl).unwrap();
+        assert!(!b, "Verification against the revocation list should fail for a signature of a revoked sanitizer");
+    }
+
+    #[test]
+    fn test_fsv_delegate() {
+        let (pp, sk_s, pk_s, [(sk_z1, pk_z1), (sk_z2, pk_z2), (sk_d1, pk_d1), (sk_d2, pk_d2)]) = fixture();
+        let san_pks = vec![pk_z1.clone(), pk_z2.clone()];
+        let mut adm = vec![vec![false; 3]; 2];
+        adm[0][1] = true;
+        adm[1][2] = true;
+        let m = vec!["not_adm".to_string(), "adm1".to_string(), "adm2".to_string()];
+        let sig = KSan::sign(&pp, &sk_s, &pk_s, &san_pks, &m, &adm).unwrap();
+
+        //Test that only admissible blocks can be delegated, and not to a sanitizer
+        assert!(KSan::delegate(&pp, &sk_z1, &pk_z1, &san_pks, &pk_d1, &[2], &sig).is_err(), 
+            "A sanitizer should not be able to delegate a block it cannot modify");
+        assert!(KSan::delegate(&pp, &sk_z1, &pk_z1, &san_pks, &pk_z2, &[1], &sig).is_err(), 
+            "A sanitizer should not be able to delegate to another sanitizer");
+        let modif = vec![Mod { i: 1, m: "modadm1".to_string() }];
+        assert!(KSan::sanitize(&pp, &sk_d1, &pk_s, &pk_d1, &san_pks, &m, &modif, &sig).is_err(), 
+            "A key without delegation should not be able to sanitize");
+
+        //Test a delegation chain: z1 delegates block 1 to d1, who delegates it to d2
+        let sigd = KSan::delegate(&pp, &sk_z1, &pk_z1, &san_pks, &pk_d1, &[1], &sig).unwrap();
+        let sigd = KSan::delegate(&pp, &sk_d1, &pk_d1, &san_pks, &pk_d2, &[1], &sigd).unwrap();
+        assert_eq!(sigd.delegations.len(), 2, "The signature should hold both delegations");
+        let sigp = KSan::sanitize(&pp, &sk_d2, &pk_s, &pk_d2, &san_pks, &m, &modif, &sigd).unwrap();
+        let modif = vec![Mod { i: 2, m: "modadm2".to_string() }];
+        let mp = vec!["not_adm".to_string(), "modadm1".to_string(), "adm2".to_string()];
+        let sigp = KSan::sanitize(&pp, &sk_z2, &pk_s, &pk_z2, &san_pks, &mp, &modif, &sigp).unwrap();
+        let mp = vec!["not_adm".to_string(), "modadm1".to_string(), "modadm2".to_string()];
+        let b = KSan::verify(&pp, &pk_s, &san_pks, &mp, &sigp).unwrap();
+        assert!(b, "Verification of a signature sanitized by a delegate failed");
+        let (b, _) = KSan::verify_batch(&pp, &pk_s, std::slice::from_ref(&san_pks), std::slice::from_ref(&mp), 
+            std::slice::from_ref(&sigp)).unwrap();
+        assert!(b, "Batch verification of a signature sanitized by a delegate failed");
+        let j = KSan::judge(&pp, &pk_s, &san_pks, &mp, &sigp, None, Some(&1)).unwrap();
+        assert_eq!(j, 'Z', "Judge should blame the sanitizers for a block modified by a delegate");
+
+        //Test that the delegation chain is bound into the signature
+        let mut sig_false = sigp.clone();
+        sig_false.delegations.remove(0);
+        assert!(!KSan::verify(&pp, &pk_s, &san_pks, &mp, &sig_false).unwrap(), 
+            "Verification should fail if an adversary removes a delegation");
+        let mut sig_false = sigp.clone();
+        sig_false.delegations[1].pk_d = pk_d1.clone();
+        assert!(!KSan::verify(&pp, &pk_s, &san_pks, &mp, &sig_false).unwrap(), 
+            "Verification should fail if an adversary changes a delegate");
+        let mut sig_false = sigp.clone();
+        sig_false.delegations[1].cert = sig_false.delegations[0].cert.clone();
+        assert!(!KSan::verify(&pp, &pk_s, &san_pks, &mp, &sig_false).unwrap(), 
+            "Verification should fail if an adversary forges a certificate");
+    }
+
+    #[test]
+    fn test_fsv_value_sets() {
+        let (pp, sk_s, pk_s, [(sk_z, pk_z)]) = fixture();
+        let san_pks = vec![pk_z.clone()];
+        let adm = vec![vec![false, true]];
+        let set: Vec<String> = ["draft", "submitted", "approved"].iter().map(|v| v.to_string()).collect();
+        let values = vec![None, Some(set.clone())];
+        let m = vec!["form-42".to_string(), "draft".to_string()];
+
+        //Test that the value of a restricted block must be in its set
+        let m_false = vec!["form-42".to_string(), "deleted".to_string()];
+        assert!(KSan::sign_with_values(&pp, &sk_s, &pk_s, &san_pks, &m_false, &adm, &values).is_err(), 
+            "Signing a value out of the set should fail");
+        let sig = KSan::sign_with_values(&pp, &sk_s, &pk_s, &san_pks, &m, &adm, &values).unwrap();
+        assert!(sig.value_sets[0].is_none() && sig.value_sets[1].is_some(), "Only the restricted block should have a value set");
+
+        //Test that a sanitizer can write a value of the set and no other one
+        let modif = vec![Mod { i: 1, m: "approved".to_string() }];
+        let san_opts = SanitizeOptions { values: values.clone(), ..SanitizeOptions::new(&m, &modif) };
+        let sigp = KSan::sanitize_with_options(&pp, &sk_z, &pk_s, &pk_z, &san_pks, &san_opts, &sig).unwrap();
+        let mp = vec!["form-42".to_string(), "approved".to_string()];
+        let b = KSan::verify(&pp, &pk_s, &san_pks, &mp, &sigp).unwrap();
+        assert!(b, "Verification of a signature sanitized with an allowed value failed");
+        let (b, _) = KSan::verify_batch(&pp, &pk_s, std::slice::from_ref(&san_pks), std::slice::from_ref(&mp), 
+            std::slice::from_ref(&sigp)).unwrap();
+        assert!(b, "Batch verification of a signature sanitized with an allowed value failed");
+        let modif_false = vec![Mod { i: 1, m: "deleted".to_string() }];
+        let san_opts = SanitizeOptions { values: values.clone(), ..SanitizeOptions::new(&m, &modif_false) };
+        let r = KSan::sanitize_with_options(&pp, &sk_z, &pk_s, &pk_z, &san_pks, &san_opts, &sig);
+        assert!(r.is_err(), "Sanitizing with a value out of the set should fail");
+        let r = KSan::sanitize(&pp, &sk_z, &pk_s, &pk_z, &san_pks, &m, &modif, &sig);
+        assert!(r.is_err(), "Sanitizing a restricted block without its value set should fail");
+        let mut set_false = set.clone();
+        set_false.push("deleted".to_string());
+        let values_false = vec![None, Some(set_false.clone())];
+        let san_opts = SanitizeOptions { values: values_false, ..SanitizeOptions::new(&m, &modif_false) };
+        let r = KSan::sanitize_with_options(&pp, &sk_z, &pk_s, &pk_z, &san_pks, &san_opts, &sig);
+        assert!(r.is_err(), "Sanitizing with another value set should fail");
+
+        //Test that the roots are bound into the signature
+        let mut sig_false = sigp.clone();
+        sig_false.value_sets[1] = None;
+        assert!(!KSan::verify(&pp, &pk_s, &san_pks, &mp, &sig_false).unwrap(), 
+            "Verification should fail if an adversary removes a value set");
+        let mut sig_false = sigp.clone();
+        sig_false.value_sets[1].as_mut().unwrap().root = Merkle::root(&set_false);
+        sig_false.value_sets[1].as_mut().unwrap().membership = Merkle::prove(&set_false, &"approved".to_string()).unwrap();
+        assert!(!KSan::verify(&pp, &pk_s, &san_pks, &mp, &sig_false).unwrap(), 
+            "Verification should fail if an adversary replaces a value set");
+    }
+
+    #[test]
+    fn test_fsv_redact() {
+        let (pp, sk_s, pk_s, [(sk_z, pk_z)]) = fixture();
+        let san_pks = vec![pk_z.clone()];
+        let adm = vec![vec![false, true, true]];
+        let policy = vec![
+            Policy { modify: true, redact: false },
+            Policy { modify: false, redact: true },
+            Policy { modify: true, redact: true }
+        ];
+        let m = vec!["name".to_string(), "ssn".to_string(), "address".to_string()];
+        let sig = KSan::sign_with_policy(&pp, &sk_s, &pk_s, &san_pks, &m, &adm, &policy).unwrap();
+        assert!(!sig.pub_adm[1], "A block that is only redactable should not be publicly admissible");
+
+        //Test that the policy is enforced
+        let san_opts = SanitizeOptions { redact: vec![0], ..SanitizeOptions::new(&m, &[]) };
+        let r = KSan::sanitize_with_options(&pp, &sk_z, &pk_s, &pk_z, &san_pks, &san_opts, &sig);
+        assert!(r.is_err(), "Redacting a block that is not redactable should fail");
+        let modif = vec![Mod { i: 1, m: "000-00-0000".to_string() }];
+        let r = KSan::sanitize(&pp, &sk_z, &pk_s, &pk_z, &san_pks, &m, &modif, &sig);
+        assert!(r.is_err(), "Modifying a block that is only redactable should fail");
+        let modif = vec![Mod { i: 2, m: "new address".to_string() }];
+        let sigp2 = KSan::sanitize(&pp, &sk_z, &pk_s, &pk_z, &san_pks, &m, &modif, &sig).unwrap();
+        let m2 = vec!["name".to_string(), "ssn".to_string(), "new address".to_string()];
+        assert!(KSan::verify(&pp, &pk_s, &san_pks, &m2, &sigp2).unwrap(), "Verification of a signature with a policy failed");
+        let m_false = vec!["name".to_string(), "000-00-0000".to_string(), "new address".to_string()];
+        assert!(!KSan::verify(&pp, &pk_s, &san_pks, &m_false, &sigp2).unwrap(), 
+            "Verification should fail if a block that is only redactable is modified");
+
+        //Test that a redactable block can be redacted and the remaining blocks verified
+        let san_opts = SanitizeOptions { redact: vec![1], ..SanitizeOptions::new(&m2, &[]) };
+        let sigp = KSan::sanitize_with_options(&pp, &sk_z, &pk_s, &pk_z, &san_pks, &san_opts, &sigp2).unwrap();
+        let mp = vec!["name".to_string(), "new address".to_string()];
+        assert_eq!(sigp.redacted, vec![1], "The redacted block should be recorded");
+        assert!(KSan::verify(&pp, &pk_s, &san_pks, &mp, &sigp).unwrap(), "Verification of a redacted signature failed");
+        let (b, _) = KSan::verify_batch(&pp, &pk_s, std::slice::from_ref(&san_pks), std::slice::from_ref(&mp), 
+            std::slice::from_ref(&sigp)).unwrap();
+        assert!(b, "Batch verification of a redacted signature failed");
+        assert!(!KSan::verify(&pp, &pk_s, &san_pks, &m2, &sigp).unwrap(), 
+            "Verification with the redacted block should fail");
+        let j = KSan::judge(&pp, &pk_s, &san_pks, &mp, &sigp, None, Some(&1)).unwrap();
+        assert_eq!(j, 'Z', "Judge should blame the sanitizers for a redacted block");
+
+        //Test that the redacted blocks are bound into the signature
+        let mut sig_false = sigp.clone();
+        sig_false.redacted = vec![2];
+        let mp_false = vec!["name".to_string(), "ssn".to_string()];
+        assert!(!KSan::verify(&pp, &pk_s, &san_pks, &mp_false, &sig_false).unwrap(), 
+            "Verification should fail if an adversary changes the redacted blocks");
+        let mut sig_false = sigp2.clone();
+        sig_false.redacted = vec![1];
+        assert!(!KSan::verify(&pp, &pk_s, &san_pks, &mp, &sig_false).unwrap(), 
+            "Verification should fail if an adversary redacts a block without the trapdoor");
+    }
+
+    #[test]
+    fn test_fsv_append() {
+        let (pp, sk_s, pk_s, [(sk_z1, pk_z1), (sk_z2, pk_z2)]) = fixture();
+        let san_pks = vec![pk_z1.clone(), pk_z2.clone()];
+        let adm = vec![vec![false], vec![false]];
+        let m = vec!["report".to_string()];
+        let sig = KSan::sign_with_slots(&pp, &sk_s, &pk_s, &san_pks, &m, &adm, &[2, 1]).unwrap();
+        assert!(KSan::verify(&pp, &pk_s, &san_pks, &m, &sig).unwrap(), "Verification of a signature with unfilled slots failed");
+        assert_eq!(KSan::unfilled_slots(&san_pks, &pk_z1, &sig), vec![1, 2], "The first sanitizer should own two slots");
+        assert_eq!(KSan::unfilled_slots(&san_pks, &pk_z2, &sig), vec![3], "The second sanitizer should own one slot");
+
+        //Test that a sanitizer can fill its own slots
+        let san_opts = SanitizeOptions { append: vec!["comment".to_string()], ..SanitizeOptions::new(&m, &[]) };
+        let sigp = KSan::sanitize_with_options(&pp, &sk_z1, &pk_s, &pk_z1, &san_pks, &san_opts, &sig).unwrap();
+        let mp = vec!["report".to_string(), "comment".to_string()];
+        assert_eq!(sigp.filled, vec![1], "The filled slot should be recorded");
+        assert!(KSan::verify(&pp, &pk_s, &san_pks, &mp, &sigp).unwrap(), "Verification of a filled slot failed");
+        let (b, _) = KSan::verify_batch(&pp, &pk_s, std::slice::from_ref(&san_pks), std::slice::from_ref(&mp), 
+            std::slice::from_ref(&sigp)).unwrap();
+        assert!(b, "Batch verification of a filled slot failed");
+        assert!(!KSan::verify(&pp, &pk_s, &san_pks, &m, &sigp).unwrap(), "Verification without the filled slot should fail");
+        let san_opts = SanitizeOptions { append: vec!["approved".to_string()], ..SanitizeOptions::new(&mp, &[]) };
+        let sigpp = KSan::sanitize_with_options(&pp, &sk_z2, &pk_s, &pk_z2, &san_pks, &san_opts, &sigp).unwrap();
+        let mpp = vec!["report".to_string(), "comment".to_string(), "approved".to_string()];
+        assert!(KSan::verify(&pp, &pk_s, &san_pks, &mpp, &sigpp).unwrap(), "Verification of slots filled by two sanitizers failed");
+        let d = KSan::judge(&pp, &pk_s, &san_pks, &mpp, &sigpp, None, Some(&3)).unwrap();
+        assert_eq!(d, 'Z', "Judge should blame the sanitizers for a filled slot");
+        let d = KSan::judge(&pp, &pk_s, &san_pks, &mpp, &sigpp, None, Some(&2)).unwrap();
+        assert_eq!(d, 'S', "Judge should blame the signer for an unfilled slot");
+
+        //Test that the slots of another sanitizer and extra slots cannot be filled
+        let modif = vec![Mod { i: 2, m: "forged".to_string() }];
+        let r = KSan::sanitize(&pp, &sk_z2, &pk_s, &pk_z2, &san_pks, &mp, &modif, &sigp);
+        assert!(r.is_err(), "Filling the slot of another sanitizer should fail");
+        let san_opts = SanitizeOptions { append: vec!["a".to_string(), "b".to_string()], ..SanitizeOptions::new(&m, &[]) };
+        let r = KSan::sanitize_with_options(&pp, &sk_z2, &pk_s, &pk_z2, &san_pks, &san_opts, &sig);
+        assert!(r.is_err(), "Appending more blocks than unfilled slots should fail");
+
+        //Test that the filled slots and the owners are bound into the signature
+        let mut sig_false = sigp.clone();
+        sig_false.filled = vec![1, 2];
+        let m_false = vec!["report".to_string(), "comment".to_string(), "forged".to_string()];
+        assert!(!KSan::verify(&pp, &pk_s, &san_pks, &m_false, &sig_false).unwrap(), 
+            "Verification should fail if an adversary marks a slot as filled");
+        let mut sig_false = sigp.clone();
+        sig_false.filled = Vec::new();
+        assert!(!KSan::verify(&pp, &pk_s, &san_pks, &m, &sig_false).unwrap(), 
+            "Verification should fail if an adversary marks a filled slot as unfilled");
+        let mut sig_false = sig.clone();
+        sig_false.slots = vec![1, 1, 0];
+        assert!(!KSan::verify(&pp, &pk_s, &san_pks, &m, &sig_false).unwrap(), 
+            "Verification should fail if an adversary changes the owners of the slots");
+    }
+    #[test]
+    fn test_fsv_limits() {
+        let (pp, sk_s, pk_s, [(sk_z1, pk_z1), (sk_z2, pk_z2)]) = fixture();
+        let san_pks = vec![pk_z1.clone(), pk_z2.clone()];
+        let adm = vec![vec![true, false], vec![true, false]];
+        let m = vec!["draft".to_string(), "footer".to_string()];
+        let sig = KSan::sign_with_limits(&pp, &sk_s, &pk_s, &san_pks, &m, &adm, &[Some(2), None]).unwrap();
+        let r = KSan::sign_with_limits(&pp, &sk_s, &pk_s, &san_pks, &m, &adm, &[Some(0), None]);
+        assert!(r.is_err(), "A limit of zero should be rejected");
+
+        //Test that a sanitizer can sanitize a limited block up to its limit
+        let modif = vec![Mod { i: 0, m: "v1".to_string() }];
+        let sig1 = KSan::sanitize(&pp, &sk_z1, &pk_s, &pk_z1, &san_pks, &m, &modif, &sig).unwrap();
+        let m1 = vec!["v1".to_string(), "footer".to_string()];
+        assert!(KSan::verify(&pp, &pk_s, &san_pks, &m1, &sig1).unwrap(), "Verification of a limited sanitization failed");
+        let modif = vec![Mod { i: 0, m: "v2".to_string() }];
+        let sig2 = KSan::sanitize(&pp, &sk_z1, &pk_s, &pk_z1, &san_pks, &m1, &modif, &sig1).unwrap();
+        let m2 = vec!["v2".to_string(), "footer".to_string()];
+        assert!(KSan::verify(&pp, &pk_s, &san_pks, &m2, &sig2).unwrap(), "Verification of a second limited sanitization failed");
+        let (b, _) = KSan::verify_batch(&pp, &pk_s, std::slice::from_ref(&san_pks), std::slice::from_ref(&m2), std::slice::from_ref(&sig2)).unwrap();
+        assert!(b, "Batch verification of a limited sanitization failed");
+        assert_eq!(sig2.tags.iter().map(|x| x.c).collect::<Vec<usize>>(), vec![1, 2], "The counts should be logged in order");
+        let modif = vec![Mod { i: 0, m: "v3".to_string() }];
+        let r = KSan::sanitize(&pp, &sk_z1, &pk_s, &pk_z1, &san_pks, &m2, &modif, &sig2);
+        assert!(r.is_err(), "Sanitizing over the limit should fail");
+        let r = KSan::sanitize_with_options(&pp, &sk_z1, &pk_s, &pk_z1, &san_pks, &SanitizeOptions::new(&m2, &modif), &sig2);
+        assert!(r.is_err(), "Sanitizing over the limit with options should fail");
+        let mut sig_false = sig2.clone();
+        sig_false.tags[1].c = 3;
+        assert!(!KSan::verify(&pp, &pk_s, &san_pks, &m2, &sig_false).unwrap(), 
+            "Verification should fail for a count over the limit");
+
+        //Test that the limit is per sanitizer
+        let sig3 = KSan::sanitize(&pp, &sk_z2, &pk_s, &pk_z2, &san_pks, &m2, &modif, &sig2).unwrap();
+        let m3 = vec!["v3".to_string(), "footer".to_string()];
+        assert!(KSan::verify(&pp, &pk_s, &san_pks, &m3, &sig3).unwrap(), "Verification of another sanitizer's sanitization failed");
+
+        //Test that a count used twice on different versions exposes the sanitizer
+        let modif = vec![Mod { i: 0, m: "other".to_string() }];
+        let sig_b = KSan::sanitize(&pp, &sk_z1, &pk_s, &pk_z1, &san_pks, &m, &modif, &sig).unwrap();
+        let pk = KSan::judge_overuse(&pp, &san_pks, &sig1, &sig_b).unwrap();
+        assert!(pk.is_some() && pk.unwrap().pkp == pk_z1.pkp, "Judge should identify the sanitizer that reused a count");
+        let pk = KSan::judge_overuse(&pp, &san_pks, &sig1, &sig2).unwrap();
+        assert!(pk.is_none(), "Judge should not identify a sanitizer within its limit");
+        let mut sig_false = sig_b.clone();
+        sig_false.times = Vec::new();
+        assert!(KSan::judge_overuse(&pp, &san_pks, &sig1, &sig_false).is_err(), 
+            "Judge should reject a malformed second signature");
+        let mut sig_false = sig_b.clone();
+        sig_false.limits = vec![Some(3), None];
+        assert!(KSan::judge_overuse(&pp, &san_pks, &sig1, &sig_false).is_err(), 
+            "Judge should reject signatures with different limits");
+
+        //Test that the log and the limits are bound into the signature
+        let mut sig_false = sig2.clone();
+        sig_false.tags[1].c = 1;
+        assert!(!KSan::verify(&pp, &pk_s, &san_pks, &m2, &sig_false).unwrap(), 
+            "Verification should fail if an adversary changes a logged count");
+        let mut sig_false = sig2.clone();
+        sig_false.tags = Vec::new();
+        assert!(!KSan::verify(&pp, &pk_s, &san_pks, &m2, &sig_false).unwrap(), 
+            "Verification should fail if an adversary removes the log");
+        let mut sig_false = sig2.clone();
+        sig_false.limits = vec![Some(3), None];
+        assert!(!KSan::verify(&pp, &pk_s, &san_pks, &m2, &sig_false).unwrap(), 
+            "Verification should fail if an adversary changes the limits");
+    }
+    #[test]
+    fn test_fsv_windows() {
+        let (pp, sk_s, pk_s, [(sk_z1, pk_z1), (sk_z2, pk_z2)]) = fixture();
+        let san_pks = vec![pk_z1.clone(), pk_z2.clone()];
+        let adm = vec![vec![true, false], vec![true, false]];
+        let m = vec!["embargoed".to_string(), "footer".to_string()];
+        let windows = vec![Some(Window { from: 100, until: 200 }), None];
+        let sig = KSan::sign_with_windows(&pp, &sk_s, &pk_s, &san_pks, &m, &adm, &windows).unwrap();
+        let r = KSan::sign_with_windows(&pp, &sk_s, &pk_s, &san_pks, &m, &adm, &[None]);
+        assert!(r.is_err(), "Signing with a window count different from the sanitizer count should fail");
+
+        //Test that a sanitizer can sanitize within its window
+        let modif = vec![Mod { i: 0, m: "published".to_string() }];
+        let r = KSan::sanitize(&pp, &sk_z1, &pk_s, &pk_z1, &san_pks, &m, &modif, &sig);
+        assert!(r.is_err(), "Sanitizing a signature with windows without a time should fail");
+        let san_opts = SanitizeOptions { now: Some(150), ..SanitizeOptions::new(&m, &modif) };
+        let sig1 = KSan::sanitize_with_options(&pp, &sk_z1, &pk_s, &pk_z1, &san_pks, &san_opts, &sig).unwrap();
+        let m1 = vec!["published".to_string(), "footer".to_string()];
+        assert!(KSan::verify(&pp, &pk_s, &san_pks, &m1, &sig1).unwrap(), "Verification of a sanitization within the window failed");
+        let (b, _) = KSan::verify_batch(&pp, &pk_s, std::slice::from_ref(&san_pks), std::slice::from_ref(&m1), std::slice::from_ref(&sig1)).unwrap();
+        assert!(b, "Batch verification of a sanitization within the window failed");
+        assert!(KSan::verify_at(&pp, &pk_s, &san_pks, &m1, &sig1, 160).unwrap(), "Verification at a later time failed");
+        assert!(!KSan::verify_at(&pp, &pk_s, &san_pks, &m1, &sig1, 120).unwrap(), 
+            "Verification should fail for a sanitization dated after the clock");
+
+        //Test that the rights expire with the window
+        let san_opts = SanitizeOptions { now: Some(250), ..SanitizeOptions::new(&m, &modif) };
+        let r = KSan::sanitize_with_options(&pp, &sk_z1, &pk_s, &pk_z1, &san_pks, &san_opts, &sig);
+        assert!(r.is_err(), "Sanitizing after the window should fail");
+        let san_opts = SanitizeOptions { now: Some(250), ..SanitizeOptions::new(&m1, &modif) };
+        let r = KSan::sanitize_with_options(&pp, &sk_z1, &pk_s, &pk_z1, &san_pks, &san_opts, &sig1);
+        assert!(r.is_err(), "Sanitizing a sanitized signature after the window should fail");
+        let san_opts = SanitizeOptions { now: Some(50), ..SanitizeOptions::new(&m, &modif) };
+        let r = KSan::sanitize_with_options(&pp, &sk_z1, &pk_s, &pk_z1, &san_pks, &san_opts, &sig);
+        assert!(r.is_err(), "Sanitizing before the window should fail");
+        let san_opts = SanitizeOptions { now: Some(250), ..SanitizeOptions::new(&m, &modif) };
+        let sig2 = KSan::sanitize_with_options(&pp, &sk_z2, &pk_s, &pk_z2, &san_pks, &san_opts, &sig).unwrap();
+        assert!(KSan::verify(&pp, &pk_s, &san_pks, &m1, &sig2).unwrap(), "Verification of a sanitizer without window failed");
+
+        //Test that the times and the windows are bound into the signature
+        let mut sig_false = sig1.clone();
+        sig_false.times[0] = Some(250);
+        assert!(!KSan::verify(&pp, &pk_s, &san_pks, &m1, &sig_false).unwrap(), 
+            "Verification should fail if an adversary changes the time of a sanitization");
+        let mut sig_false = sig1.clone();
+        sig_false.windows[0] = Some(Window { from: 0, until: 1000 });
+        assert!(!KSan::verify(&pp, &pk_s, &san_pks, &m1, &sig_false).unwrap(), 
+            "Verification should fail if an adversary changes a window");
+    }
+
+    #[test]
+    fn test_fsv_thresholds() {
+        let (pp, sk_s, pk_s, [(sk_z1, pk_z1), (sk_z2, pk_z2), (sk_z3, pk_z3), (sk_z4, pk_z4)]) = fixture();
+        let san_pks = vec![pk_z1.clone(), pk_z2.clone(), pk_z3.clone(), pk_z4.clone()];
+        let adm = vec![vec![true, false]; 4];
+        let m = vec!["amount: 1000".to_string(), "notes".to_string()];
+        let groups = vec![Some(Group { t: 2, members: vec![0, 1, 2] }), None];
+        let sig = KSan::sign_with_thresholds(&pp, &sk_s, &pk_s, &san_pks, &m, &adm, &groups).unwrap();
+        let r = KSan::sign_with_thresholds(&pp, &sk_s, &pk_s, &san_pks, &m, &adm, 
+            &[Some(Group { t: 4, members: vec![0, 1, 2] }), None]);
+        assert!(r.is_err(), "Signing with a threshold larger than the group should fail");
+        let th = sig.thresholds[0].as_ref().unwrap();
+        let ys = vec![PKE::decrypt(&sk_z1.ske, &th.shares[0]), PKE::decrypt(&sk_z3.ske, &th.shares[2])];
+        let skch = Shamir::combine(&pp.q, &[1, 3], &ys);
+        assert_eq!(pp.g.modpow(&skch, &pp.p), sig.hashes[0].pkch, "t shares should give the trapdoor of the block");
+
+        //Test that no sanitizer can modify a threshold block alone
+        let modif = vec![Mod { i: 0, m: "amount: 2000".to_string() }];
+        let r = KSan::sanitize(&pp, &sk_z1, &pk_s, &pk_z1, &san_pks, &m, &modif, &sig);
+        assert!(r.is_err(), "A member should not modify a threshold block alone");
+        let san_opts = SanitizeOptions::new(&m, &modif);
+        let r = KSan::sanitize_commit(&pp, &pk_z1, &san_pks, &san_opts, &[0], &sig);
+        assert!(r.is_err(), "A threshold sanitization with fewer signers than the threshold should fail");
+        let r = KSan::sanitize_commit(&pp, &pk_z4, &san_pks, &san_opts, &[0, 3], &sig);
+        assert!(r.is_err(), "A threshold sanitization by a sanitizer out of the group should fail");
+
+        //Test that t members can modify the block, the proofs being made by any sanitizer
+        let (n1, c1) = KSan::sanitize_commit(&pp, &pk_z1, &san_pks, &san_opts, &[0, 2], &sig).unwrap();
+        let (n3, c3) = KSan::sanitize_commit(&pp, &pk_z3, &san_pks, &san_opts, &[0, 2], &sig).unwrap();
+        let san_opts = SanitizeOptions { commitments: vec![c1, c3], ..san_opts };
+        let p1 = KSan::sanitize_partial(&pp, &sk_z1, &pk_z1, &san_pks, &san_opts, n1, &sig).unwrap();
+        let p3 = KSan::sanitize_partial(&pp, &sk_z3, &pk_z3, &san_pks, &san_opts, n3, &sig).unwrap();
+        let opts_p = SanitizeOptions { partials: vec![p1.clone()], ..san_opts.clone() };
+        let r = KSan::sanitize_with_options(&pp, &sk_z4, &pk_s, &pk_z4, &san_pks, &opts_p, &sig);
+        assert!(r.is_err(), "A threshold sanitization with t - 1 partial adaptations should fail");
+        let partials = vec![p1.clone(), p3.clone()];
+        let opts_p = SanitizeOptions { partials: partials.clone(), ..san_opts.clone() };
+        let sig1 = KSan::sanitize_with_options(&pp, &sk_z4, &pk_s, &pk_z4, &san_pks, &opts_p, &sig).unwrap();
+        let m1 = vec!["amount: 2000".to_string(), "notes".to_string()];
+        assert!(KSan::verify(&pp, &pk_s, &san_pks, &m1, &sig1).unwrap(), "Verification of a threshold sanitization failed");
+        let (b, _) = KSan::verify_batch(&pp, &pk_s, std::slice::from_ref(&san_pks), std::slice::from_ref(&m1), std::slice::from_ref(&sig1)).unwrap();
+        assert!(b, "Batch verification of a threshold sanitization failed");
+
+        //Test that the two versions do not expose the trapdoor of the block, as they would with the discrete
+        //logarithm chameleon hash, so that fewer than t members still cannot modify it
+        let q = &pp.q;
+        let d = (hash(&block_input(0, &m[0], false, false)) - hash(&block_input(0, &m1[0], false, false))).mod_floor(q);
+        let dr = (&sig1.hashes[0].r - &sig.hashes[0].r).mod_floor(q);
+        let exposed = dr.modinv(q).map(|x| d * x % q).unwrap_or_default();
+        assert!(pp.g.modpow(&exposed, &pp.p) != sig.hashes[0].pkch, "A threshold sanitization should not expose the trapdoor");
+        let modif2 = vec![Mod { i: 0, m: "amount: 3000".to_string() }];
+        let san_opts2 = SanitizeOptions::new(&m1, &modif2);
+        let (n1, c1) = KSan::sanitize_commit(&pp, &pk_z1, &san_pks, &san_opts2, &[0, 1], &sig1).unwrap();
+        let (n2, c2) = KSan::sanitize_commit(&pp, &pk_z2, &san_pks, &san_opts2, &[0, 1], &sig1).unwrap();
+        let san_opts2 = SanitizeOptions { commitments: vec![c1, c2], ..san_opts2 };
+        let (n1_false, _) = KSan::sanitize_commit(&pp, &pk_z1, &san_pks, &san_opts2, &[0, 1], &sig1).unwrap();
+        let r = KSan::sanitize_partial(&pp, &sk_z1, &pk_z1, &san_pks, &san_opts2, n1_false, &sig1);
+        assert!(r.is_err(), "A partial adaptation with nonces that are not committed should fail");
+        let p1 = KSan::sanitize_partial(&pp, &sk_z1, &pk_z1, &san_pks, &san_opts2, n1, &sig1).unwrap();
+        let mut p2_false = p1.clone();
+        p2_false.member = 1;
+        let opts_p = SanitizeOptions { partials: vec![p1.clone(), p2_false], ..san_opts2.clone() };
+        let r = KSan::sanitize_with_options(&pp, &sk_z1, &pk_s, &pk_z1, &san_pks, &opts_p, &sig1);
+        assert!(r.is_err(), "A second threshold sanitization by fewer than t members should fail");
+        let p2 = KSan::sanitize_partial(&pp, &sk_z2, &pk_z2, &san_pks, &san_opts2, n2, &sig1).unwrap();
+        let opts_p = SanitizeOptions { partials: vec![p1, p2], ..san_opts2 };
+        let sig2 = KSan::sanitize_with_options(&pp, &sk_z1, &pk_s, &pk_z1, &san_pks, &opts_p, &sig1).unwrap();
+        let m2 = vec!["amount: 3000".to_string(), "notes".to_string()];
+        assert!(KSan::verify(&pp, &pk_s, &san_pks, &m2, &sig2).unwrap(), "Verification of a second threshold sanitization failed");
+
+        //Test that the other blocks are sanitized as usual in the same sanitization
+        let adm_b = vec![vec![true, true], vec![false, false], vec![false, false], vec![false, false]];
+        let sig_b = KSan::sign_with_thresholds(&pp, &sk_s, &pk_s, &san_pks, &m, &adm_b, &groups).unwrap();
+        let modif_b = vec![Mod { i: 0, m: "amount: 1500".to_string() }, Mod { i: 1, m: "paid".to_string() }];
+        let san_opts_b = SanitizeOptions::new(&m, &modif_b);
+        let (n1, c1) = KSan::sanitize_commit(&pp, &pk_z1, &san_pks, &san_opts_b, &[0, 1], &sig_b).unwrap();
+        let (n2, c2) = KSan::sanitize_commit(&pp, &pk_z2, &san_pks, &san_opts_b, &[0, 1], &sig_b).unwrap();
+        let san_opts_b = SanitizeOptions { commitments: vec![c1, c2], ..san_opts_b };
+        let p1 = KSan::sanitize_partial(&pp, &sk_z1, &pk_z1, &san_pks, &san_opts_b, n1, &sig_b).unwrap();
+        let p2 = KSan::sanitize_partial(&pp, &sk_z2, &pk_z2, &san_pks, &san_opts_b, n2, &sig_b).unwrap();
+        let opts_p = SanitizeOptions { partials: vec![p2.clone(), p1], ..san_opts_b };
+        let sig_b1 = KSan::sanitize_with_options(&pp, &sk_z1, &pk_s, &pk_z1, &san_pks, &opts_p, &sig_b).unwrap();
+        let m_b1 = vec!["amount: 1500".to_string(), "paid".to_string()];
+        assert!(KSan::verify(&pp, &pk_s, &san_pks, &m_b1, &sig_b1).unwrap(), "Verification of a mixed sanitization failed");
+        let r = KSan::sanitize_with_options(&pp, &sk_z2, &pk_s, &pk_z2, &san_pks, &opts_p, &sig_b);
+        assert!(r.is_err(), "A sanitizer without rights on the other blocks should not modify them");
+
+        //Test that partial adaptations for another modification or other signers are rejected
+        let modif3 = vec![Mod { i: 0, m: "amount: 9000".to_string() }];
+        let opts_p = SanitizeOptions { m: m.clone(), modif: modif3, partials: partials.clone(), ..san_opts.clone() };
+        let r = KSan::sanitize_with_options(&pp, &sk_z4, &pk_s, &pk_z4, &san_pks, &opts_p, &sig);
+        assert!(r.is_err(), "Partial adaptations for another modification should fail");
+        let opts_p = SanitizeOptions { partials: vec![partials[0].clone(), p2], ..san_opts.clone() };
+        let r = KSan::sanitize_with_options(&pp, &sk_z4, &pk_s, &pk_z4, &san_pks, &opts_p, &sig);
+        assert!(r.is_err(), "Partial adaptations for different signers should fail");
+        let mut p_false = partials.clone();
+        p_false[1].rho[0] = &p_false[1].rho[0] + 1;
+        let opts_p = SanitizeOptions { partials: p_false, ..san_opts.clone() };
+        let r = KSan::sanitize_with_options(&pp, &sk_z4, &pk_s, &pk_z4, &san_pks, &opts_p, &sig);
+        assert!(r.is_err(), "A wrong partial adaptation should fail");
+
+        //Test that the groups and the shares are bound into the signature and that s opens the hash
+        let mut sig_false = sig1.clone();
+        sig_false.thresholds[0].as_mut().unwrap().group.t = 1;
+        assert!(!KSan::verify(&pp, &pk_s, &san_pks, &m1, &sig_false).unwrap(), 
+            "Verification should fail if an adversary changes the threshold of a block");
+        let mut sig_false = sig1.clone();
+        sig_false.thresholds[0].as_mut().unwrap().shares.swap(0, 1);
+        assert!(!KSan::verify(&pp, &pk_s, &san_pks, &m1, &sig_false).unwrap(), 
+            "Verification should fail if an adversary changes the shares of a block");
+        let mut sig_false = sig1.clone();
+        sig_false.thresholds[0].as_mut().unwrap().s = &sig.thresholds[0].as_ref().unwrap().s + 1;
+        assert!(!KSan::verify(&pp, &pk_s, &san_pks, &m1, &sig_false).unwrap(), 
+            "Verification should fail if an adversary changes the randomness of a threshold block");
+    }
+
+    #[test]
+    fn test_fsv_sign_options() {
+        let (pp, sk_s, pk_s, [(sk_z1, pk_z1), (_sk_z2, pk_z2), (_sk_z3, pk_z3)]) = fixture();
+        let san_pks = vec![pk_z1.clone(), pk_z2.clone()];
+        let adm = vec![vec![false, true, true]; 2];
+        let set: Vec<String> = ["draft", "approved"].iter().map(|v| v.to_string()).collect();
+        let m = vec!["form-42".to_string(), "draft".to_string(), "notes".to_string()];
+
+        //Test that the options can be combined
+        let mut opts = SignOptions::new(m.len());
+        opts.values[1] = Some(set.clone());
+        opts.limits[1] = Some(1);
+        opts.policy[2] = Policy { modify: false, redact: true };
+        let (sig, _td) = KSan::sign_with_options(&pp, &sk_s, &pk_s, &san_pks, &m, &adm, &opts).unwrap();
+        assert_eq!(sig.limits, vec![None, Some(1), None], "The signature should record the limits");
+        let modif = vec![Mod { i: 1, m: "approved".to_string() }];
+        let san_opts = SanitizeOptions { values: opts.values.clone(), ..SanitizeOptions::new(&m, &modif) };
+        let sigp = KSan::sanitize_with_options(&pp, &sk_z1, &pk_s, &pk_z1, &san_pks, &san_opts, &sig).unwrap();
+        let mp = vec!["form-42".to_string(), "approved".to_string(), "notes".to_string()];
+        assert!(KSan::verify(&pp, &pk_s, &san_pks, &mp, &sigp).unwrap(), "Verification of a sanitization with options failed");
+        let modif = vec![Mod { i: 2, m: "other".to_string() }];
+        let r = KSan::sanitize(&pp, &sk_z1, &pk_s, &pk_z1, &san_pks, &m, &modif, &sig);
+        assert!(r.is_err(), "Modifying a block that can only be redacted should fail");
+        let opts_false = SignOptions { limits: vec![None, Some(0), None], ..SignOptions::new(m.len()) };
+        let r = KSan::sign_with_options(&pp, &sk_s, &pk_s, &san_pks, &m, &adm, &opts_false);
+        assert!(r.is_err(), "Signing with a limit of 0 should fail");
+
+        //Test that slots and thresholds cannot be extended or revoked
+        let opts_s = SignOptions { slots: vec![1, 0], ..SignOptions::new(m.len()) };
+        let (sig_s, td_s) = KSan::sign_with_options(&pp, &sk_s, &pk_s, &san_pks, &m, &adm, &opts_s).unwrap();
+        let opts_t = SignOptions { groups: vec![None, Some(Group { t: 2, members: vec![0, 1] }), None], ..SignOptions::new(m.len()) };
+        let (sig_t, td_t) = KSan::sign_with_options(&pp, &sk_s, &pk_s, &san_pks, &m, &adm, &opts_t).unwrap();
+        for (sig_o, td_o) in [(&sig_s, &td_s), (&sig_t, &td_t)] {
+            let ext = Extension { pk_z: pk_z3.clone(), adm_z: vec![false, true, false], td: td_o.clone() };
+            let r = KSan::extend_sanitizers(&pp, &sk_s, &pk_s, &san_pks, &m, sig_o, &ext);
+            assert!(r.is_err(), "Extending a signature with slots or thresholds should fail");
+            let rev = Revocation { pk_z: pk_z2.clone(), td: td_o.clone() };
+            let r = KSan::revoke(&pp, &sk_s, &pk_s, &san_pks, &m, sig_o, &rev);
+            assert!(r.is_err(), "Revoking a sanitizer of a signature with slots or thresholds should fail");
+        }
+
+        //Test that a sanitizer of a signature with windows cannot delegate
+        let opts_w = SignOptions { windows: vec![Some(Window { from: 100, until: 200 }), None], ..SignOptions::new(m.len()) };
+        let (sig_w, _td_w) = KSan::sign_with_options(&pp, &sk_s, &pk_s, &san_pks, &m, &adm, &opts_w).unwrap();
+        let r = KSan::delegate(&pp, &sk_z1, &pk_z1, &san_pks, &pk_z3, &[1], &sig_w);
+        assert!(r.is_err(), "Delegating from a signature with windows should fail");
+    }
+
+    #[test]
+    fn test_fsv_admissible_blocks() {
+        let (pp, sk_s, pk_s, [(sk_z1, pk_z1), (sk_z2, pk_z2), (sk_z3, pk_z3), (sk_d, pk_d)]) = fixture();
+        let san_pks = vec![pk_z1.clone(), pk_z2.clone(), pk_z3.clone()];
+        let adm = vec![vec![true, false, true], vec![false, true, false], vec![false; 3]];
+        let m = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        let sig = KSan::sign(&pp, &sk_s, &pk_s, &san_pks, &m, &adm).unwrap();
+
+        //Test that each sanitizer gets the blocks of its column of adm
+        assert_eq!(KSan::admissible_blocks(&pp, &sk_z1, &pk_z1, &san_pks, &sig).unwrap(), vec![0, 2], "Wrong blocks for z1");
+        assert_eq!(KSan::admissible_blocks(&pp, &sk_z2, &pk_z2, &san_pks, &sig).unwrap(), vec![1], "Wrong blocks for z2");
+        assert!(KSan::admissible_blocks(&pp, &sk_z3, &pk_z3, &san_pks, &sig).unwrap().is_empty(), "z3 should have no block");
+        assert!(KSan::admissible_blocks(&pp, &sk_d, &pk_d, &san_pks, &sig).is_err(), 
+            "A key that is neither a sanitizer nor a delegate should fail");
+
+        //Test that the listed blocks are the ones that sanitize accepts
+        let modif = vec![Mod { i: 2, m: "c'".to_string() }];
+        assert!(KSan::sanitize(&pp, &sk_z1, &pk_s, &pk_z1, &san_pks, &m, &modif, &sig).is_ok(), 
+            "A listed block should be sanitizable");
+        assert!(KSan::sanitize(&pp, &sk_z2, &pk_s, &pk_z2, &san_pks, &m, &modif, &sig).is_err(), 
+            "A block that is not listed should not be sanitizable");
+
+        //Test delegates and compact signatures
+        let sigd = KSan::delegate(&pp, &sk_z1, &pk_z1, &san_pks, &pk_d, &[2], &sig).unwrap();
+        assert_eq!(KSan::admissible_blocks(&pp, &sk_d, &pk_d, &san_pks, &sigd).unwrap(), vec![2], 
+            "Wrong blocks for the delegate");
+        let sigc = KSan::sign_compact(&pp, &sk_s, &pk_s, &san_pks, &m, &adm).unwrap();
+        assert_eq!(KSan::admissible_blocks(&pp, &sk_z1, &pk_z1, &san_pks, &sigc).unwrap(), vec![0, 2], 
+            "Wrong blocks for z1 in a compact signature");
+    }
 }
\ No newline at end of file
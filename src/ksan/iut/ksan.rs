@@ -1,9 +1,10 @@
 use ark_ec::{AffineRepr, CurveGroup};
 use ark_serialize::CanonicalSerialize;
-use kzen_paillier::RawCiphertext;
-use ark_bls12_381::{Fr, G1Projective, G2Projective};
-use ark_ff::UniformRand;
-use num_bigint::{BigInt, BigUint};
+use ark_bls12_381::{Fr, G1Affine, G1Projective, G2Projective};
+use ark_ff::{PrimeField, UniformRand, Zero};
+use num_bigint::{BigInt, BigUint, Sign};
+use rand::{thread_rng, Rng};
+use sha2::{Sha256, Digest};
 use curv::arithmetic::traits::Converter;
 
 use crate::ksan::iut::params::*;
@@ -11,13 +12,38 @@ use crate::blocks::pke::*;
 use crate::blocks::eqs::*;
 use crate::blocks::vrs::*;
 use crate::blocks::bls::*;
+use crate::blocks::sig::*;
+use crate::blocks::fixed_base::FixedBase;
+use crate::blocks::merkle::Merkle;
+use crate::ksan::common::{value_sets, update_value_sets, default_policy, block_input, check_windows, in_window,
+    check_groups, members_valid, check_adm};
+use crate::blocks::shamir::Shamir;
 use crate::ksan::hash::encode;
+use crate::par::{map_range, all_range};
+use crate::ksan::size::*;
+
+//Options of a signature bound into its last block by the signer (KSan::last_block).
+struct Bound<'a> {
+    value_sets: &'a [Option<ValueSet>],
+    policy: &'a [Policy],
+    commitments: &'a [Option<Commitment>],
+    slots: &'a [usize],
+    limit: Option<&'a Limit>,
+    windows: &'a [Option<Window>],
+    thresholds: &'a [Option<Threshold>]
+}
 
 pub struct KSan;
 
 impl KSan {
     pub fn setup(secp: &SecParams) -> Result<PublicParams, String> {
+        Self::setup_with::<EQS>(secp)
+    }
+
+    //setup with another EQS backend, e.g. KSan::setup_with::<Mercurial>(&secp).
+    pub fn setup_with<E: EQSScheme>(secp: &SecParams) -> Result<PublicParams<E>, String> {
         let bg = EQS::setup(secp.n + 1, &secp.dst);
+        let eqs = E::setup(&bg);
         let min = BigInt::from(2).pow(256);
         let mut p: BigInt;
         let mut q: BigInt;
@@ -30,32 +56,46 @@ impl KSan {
         }
         let pp = PublicParams {
             secp: secp.clone(),
-            bg: bg,
+            bg,
+            eqs,
             p: p.clone(),
             q: q.clone(),
-            g: g.clone()
+            g: g.clone(),
+            fb_vrs: None
         };
         return Ok(pp);
     }
 
-    pub fn kgen_s(pp: &mut PublicParams) -> Result<(SignerSecretKey, SignerPublicKey), String> {
-        let (sk_eqs, pk_eqs) = EQS::k_gen(&mut pp.bg);
-        let (skp, pkp) = VRS::kgen(&pp.p, &pp.q, &pp.g);
+    //Builds the fixed-base table for g mod q used by VRS, with windows of w bits.
+    pub fn precompute<E: EQSScheme>(pp: &mut PublicParams<E>, w: usize) -> Result<(), String> {
+        if w == 0 || w > 16 {
+            return Err("Window size must be between 1 and 16".to_string());
+        }
+        pp.fb_vrs = Some(FixedBase::new(&pp.g, &pp.q, w));
+        Ok(())
+    }
+
+    pub fn kgen_s<E: EQSScheme>(pp: &PublicParams<E>) -> Result<(SignerSecretKey<E>, SignerPublicKey<E>), String> {
+        let (sk_eqs, pk_eqs) = E::k_gen(&pp.bg, &pp.eqs);
+        let (skp, pkp) = VRS::kgen(&pp.p, &pp.q, &pp.g, pp.fb_vrs.as_ref());
+        let (sk, pk) = SIG::k_gen();
         let sk_s = SignerSecretKey {
+            sk,
             sk_eqs: sk_eqs,
             skp: skp
         };
         let pk_s = SignerPublicKey {
+            pk,
             pk_eqs: pk_eqs,
             pkp: pkp
         };
         return Ok((sk_s, pk_s));
     }
 
-    pub fn kgen_z(pp: &mut PublicParams) -> Result<(SanitizerSecretKey, SanitizerPublicKey), String> {
+    pub fn kgen_z<E: EQSScheme>(pp: &PublicParams<E>) -> Result<(SanitizerSecretKey, SanitizerPublicKey), String> {
         //let (pke, ske) = PKE::k_gen(&pp.kp);
         let (ske, pke) = PKE::k_gen(pp.secp.bits_pke);
-        let (skp, pkp) = VRS::kgen(&pp.p, &pp.q, &pp.g);
+        let (skp, pkp) = VRS::kgen(&pp.p, &pp.q, &pp.g, pp.fb_vrs.as_ref());
         let sk_z = SanitizerSecretKey {
             ske: ske,
             skp: skp
@@ -67,77 +107,700 @@ impl KSan {
         return Ok((sk_z, pk_z));
     }
 
-    pub fn sign<'d>(
-            pp: &mut PublicParams, sk_s: &SignerSecretKey, pk_s: &SignerPublicKey,
-            san_pks: &Vec<SanitizerPublicKey>, m: &Vec<String>, adm: &Vec<Vec<bool>>
-        ) -> Result<Signature<'d>, String> {
+    pub fn sign<E: EQSScheme>(
+            pp: &PublicParams<E>, sk_s: &SignerSecretKey<E>, pk_s: &SignerPublicKey<E>,
+            san_pks: &[SanitizerPublicKey], m: &[String], adm: &[Vec<bool>]
+        ) -> Result<Signature<E>, String> {
+        let (sig, _td) = Self::sign_with_trapdoors(pp, sk_s, pk_s, san_pks, m, adm)?;
+        Ok(sig)
+    }
+
+    //Same as sign, also returns the BLS secret keys and adm, kept by the signer for extend_sanitizers.
+    pub fn sign_with_trapdoors<E: EQSScheme>(
+            pp: &PublicParams<E>, sk_s: &SignerSecretKey<E>, pk_s: &SignerPublicKey<E>,
+            san_pks: &[SanitizerPublicKey], m: &[String], adm: &[Vec<bool>]
+        ) -> Result<(Signature<E>, Trapdoors), String> {
+        Self::sign_full(pp, sk_s, pk_s, san_pks, m, adm, &SignOptions::new(m.len()))
+    }
+
+    //Same as sign_with_trapdoors, with any combination of the options below.
+    pub fn sign_with_options<E: EQSScheme>(
+            pp: &PublicParams<E>, sk_s: &SignerSecretKey<E>, pk_s: &SignerPublicKey<E>,
+            san_pks: &[SanitizerPublicKey], m: &[String], adm: &[Vec<bool>], opts: &SignOptions
+        ) -> Result<(Signature<E>, Trapdoors), String> {
+        Self::sign_full(pp, sk_s, pk_s, san_pks, m, adm, opts)
+    }
+
+    //Same as sign, with slots[i] empty blocks appended to m for san_pks[i] to fill (append).
+    pub fn sign_with_slots<E: EQSScheme>(
+            pp: &PublicParams<E>, sk_s: &SignerSecretKey<E>, pk_s: &SignerPublicKey<E>,
+            san_pks: &[SanitizerPublicKey], m: &[String], adm: &[Vec<bool>], slots: &[usize]
+        ) -> Result<Signature<E>, String> {
+        let opts = SignOptions { slots: slots.to_vec(), ..SignOptions::new(m.len()) };
+        let (sig, _td) = Self::sign_full(pp, sk_s, pk_s, san_pks, m, adm, &opts)?;
+        Ok(sig)
+    }
+
+    //Same as sign, policy[j] says whether the admissible sanitizers of block j may modify it, redact it or both.
+    pub fn sign_with_policy<E: EQSScheme>(
+            pp: &PublicParams<E>, sk_s: &SignerSecretKey<E>, pk_s: &SignerPublicKey<E>,
+            san_pks: &[SanitizerPublicKey], m: &[String], adm: &[Vec<bool>], policy: &[Policy]
+        ) -> Result<Signature<E>, String> {
+        let opts = SignOptions { policy: policy.to_vec(), ..SignOptions::new(m.len()) };
+        let (sig, _td) = Self::sign_full(pp, sk_s, pk_s, san_pks, m, adm, &opts)?;
+        Ok(sig)
+    }
+
+    //Same as sign, values[j] is the set of the values allowed for block j (None for any value).
+    pub fn sign_with_values<E: EQSScheme>(
+            pp: &PublicParams<E>, sk_s: &SignerSecretKey<E>, pk_s: &SignerPublicKey<E>,
+            san_pks: &[SanitizerPublicKey], m: &[String], adm: &[Vec<bool>], values: &[Option<Vec<String>>]
+        ) -> Result<Signature<E>, String> {
+        let opts = SignOptions { values: values.to_vec(), ..SignOptions::new(m.len()) };
+        let (sig, _td) = Self::sign_full(pp, sk_s, pk_s, san_pks, m, adm, &opts)?;
+        Ok(sig)
+    }
+
+    //Same as sign, each sanitizer may sanitize the document at most limit times.
+    pub fn sign_with_limit<E: EQSScheme>(
+            pp: &PublicParams<E>, sk_s: &SignerSecretKey<E>, pk_s: &SignerPublicKey<E>,
+            san_pks: &[SanitizerPublicKey], m: &[String], adm: &[Vec<bool>], limit: usize
+        ) -> Result<Signature<E>, String> {
+        let opts = SignOptions { limit: Some(limit), ..SignOptions::new(m.len()) };
+        let (sig, _td) = Self::sign_full(pp, sk_s, pk_s, san_pks, m, adm, &opts)?;
+        Ok(sig)
+    }
+
+    //Same as sign, groups[j] is the group of sanitizers t of which must cooperate to modify block j, if any.
+    pub fn sign_with_thresholds<E: EQSScheme>(
+            pp: &PublicParams<E>, sk_s: &SignerSecretKey<E>, pk_s: &SignerPublicKey<E>,
+            san_pks: &[SanitizerPublicKey], m: &[String], adm: &[Vec<bool>], groups: &[Option<Group>]
+        ) -> Result<Signature<E>, String> {
+        let opts = SignOptions { groups: groups.to_vec(), ..SignOptions::new(m.len()) };
+        let (sig, _td) = Self::sign_full(pp, sk_s, pk_s, san_pks, m, adm, &opts)?;
+        Ok(sig)
+    }
+
+    fn sign_full<E: EQSScheme>(
+            pp: &PublicParams<E>, sk_s: &SignerSecretKey<E>, pk_s: &SignerPublicKey<E>,
+            san_pks: &[SanitizerPublicKey], m: &[String], adm: &[Vec<bool>], opts: &SignOptions
+        ) -> Result<(Signature<E>, Trapdoors), String> {
         let k = san_pks.len();
-        let n = pp.bg.n as usize;
-        if m.len() != n - 1 {
-            return Err("m must have n-1 elements".to_string());
+        let l = m.len();
+        check_adm(adm, k, l)?;
+        let value_sets = value_sets(m, &opts.values)?;
+        if opts.policy.len() != l {
+            return Err("policy must have one element per block".to_string());
+        }
+        if !opts.slots.is_empty() && opts.slots.len() != k {
+            return Err("slots must have one element per sanitizer".to_string());
+        }
+        if opts.limit == Some(0) {
+            return Err("The limit must be at least 1".to_string());
+        }
+        if !opts.windows.is_empty() {
+            check_windows(&opts.windows, k)?;
+            if opts.now.is_none() {
+                return Err("A signature with validity windows needs a time".to_string());
+            }
         }
+        if opts.groups.len() != l {
+            return Err("groups must have one element per block".to_string());
+        }
+        check_groups(&opts.groups, k)?;
+        let owners: Vec<usize> = opts.slots.iter().enumerate().flat_map(|(i, c)| vec![i; *c]).collect();
+        let n = Self::vector_len(pp, l + owners.len())?;
+        let m: Vec<String> = m.iter().cloned().chain(vec![String::new(); owners.len()]).collect();
+        let mut policy = opts.policy.clone();
+        policy.extend(vec![Policy { modify: true, redact: false }; owners.len()]);
+        let mut value_sets = value_sets;
+        value_sets.extend(vec![None; owners.len()]);
+        let mut groups = opts.groups.clone();
+        groups.extend(vec![None; owners.len()]);
+        let adm: Vec<Vec<bool>> = adm.iter().enumerate()
+            .map(|(i, a)| (0..n - 1).map(|j| if j < l {
+                a[j] && groups[j].is_none() && (policy[j].modify || policy[j].redact)
+            } else {
+                owners[j - l] == i
+            }).collect())
+            .collect();
+        let mut rng = thread_rng();
+        let commitments: Vec<Option<Commitment>> = (0..n - 1).map(|j| {
+            if policy[j].modify || !policy[j].redact {
+                return None;
+            }
+            let salt: [u8; 32] = rng.gen();
+            Some(Commitment { c: Self::commit(&salt, j, &m[j]), salt: Some(salt.to_vec()) })
+        }).collect();
         let zero = BigInt::from(0);
-        let mut secrets: Vec<Vec<RawCiphertext<'d>>> = vec![Vec::with_capacity(n); k];
-        let mut m_ = m.clone();
-        m_.push(Self::pkz_to_string(&san_pks));
-        let mut adm_ = adm.clone();
-        for i in 0..k {
-            adm_[i].push(false);
+        let mut secrets: Vec<Vec<Ciphertext>> = vec![Vec::with_capacity(n); k];
+        let limit = opts.limit.map(|x| Self::new_limit(pp, sk_s, x));
+        let bg = &pp.bg;
+        let keys = map_range(n, |_| BLS::k_gen(bg));
+        let sk2_bls: Vec<Fr> = keys.iter().map(|x| x.1).collect();
+        let pk1_bls: Vec<G1Projective> = keys.iter().map(|x| x.2).collect();
+        let pk2_bls: Vec<G1Projective> = keys.iter().map(|x| x.3).collect();
+        let order = Self::order();
+        let thresholds: Vec<Option<Threshold>> = map_range(n - 1, |j| groups[j].as_ref().map(|g| {
+            let sk2 = sk2_bls[j].to_string().parse::<BigInt>().unwrap();
+            let shares = Shamir::share(&order, &sk2, g.t, g.members.len());
+            Threshold {
+                group: g.clone(),
+                shares: g.members.iter().zip(shares.iter()).map(|(i, y)| PKE::encrypt(&san_pks[*i].pke, y)).collect()
+            }
+        }));
+        let mut m_ = m.to_vec();
+        let v = Bound {
+            value_sets: &value_sets,
+            policy: &policy,
+            commitments: &commitments,
+            slots: &owners,
+            limit: limit.as_ref(),
+            windows: &opts.windows,
+            thresholds: &thresholds
+        };
+        m_.push(Self::last_block(san_pks, m.len(), &v));
+        let mut adm_ = adm.to_vec();
+        for a in adm_.iter_mut() {
+            a.push(false);
         }
-        let mut sk1_bls: Vec<Fr> = Vec::with_capacity(n);
-        let mut sk2_bls: Vec<Fr> = Vec::with_capacity(n);
-        let mut pk1_bls: Vec<G1Projective> = Vec::with_capacity(n);
-        let mut pk2_bls: Vec<G1Projective> = Vec::with_capacity(n);
         let mut s_bls: Vec<G2Projective> = Vec::with_capacity(n);
-        for j in 0..n {
-            let (sk1, sk2, pk1, pk2) = BLS::k_gen(&mut pp.bg);
-            sk1_bls.push(sk1);
-            sk2_bls.push(sk2);
-            pk1_bls.push(pk1);
-            pk2_bls.push(pk2);
-            let mj = j.to_string() + m_[j].as_str();
-            let s = BLS::sign(&mut pp.bg, &sk2, &mj);
-            s_bls.push(s);
-            for i in 0..k {
+        let blocks: Vec<(G2Projective, Vec<Ciphertext>)> = map_range(n, |j| {
+            let mj = block_input(j, &m_[j], false, j >= l && j < n - 1);
+            let s = BLS::sign(bg, &sk2_bls[j], &mj);
+            let cs = map_range(k, |i| {
                 if adm_[i][j] {
-                    secrets[i].push(PKE::encrypt(&san_pks[i].pke, &BigInt::from(sk2.to_string().parse::<BigInt>().unwrap())));
+                    return PKE::encrypt(&san_pks[i].pke, &sk2_bls[j].to_string().parse::<BigInt>().unwrap());
+                }
+                PKE::encrypt(&san_pks[i].pke, &zero)
+            });
+            (s, cs)
+        });
+        for (s, cs) in blocks {
+            s_bls.push(s);
+            for (i, c) in cs.into_iter().enumerate() {
+                secrets[i].push(c);
+            }
+        }
+        let s_x_eqs = E::sign(&pp.bg, &pp.eqs, &sk_s.sk_eqs, 
+            &pk1_bls.iter().map(|x| x.into_affine()).collect::<Vec<_>>());
+        let s_y_eqs = E::sign(&pp.bg, &pp.eqs, &sk_s.sk_eqs, 
+            &pk2_bls.iter().map(|x| x.into_affine()).collect::<Vec<_>>());
+        let s_ss = SignatureSS {
+            s_x_eqs,
+            s_y_eqs,
+            s_bls,
+            pk1_bls,
+            pk2_bls,
+            secrets,
+            delegations: Vec::new(),
+            covered: 0,
+            value_sets,
+            policy,
+            commitments,
+            redacted: Vec::new(),
+            slots: owners,
+            filled: Vec::new(),
+            limit,
+            tags: Vec::new(),
+            windows: opts.windows.clone(),
+            time: opts.now,
+            thresholds
+        };
+        let td = Trapdoors {
+            sk2_bls,
+            adm
+        };
+        Ok((Self::sign_ss(pp, sk_s, pk_s, san_pks, &m_, s_ss), td))
+    }
+
+    //Grants the sanitizer ext.pk_z the blocks of ext.adm_z on a signature that has not been sanitized yet, without
+    //new BLS keys or EQS signatures: the BLS secret keys are encrypted under pk_z, the last block is signed again for
+    //the sanitizers san_pks followed by pk_z and so is the VRS ring signature.
+    pub fn extend_sanitizers<E: EQSScheme>(
+        pp: &PublicParams<E>, sk_s: &SignerSecretKey<E>, pk_s: &SignerPublicKey<E>, san_pks: &[SanitizerPublicKey],
+        m: &[String], sig: &Signature<E>, ext: &Extension
+    ) -> Result<(Signature<E>, Trapdoors), String> {
+        let Extension { pk_z, adm_z, td } = ext;
+        let n = Self::vector_len(pp, m.len())?;
+        Self::check_trapdoors(pp, san_pks, m, sig, td)?;
+        if adm_z.len() != n - 1 {
+            return Err("adm_z must have one element per block".to_string());
+        }
+        if san_pks.iter().any(|x| x.pkp == pk_z.pkp) {
+            return Err("The sanitizer is already in san_pks".to_string());
+        }
+        let zero = BigInt::from(0);
+        let adm_z: Vec<bool> = (0..n - 1).map(|j| adm_z[j] && (sig.s_ss.policy[j].modify || sig.s_ss.policy[j].redact)).collect();
+        let cs = map_range(n, |j| {
+            if j < n - 1 && adm_z[j] {
+                return PKE::encrypt(&pk_z.pke, &td.sk2_bls[j].to_string().parse::<BigInt>().unwrap());
+            }
+            PKE::encrypt(&pk_z.pke, &zero)
+        });
+        let mut san_pks_p = san_pks.to_owned();
+        san_pks_p.push(pk_z.clone());
+        let limit = sig.s_ss.limit.as_ref().map(|x| Self::new_limit(pp, sk_s, x.max));
+        let mut windows = sig.s_ss.windows.clone();
+        if !windows.is_empty() {
+            windows.push(None);
+        }
+        let mut m_ = m.to_vec();
+        let v = Bound { limit: limit.as_ref(), windows: &windows, ..Self::bound(&sig.s_ss) };
+        m_.push(Self::last_block(&san_pks_p, m.len(), &v));
+        let mut s_bls = sig.s_ss.s_bls.clone();
+        s_bls[n - 1] = BLS::sign(&pp.bg, &td.sk2_bls[n - 1], &block_input(n - 1, &m_[n - 1], false, false));
+        let mut secrets = sig.s_ss.secrets.clone();
+        secrets.push(cs);
+        let s_ss = SignatureSS {
+            s_x_eqs: sig.s_ss.s_x_eqs.clone(),
+            s_y_eqs: sig.s_ss.s_y_eqs.clone(),
+            s_bls,
+            pk1_bls: sig.s_ss.pk1_bls.clone(),
+            pk2_bls: sig.s_ss.pk2_bls.clone(),
+            secrets,
+            delegations: Vec::new(),
+            covered: 0,
+            value_sets: sig.s_ss.value_sets.clone(),
+            policy: sig.s_ss.policy.clone(),
+            commitments: sig.s_ss.commitments.clone(),
+            redacted: Vec::new(),
+            slots: sig.s_ss.slots.clone(),
+            filled: Vec::new(),
+            limit,
+            tags: Vec::new(),
+            windows,
+            time: sig.s_ss.time,
+            thresholds: sig.s_ss.thresholds.clone()
+        };
+        let mut adm = td.adm.clone();
+        adm.push(adm_z.to_vec());
+        let tdp = Trapdoors {
+            sk2_bls: td.sk2_bls.clone(),
+            adm
+        };
+        Ok((Self::sign_ss(pp, sk_s, pk_s, &san_pks_p, &m_, s_ss), tdp))
+    }
+
+    //Removes the sanitizer rev.pk_z from a signature that has not been sanitized yet. The blocks that were admissible
+    //for pk_z get a new BLS key pair, whose secret key is encrypted for the other sanitizers that have rights on
+    //them, and the EQS signatures are computed again. The other blocks are kept. The signature is for san_pks without
+    //pk_z. Sanitized signatures are rejected since the signer cannot compute the VRS signature of the sanitizer
+    //again, it has to sign the current message instead.
+    pub fn revoke<E: EQSScheme>(
+        pp: &PublicParams<E>, sk_s: &SignerSecretKey<E>, pk_s: &SignerPublicKey<E>, san_pks: &[SanitizerPublicKey],
+        m: &[String], sig: &Signature<E>, rev: &Revocation
+    ) -> Result<(Signature<E>, Trapdoors), String> {
+        let Revocation { pk_z, td } = rev;
+        let n = Self::vector_len(pp, m.len())?;
+        Self::check_trapdoors(pp, san_pks, m, sig, td)?;
+        let ir = match san_pks.iter().position(|x| x.pkp == pk_z.pkp) {
+            Some(ir) => ir,
+            None => return Err("The sanitizer is not in san_pks".to_string()),
+        };
+        let mut san_pks_p = san_pks.to_owned();
+        san_pks_p.remove(ir);
+        let mut adm = td.adm.clone();
+        adm.remove(ir);
+        let mut secrets = sig.s_ss.secrets.clone();
+        secrets.remove(ir);
+        let k = san_pks_p.len();
+        let zero = BigInt::from(0);
+        let limit = sig.s_ss.limit.as_ref().map(|x| Self::new_limit(pp, sk_s, x.max));
+        let mut windows = sig.s_ss.windows.clone();
+        if !windows.is_empty() {
+            windows.remove(ir);
+        }
+        let mut m_ = m.to_vec();
+        let v = Bound { limit: limit.as_ref(), windows: &windows, ..Self::bound(&sig.s_ss) };
+        m_.push(Self::last_block(&san_pks_p, m.len(), &v));
+        let mut sk2_bls = td.sk2_bls.clone();
+        let mut pk1_bls = sig.s_ss.pk1_bls.clone();
+        let mut pk2_bls = sig.s_ss.pk2_bls.clone();
+        let mut s_bls = sig.s_ss.s_bls.clone();
+        let bg = &pp.bg;
+        let blocks = map_range(n - 1, |j| {
+            if !td.adm[ir][j] {
+                return None;
+            }
+            let (_sk1, sk2, pk1, pk2) = BLS::k_gen(bg);
+            let mj = Self::input(sig, j, &m_[j]);
+            let s = BLS::sign(bg, &sk2, &mj);
+            let cs = map_range(k, |i| {
+                if adm[i][j] {
+                    return PKE::encrypt(&san_pks_p[i].pke, &sk2.to_string().parse::<BigInt>().unwrap());
+                }
+                PKE::encrypt(&san_pks_p[i].pke, &zero)
+            });
+            Some((sk2, pk1, pk2, s, cs))
+        });
+        for (j, b) in blocks.into_iter().enumerate() {
+            if let Some((sk2, pk1, pk2, s, cs)) = b {
+                sk2_bls[j] = sk2;
+                pk1_bls[j] = pk1;
+                pk2_bls[j] = pk2;
+                s_bls[j] = s;
+                for (i, c) in cs.into_iter().enumerate() {
+                    secrets[i][j] = c;
+                }
+            }
+        }
+        s_bls[n - 1] = BLS::sign(bg, &sk2_bls[n - 1], &block_input(n - 1, &m_[n - 1], false, false));
+        let s_x_eqs = E::sign(&pp.bg, &pp.eqs, &sk_s.sk_eqs, 
+            &pk1_bls.iter().map(|x| x.into_affine()).collect::<Vec<_>>());
+        let s_y_eqs = E::sign(&pp.bg, &pp.eqs, &sk_s.sk_eqs, 
+            &pk2_bls.iter().map(|x| x.into_affine()).collect::<Vec<_>>());
+        let s_ss = SignatureSS {
+            s_x_eqs,
+            s_y_eqs,
+            s_bls,
+            pk1_bls,
+            pk2_bls,
+            secrets,
+            delegations: Vec::new(),
+            covered: 0,
+            value_sets: sig.s_ss.value_sets.clone(),
+            policy: sig.s_ss.policy.clone(),
+            commitments: sig.s_ss.commitments.clone(),
+            redacted: Vec::new(),
+            slots: sig.s_ss.slots.clone(),
+            filled: Vec::new(),
+            limit,
+            tags: Vec::new(),
+            windows,
+            time: sig.s_ss.time,
+            thresholds: sig.s_ss.thresholds.clone()
+        };
+        let tdp = Trapdoors {
+            sk2_bls,
+            adm
+        };
+        Ok((Self::sign_ss(pp, sk_s, pk_s, &san_pks_p, &m_, s_ss), tdp))
+    }
+
+    //Revocation list of the signer for the given epoch, signed with its Schnorr key.
+    pub fn revocation_list<E: EQSScheme>(
+        _pp: &PublicParams<E>, sk_s: &SignerSecretKey<E>, pk_s: &SignerPublicKey<E>, epoch: u64,
+        revoked: &[SanitizerPublicKey]
+    ) -> Result<RevocationList, String> {
+        let revoked: Vec<BigInt> = revoked.iter().map(|x| x.pkp.clone()).collect();
+        let t = Self::revocation_message(pk_s, epoch, &revoked);
+        let rl = RevocationList {
+            epoch,
+            revoked,
+            s: SIG::sign(&sk_s.sk, &t)
+        };
+        Ok(rl)
+    }
+
+    pub fn verify_revocation_list<E: EQSScheme>(
+        _pp: &PublicParams<E>, pk_s: &SignerPublicKey<E>, rl: &RevocationList
+    ) -> Result<bool, String> {
+        let t = Self::revocation_message(pk_s, rl.epoch, &rl.revoked);
+        Ok(SIG::verify(&pk_s.pk, &t, &rl.s))
+    }
+
+    //verify, and checks that rl is a revocation list of the signer in which none of the sanitizers of the
+    //signature appear.
+    pub fn verify_with_revocation<E: EQSScheme>(
+        pp: &PublicParams<E>, pk_s: &SignerPublicKey<E>,
+        san_pks: &[SanitizerPublicKey], m: &[String], sig: &Signature<E>, rl: &RevocationList
+    ) -> Result<bool, String> {
+        if !Self::verify_revocation_list(pp, pk_s, rl)? {
+            return Ok(false);
+        }
+        if san_pks.iter().any(|x| rl.revoked.contains(&x.pkp)) {
+            return Ok(false);
+        }
+        Self::verify(pp, pk_s, san_pks, m, sig)
+    }
+
+    //Offline phase of sign: precomputes size signatures worth of material that does not depend on the message
+    //(BLS key pairs, EQS signatures on the BLS public keys, encryptions of the BLS secret keys and of zero), for
+    //messages of l blocks.
+    pub fn sign_offline<E: EQSScheme>(
+            pp: &PublicParams<E>, sk_s: &SignerSecretKey<E>, san_pks: &[SanitizerPublicKey], l: usize, size: usize
+        ) -> Result<SignPool<E>, String> {
+        let k = san_pks.len();
+        let n = Self::vector_len(pp, l)?;
+        let zero = BigInt::from(0);
+        let mut sigs: Vec<PreSig<E>> = Vec::with_capacity(size);
+        for _ in 0..size {
+            let keys = map_range(n, |_| BLS::k_gen(&pp.bg));
+            let sk2_bls: Vec<Fr> = keys.iter().map(|x| x.1).collect();
+            let pk1_bls: Vec<G1Projective> = keys.iter().map(|x| x.2).collect();
+            let pk2_bls: Vec<G1Projective> = keys.iter().map(|x| x.3).collect();
+            let s_x_eqs = E::sign(&pp.bg, &pp.eqs, &sk_s.sk_eqs, 
+                &pk1_bls.iter().map(|x| x.into_affine()).collect::<Vec<_>>());
+            let s_y_eqs = E::sign(&pp.bg, &pp.eqs, &sk_s.sk_eqs, 
+                &pk2_bls.iter().map(|x| x.into_affine()).collect::<Vec<_>>());
+            let c_trap = map_range(k, |i| map_range(n, |j| 
+                PKE::encrypt(&san_pks[i].pke, &sk2_bls[j].to_string().parse::<BigInt>().unwrap())));
+            let c_zero = map_range(k, |i| map_range(n, |_| PKE::encrypt(&san_pks[i].pke, &zero)));
+            sigs.push(PreSig {
+                sk2_bls,
+                pk1_bls,
+                pk2_bls,
+                s_x_eqs,
+                s_y_eqs,
+                c_trap,
+                c_zero
+            });
+        }
+        let pool = SignPool {
+            san_pks: san_pks.to_vec(),
+            sigs
+        };
+        Ok(pool)
+    }
+
+    //Online phase of sign: same output as sign, the precomputed signature is taken out of the pool so it is
+    //never reused. The pool must have been computed with sk_s.
+    pub fn sign_online<E: EQSScheme>(
+            pp: &PublicParams<E>, sk_s: &SignerSecretKey<E>, pk_s: &SignerPublicKey<E>,
+            san_pks: &[SanitizerPublicKey], m: &[String], adm: &[Vec<bool>], pool: &mut SignPool<E>
+        ) -> Result<Signature<E>, String> {
+        let k = san_pks.len();
+        let n = Self::vector_len(pp, m.len())?;
+        check_adm(adm, k, m.len())?;
+        if pool.san_pks.len() != k || 
+            (0..k).any(|i| pool.san_pks[i].pkp != san_pks[i].pkp || pool.san_pks[i].pke.n != san_pks[i].pke.n) {
+            return Err("The pool was computed for another set of sanitizers".to_string());
+        }
+        match pool.sigs.last() {
+            Some(pre) if pre.sk2_bls.len() != n => {
+                return Err("The pool was computed for another message length".to_string());
+            }
+            Some(_) => (),
+            None => return Err("Not enough precomputed signatures in the pool".to_string()),
+        }
+        let pre = pool.sigs.pop().unwrap();
+        let value_sets: Vec<Option<ValueSet>> = vec![None; m.len()];
+        let policy = default_policy(m.len());
+        let commitments: Vec<Option<Commitment>> = vec![None; m.len()];
+        let mut m_ = m.to_vec();
+        let v = Bound {
+            value_sets: &value_sets,
+            policy: &policy,
+            commitments: &commitments,
+            slots: &[],
+            limit: None,
+            windows: &[],
+            thresholds: &vec![None; m.len()]
+        };
+        m_.push(Self::last_block(san_pks, m.len(), &v));
+        let bg = &pp.bg;
+        let s_bls: Vec<G2Projective> = map_range(n, |j| {
+            let mj = block_input(j, &m_[j], false, false);
+            BLS::sign(bg, &pre.sk2_bls[j], &mj)
+        });
+        let mut secrets: Vec<Vec<Ciphertext>> = vec![Vec::with_capacity(n); k];
+        for i in 0..k {
+            for j in 0..n {
+                if j < n - 1 && adm[i].get(j) == Some(&true) {
+                    secrets[i].push(pre.c_trap[i][j].clone());
                 } else {
-                    secrets[i].push(PKE::encrypt(&san_pks[i].pke, &zero));
+                    secrets[i].push(pre.c_zero[i][j].clone());
                 }
             }
         }
-        let s_x_eqs = EQS::sign(&mut pp.bg, &sk_s.sk_eqs, 
-            &pk1_bls.iter().map(|x| x.into_affine()).collect());
-        let s_y_eqs = EQS::sign(&mut pp.bg, &sk_s.sk_eqs, 
-            &pk2_bls.iter().map(|x| x.into_affine()).collect());
         let s_ss = SignatureSS {
-            s_x_eqs: s_x_eqs,
-            s_y_eqs: s_y_eqs,
-            s_bls: s_bls,
-            pk1_bls: pk1_bls,
-            pk2_bls: pk2_bls,
-            secrets: secrets
-        };
-        let t = Self::generate_t(&pk_s, &m_, &s_ss);
-        let mut ring: Vec<BigInt> = san_pks.iter().map(|x| x.pkp.clone()).collect();
-        ring.push(pk_s.pkp.clone());
-        let s_vrs = VRS::sign(&pp.p, &pp.q, &pp.g, &sk_s.skp, &ring, &t);
-        let sig = Signature {
-            s_ss: s_ss,
-            s_vrs: s_vrs
+            s_x_eqs: pre.s_x_eqs,
+            s_y_eqs: pre.s_y_eqs,
+            s_bls,
+            pk1_bls: pre.pk1_bls,
+            pk2_bls: pre.pk2_bls,
+            secrets,
+            delegations: Vec::new(),
+            covered: 0,
+            value_sets,
+            policy,
+            commitments,
+            redacted: Vec::new(),
+            slots: Vec::new(),
+            filled: Vec::new(),
+            limit: None,
+            tags: Vec::new(),
+            windows: Vec::new(),
+            time: None,
+            thresholds: vec![None; m.len()]
         };
-        return Ok(sig);
+        Ok(Self::sign_ss(pp, sk_s, pk_s, san_pks, &m_, s_ss))
     }
 
-    pub fn sanitize<'d>(
-        pp: &mut PublicParams, sk_z: &SanitizerSecretKey, pk_s: &SignerPublicKey, pk_z: &SanitizerPublicKey,
-        san_pks: &Vec<SanitizerPublicKey>, m: &Vec<String>, modif: &Vec<Mod>, sig: &Signature
-    ) -> Result<Signature<'d>, String> {
+    pub fn sanitize<E: EQSScheme>(
+        pp: &PublicParams<E>, sk_z: &SanitizerSecretKey, pk_s: &SignerPublicKey<E>, pk_z: &SanitizerPublicKey,
+        san_pks: &[SanitizerPublicKey], m: &[String], modif: &[Mod], sig: &Signature<E>
+    ) -> Result<Signature<E>, String> {
+        Self::sanitize_with_options(pp, sk_z, pk_s, pk_z, san_pks, &SanitizeOptions::new(m, modif), sig)
+    }
+
+    //Original indexes of the slots of pk_z that have not been filled yet.
+    pub fn unfilled_slots<E: EQSScheme>(
+        san_pks: &[SanitizerPublicKey], pk_z: &SanitizerPublicKey, sig: &Signature<E>
+    ) -> Vec<usize> {
+        let ip = match san_pks.iter().position(|x| x.pkp == pk_z.pkp) {
+            Some(ip) => ip,
+            None => return Vec::new(),
+        };
+        let l = sig.s_ss.policy.len() - sig.s_ss.slots.len().min(sig.s_ss.policy.len());
+        (0..sig.s_ss.slots.len()).filter(|x| sig.s_ss.slots[*x] == ip).map(|x| l + x)
+            .filter(|j| Self::unfilled(sig, *j)).collect()
+    }
+
+    //Original indexes of the message blocks of sig that pk_z may modify: the blocks whose BLS secret key it can
+    //decrypt from its secrets (or its delegations) and that may be modified by the policy, without producing a
+    //signature. The validity windows, the limit and the threshold blocks are not taken into account.
+    pub fn admissible_blocks<E: EQSScheme>(
+        _pp: &PublicParams<E>, sk_z: &SanitizerSecretKey, pk_z: &SanitizerPublicKey, san_pks: &[SanitizerPublicKey],
+        sig: &Signature<E>
+    ) -> Result<Vec<usize>, String> {
         let k = san_pks.len();
-        let n = pp.bg.n as usize;
+        let l = sig.s_ss.policy.len();
+        let ip = san_pks.iter().position(|x| x.pkp == pk_z.pkp);
+        if ip.is_none() && !sig.s_ss.delegations.iter().any(|d| d.pk_d.pkp == pk_z.pkp) {
+            return Err("The sanitizer is neither in san_pks nor a delegate".to_string());
+        }
+        if sig.s_ss.secrets.len() != k || sig.s_ss.secrets.iter().any(|c| c.len() != l + 1) {
+            return Err("The signature is not well formed".to_string());
+        }
+        let zero = BigInt::from(0);
+        let admissible: Vec<bool> = map_range(l, |j| {
+            if !sig.s_ss.policy[j].modify || sig.s_ss.redacted.contains(&j) {
+                return false;
+            }
+            Self::secret(sk_z, pk_z, ip, sig, j) != zero
+        });
+        Ok((0..l).filter(|j| admissible[*j]).collect())
+    }
+
+    //First round of a threshold sanitization: partial BLS signatures by pk_z on the new values of the threshold
+    //blocks modified by opts.modif, for the sanitization in which the sanitizers signers (indexes in san_pks, pk_z
+    //included) cooperate. pk_z must be in the group of each of these blocks, and signs block j with
+    //lagrange(signers, x) * share_x, x being its point in the group.
+    pub fn sanitize_partial<E: EQSScheme>(
+        pp: &PublicParams<E>, sk_z: &SanitizerSecretKey, pk_z: &SanitizerPublicKey, san_pks: &[SanitizerPublicKey],
+        opts: &SanitizeOptions, signers: &[usize], sig: &Signature<E>
+    ) -> Result<PartialAdaptation, String> {
+        let ip = match san_pks.iter().position(|x| x.pkp == pk_z.pkp) {
+            Some(ip) => ip,
+            None => return Err("The sanitizer is not in san_pks".to_string()),
+        };
+        if !signers.contains(&ip) || !members_valid(signers, san_pks.len()) {
+            return Err("The signers must be distinct sanitizers of san_pks, the sanitizer included".to_string());
+        }
+        let order = Self::order();
+        let mut blocks: Vec<usize> = Vec::new();
+        let mut s_bls: Vec<G2Projective> = Vec::new();
+        for modif in opts.modif.iter() {
+            let th = match sig.s_ss.thresholds.get(modif.i) {
+                Some(Some(th)) => th,
+                _ => continue,
+            };
+            if signers.len() < th.group.t || signers.iter().any(|i| !th.group.members.contains(i)) {
+                return Err("The signers must be t members of the group of the block".to_string());
+            }
+            let xs: Vec<usize> = signers.iter()
+                .map(|i| th.group.members.iter().position(|x| x == i).unwrap() + 1)
+                .collect();
+            let x = th.group.members.iter().position(|x| *x == ip).unwrap() + 1;
+            let y = match th.shares.get(x - 1) {
+                Some(c) => PKE::decrypt(&sk_z.ske, c),
+                None => return Err("The share of the sanitizer is missing".to_string()),
+            };
+            let a = (Shamir::lagrange(&order, &xs, x) * y) % &order;
+            let mpj = block_input(modif.i, &modif.m, false, false);
+            blocks.push(modif.i);
+            s_bls.push(BLS::sign(&pp.bg, &Fr::from(BigUint::from_bytes_be(&a.to_bytes_be().1)), &mpj));
+        }
+        if blocks.is_empty() {
+            return Err("The modification does not change any threshold block".to_string());
+        }
+        let partial = PartialAdaptation {
+            member: ip,
+            signers: signers.to_vec(),
+            blocks,
+            s_bls
+        };
+        Ok(partial)
+    }
+
+    //Combines the partial adaptations of the threshold blocks (SanitizeOptions::partials) of all the signers of a
+    //threshold sanitization: the sum of their partial signatures is a BLS signature under the key of the block
+    //if at least t of them cooperated, and is then randomized as the other blocks.
+    fn combine_partials(partials: &[PartialAdaptation]) -> Result<Vec<(usize, G2Projective)>, String> {
+        let first = match partials.first() {
+            Some(x) => x,
+            None => return Ok(Vec::new()),
+        };
+        let mut members: Vec<usize> = partials.iter().map(|x| x.member).collect();
+        members.sort();
+        members.dedup();
+        let mut signers = first.signers.clone();
+        signers.sort();
+        if members != signers || partials.len() != signers.len() || partials.iter().any(|x| {
+            x.signers != first.signers || x.blocks != first.blocks || x.s_bls.len() != x.blocks.len()
+        }) {
+            return Err("There must be one partial adaptation of the same blocks by each signer".to_string());
+        }
+        let adapted = first.blocks.iter().enumerate()
+            .map(|(e, j)| (*j, partials.iter().map(|x| x.s_bls[e]).sum::<G2Projective>()))
+            .collect();
+        Ok(adapted)
+    }
+
+    //Same as sanitize, with the message, the modifications, the redacted and appended blocks, the value sets of the
+    //modified blocks given by the signer, the time and the partial adaptations of the threshold blocks in opts.
+    pub fn sanitize_with_options<E: EQSScheme>(
+        pp: &PublicParams<E>, sk_z: &SanitizerSecretKey, pk_s: &SignerPublicKey<E>, pk_z: &SanitizerPublicKey,
+        san_pks: &[SanitizerPublicKey], opts: &SanitizeOptions, sig: &Signature<E>
+    ) -> Result<Signature<E>, String> {
+        let SanitizeOptions { m, modif, redact, append, values, now, partials } = opts;
+        let now = *now;
+        let free = Self::unfilled_slots(san_pks, pk_z, sig);
+        if append.len() > free.len() {
+            return Err("Not enough unfilled slots for the sanitizer".to_string());
+        }
+        let modif: Vec<Mod> = modif.iter().cloned()
+            .chain(append.iter().zip(free.iter()).map(|(b, j)| Mod { i: *j, m: b.clone() }))
+            .collect();
+        let adapted = Self::combine_partials(partials)?;
+        let k = san_pks.len();
+        let m_ = match Self::full_message(san_pks, m, sig) {
+            Some(m_) => m_,
+            None => return Err("The signature does not match the length of m".to_string()),
+        };
+        let n = Self::vector_len(pp, m_.len() - 1)?;
+        if sig.s_ss.s_bls.len() != n || sig.s_ss.secrets.len() != k || sig.s_ss.secrets.iter().any(|c| c.len() != n) {
+            return Err("The signature does not match the length of m".to_string());
+        }
+        for modif in modif.iter() {
+            if modif.i >= n - 1 || sig.s_ss.redacted.contains(&modif.i) {
+                return Err("The modification is out of the message".to_string());
+            }
+            if !sig.s_ss.policy[modif.i].modify {
+                return Err("The block may not be modified".to_string());
+            }
+        }
+        let mut redacted = sig.s_ss.redacted.clone();
+        for j in redact.iter() {
+            if *j >= n - 1 || redacted.contains(j) {
+                return Err("The redacted blocks must be blocks of the message".to_string());
+            }
+            if !sig.s_ss.policy[*j].redact {
+                return Err("The block may not be redacted".to_string());
+            }
+            redacted.push(*j);
+        }
+        redacted.sort();
+        let mut filled = sig.s_ss.filled.clone();
+        for modif in modif.iter() {
+            if Self::unfilled(sig, modif.i) && !filled.contains(&modif.i) {
+                filled.push(modif.i);
+            }
+        }
+        filled.sort();
         let zero = BigInt::from(0);
-        let mut m_ = m.clone();
-        m_.push(Self::pkz_to_string(&san_pks));
         let mut mp: Vec<String> = Vec::with_capacity(n);
         for j in 0..n {
             mp.push(m_[j].clone());
@@ -145,54 +808,125 @@ impl KSan {
         for modif in modif.iter() {
             mp[modif.i] = modif.m.clone();
         }
-        let mut ip: usize = 0;
-        for i in 0..k {
-            if san_pks[i].pkp == pk_z.pkp {
-                ip = i;
-                break;
+        let value_sets = update_value_sets(&sig.s_ss.value_sets, &m_[..n - 1], &mp, values)?;
+        let mut commitments = sig.s_ss.commitments.clone();
+        for j in redact.iter() {
+            mp[*j] = String::new();
+            if let Some(c) = &mut commitments[*j] {
+                c.salt = None;
+            }
+        }
+        let ip = san_pks.iter().position(|x| x.pkp == pk_z.pkp);
+        if ip.is_none() && !sig.s_ss.delegations.iter().any(|d| d.pk_d.pkp == pk_z.pkp) {
+            return Err("The sanitizer is neither in san_pks nor a delegate".to_string());
+        }
+        if !Self::verify_delegations(pp, pk_s, san_pks, sig, n) {
+            return Err("The signature has an invalid delegation".to_string());
+        }
+        if !sig.s_ss.windows.is_empty() {
+            let now = match now {
+                Some(now) => now,
+                None => return Err("The signature has validity windows, the sanitization needs a time".to_string()),
+            };
+            if !ip.is_some_and(|i| in_window(&sig.s_ss.windows[i], now)) {
+                return Err("The rights of the sanitizer are not valid at this time".to_string());
             }
         }
-        let r = Fr::rand(&mut pp.bg.rng).to_string().parse::<BigInt>().unwrap();
-        let s = Fr::rand(&mut pp.bg.rng).to_string().parse::<BigInt>().unwrap();
-        let (s_x_eqs, pk1_bls) = EQS::chg_rep(
-            &mut pp.bg, &pk_s.pk_eqs, &sig.s_ss.pk1_bls.iter().map(|x| x.into_affine()).collect(), 
+        let mut tags = sig.s_ss.tags.clone();
+        if let Some(lim) = &sig.s_ss.limit {
+            //The count is only taken from the log of sig: sanitizing another version of the document that does not
+            //hold this log reuses a count, and two signatures on the same count and different messages expose sk_z.
+            tags.push(Self::next_tag(pp, sk_z, lim, &tags)?);
+        }
+        let mut rng = thread_rng();
+        let r = Fr::rand(&mut rng).to_string().parse::<BigInt>().unwrap();
+        let s = Fr::rand(&mut rng).to_string().parse::<BigInt>().unwrap();
+        let (s_x_eqs, pk1_bls) = E::chg_rep(
+            &pp.bg, &pp.eqs, &pk_s.pk_eqs, &sig.s_ss.pk1_bls.iter().map(|x| x.into_affine()).collect::<Vec<_>>(), 
             &sig.s_ss.s_x_eqs, &r
         );
         let pk1_bls_p = pk1_bls.iter().map(|x| x.into_group()).collect();
-        let (s_y_eqs, pk2_bls) = EQS::chg_rep(
-                    &mut pp.bg, &pk_s.pk_eqs, &sig.s_ss.pk2_bls.iter().map(|y| y.into_affine()).collect(), 
+        let (s_y_eqs, pk2_bls) = E::chg_rep(
+                    &pp.bg, &pp.eqs, &pk_s.pk_eqs, &sig.s_ss.pk2_bls.iter().map(|y| y.into_affine()).collect::<Vec<_>>(), 
                     &sig.s_ss.s_y_eqs, &(r * s.clone())
                 );
         let pk2_bls_p = pk2_bls.iter().map(|y| y.into_group()).collect();
         let mut s_bls_v: Vec<G2Projective> = Vec::with_capacity(n);
-        let mut secrets: Vec<Vec<RawCiphertext<'d>>> = vec![Vec::with_capacity(n); k];
-        for j in 0..n {
-            if mp[j] != m_[j] {
-                let y = PKE::decrypt(&sk_z.ske, &sig.s_ss.secrets[ip][j]);
+        let mut secrets: Vec<Vec<Ciphertext>> = vec![Vec::with_capacity(n); k];
+        let bg = &pp.bg;
+        let blocks: Vec<Result<(G2Projective, Vec<Ciphertext>), String>> = map_range(n, |j| {
+            let mj = Self::input(sig, j, &m_[j]);
+            let unfilled = Self::unfilled(sig, j) && !filled.contains(&j);
+            let mpj = block_input(j, &mp[j], redacted.contains(&j), unfilled);
+            let threshold = j < n - 1 && sig.s_ss.thresholds[j].is_some();
+            let s_bls = if mpj != mj && threshold {
+                let s0 = match adapted.iter().find(|(b, _)| *b == j) {
+                    Some((_, s0)) => s0,
+                    None => return Err("A threshold block needs the partial adaptations of its group".to_string()),
+                };
+                if !BLS::verify(bg, &sig.s_ss.pk1_bls[j], &sig.s_ss.pk2_bls[j], &mpj, s0) {
+                    return Err("The partial adaptations do not give a signature on the modification".to_string());
+                }
+                BLS::randomize_g2(s0, &s)
+            } else if mpj != mj {
+                let y = Self::secret(sk_z, pk_z, ip, sig, j);
                 if y == zero {
                     return Err("The modification is not admissible for the chosen sanitizer".to_string());
                 }
-                let mpj = j.to_string() + mp[j].as_str();
-                s_bls_v.push(BLS::sign(&mut pp.bg, &Fr::from(BigUint::from_bytes_be(&(y * s.clone()).to_bytes_be().1)), &mpj));
+                BLS::sign(bg, &Fr::from(BigUint::from_bytes_be(&(y * s.clone()).to_bytes_be().1)), &mpj)
             } else {
-                s_bls_v.push(BLS::randomize_g2(&sig.s_ss.s_bls[j], &s));
-            }
-            for i in 0..k {
-                secrets[i].push(PKE::multiply(&san_pks[i].pke, &sig.s_ss.secrets[i][j], &s));
+                BLS::randomize_g2(&sig.s_ss.s_bls[j], &s)
+            };
+            let cs = map_range(k, |i| PKE::multiply(&san_pks[i].pke, &sig.s_ss.secrets[i][j], &s));
+            Ok((s_bls, cs))
+        });
+        for b in blocks {
+            let (s_bls, cs) = b?;
+            s_bls_v.push(s_bls);
+            for (i, c) in cs.into_iter().enumerate() {
+                secrets[i].push(c);
             }
         }
+        let thresholds: Vec<Option<Threshold>> = sig.s_ss.thresholds.iter().map(|th| th.as_ref().map(|th| Threshold {
+            group: th.group.clone(),
+            shares: th.group.members.iter().zip(th.shares.iter())
+                .map(|(i, c)| PKE::multiply(&san_pks[*i].pke, c, &s))
+                .collect()
+        })).collect();
+        let delegations: Vec<Delegation> = sig.s_ss.delegations.iter().map(|d| Delegation {
+            pk_d: d.pk_d.clone(),
+            blocks: d.blocks.clone(),
+            secrets: d.secrets.iter().map(|c| PKE::multiply(&d.pk_d.pke, c, &s)).collect(),
+            cert: d.cert.clone()
+        }).collect();
         let s_ss = SignatureSS {
-            s_x_eqs: s_x_eqs,
-            s_y_eqs: s_y_eqs,
+            s_x_eqs,
+            s_y_eqs,
             s_bls: s_bls_v,
             pk1_bls: pk1_bls_p,
             pk2_bls: pk2_bls_p,
-            secrets: secrets
+            secrets,
+            covered: delegations.len(),
+            delegations,
+            value_sets,
+            policy: sig.s_ss.policy.clone(),
+            commitments,
+            redacted,
+            slots: sig.s_ss.slots.clone(),
+            filled,
+            limit: sig.s_ss.limit.clone(),
+            tags,
+            windows: sig.s_ss.windows.clone(),
+            time: now,
+            thresholds
         };
         let t = Self::generate_t(&pk_s, &mp, &s_ss);
-        let mut ring: Vec<BigInt> = san_pks.iter().map(|x| x.pkp.clone()).collect();
-        ring.push(pk_s.pkp.clone());
-        let s_vrs = VRS::sign(&pp.p, &pp.q, &pp.g, &sk_z.skp, &ring, &t);
+        let ring = Self::ring(san_pks, &s_ss, pk_s);
+        let s_vrs = match Self::vrs_tag(&s_ss) {
+            Some(tag) => VRS::sign_linkable(&pp.q, &pp.g, &sk_z.skp, &ring, &VRS::link_base(&pp.p, &pp.q, &tag), &t,
+                pp.fb_vrs.as_ref())?,
+            None => VRS::sign(&pp.p, &pp.q, &pp.g, &sk_z.skp, &ring, &t, pp.fb_vrs.as_ref()),
+        };
         let sig = Signature {
             s_ss: s_ss,
             s_vrs: s_vrs
@@ -200,68 +934,243 @@ impl KSan {
         return Ok(sig);
     }
 
-    pub fn verify(
-        pp: &mut PublicParams, pk_s: &SignerPublicKey,
-        san_pks: &Vec<SanitizerPublicKey>, m: &Vec<String>, sig: &Signature
+    //Hands the rights of the sanitizer pk_z (a sanitizer of san_pks or a delegate) over the given blocks to pk_d,
+    //who is not in san_pks: the BLS secret keys of the blocks are encrypted under pk_d and the delegation is
+    //certified with a VRS ring signature over san_pks and the previous delegates, bound to the VRS ring signature
+    //of sig. The signature is not sanitized, so no limit tag is used. The next sanitization covers the delegation
+    //with its VRS ring signature, whose ring is san_pks, the delegates and the signer, so verify rejects a delegate
+    //with the key of the signer.
+    pub fn delegate<E: EQSScheme>(
+        pp: &PublicParams<E>, sk_z: &SanitizerSecretKey, pk_z: &SanitizerPublicKey, san_pks: &[SanitizerPublicKey],
+        pk_d: &SanitizerPublicKey, blocks: &[usize], sig: &Signature<E>
+    ) -> Result<Signature<E>, String> {
+        let k = san_pks.len();
+        let n = sig.s_ss.s_bls.len();
+        if n < 2 || sig.s_ss.secrets.len() != k || sig.s_ss.secrets.iter().any(|c| c.len() != n) {
+            return Err("The signature is malformed".to_string());
+        }
+        if blocks.is_empty() || blocks.iter().any(|j| *j >= n - 1) {
+            return Err("The delegated blocks must be blocks of the message".to_string());
+        }
+        if san_pks.iter().any(|x| x.pkp == pk_d.pkp) {
+            return Err("The delegate is already in the ring".to_string());
+        }
+        if !sig.s_ss.windows.is_empty() {
+            return Err("Signatures with validity windows are not supported".to_string());
+        }
+        let ip = san_pks.iter().position(|x| x.pkp == pk_z.pkp);
+        if ip.is_none() && !sig.s_ss.delegations.iter().any(|d| d.pk_d.pkp == pk_z.pkp) {
+            return Err("The sanitizer is neither in san_pks nor a delegate".to_string());
+        }
+        let zero = BigInt::from(0);
+        let ys: Vec<BigInt> = map_range(blocks.len(), |x| Self::secret(sk_z, pk_z, ip, sig, blocks[x]));
+        if ys.contains(&zero) {
+            return Err("The delegated blocks are not admissible for the chosen sanitizer".to_string());
+        }
+        //Reduced mod the group order so that the plaintexts stay small under the next randomizations.
+        let ys: Vec<BigInt> = ys.iter()
+            .map(|y| Fr::from(BigUint::from_bytes_be(&y.to_bytes_be().1)).to_string().parse::<BigInt>().unwrap())
+            .collect();
+        let secrets: Vec<Ciphertext> = map_range(blocks.len(), |x| PKE::encrypt(&pk_d.pke, &ys[x]));
+        let mut ring: Vec<BigInt> = san_pks.iter().map(|x| x.pkp.clone()).collect();
+        ring.extend(sig.s_ss.delegations.iter().map(|d| d.pk_d.pkp.clone()));
+        let t = Self::delegation_message(sig, sig.s_ss.delegations.len(), pk_d, blocks, &secrets);
+        let cert = VRS::sign(&pp.p, &pp.q, &pp.g, &sk_z.skp, &ring, &t, pp.fb_vrs.as_ref());
+        let mut delegations = sig.s_ss.delegations.clone();
+        delegations.push(Delegation {
+            pk_d: pk_d.clone(),
+            blocks: blocks.to_owned(),
+            secrets,
+            cert
+        });
+        let sigp = Signature {
+            s_ss: SignatureSS {
+                s_x_eqs: sig.s_ss.s_x_eqs.clone(),
+                s_y_eqs: sig.s_ss.s_y_eqs.clone(),
+                s_bls: sig.s_ss.s_bls.clone(),
+                pk1_bls: sig.s_ss.pk1_bls.clone(),
+                pk2_bls: sig.s_ss.pk2_bls.clone(),
+                secrets: sig.s_ss.secrets.clone(),
+                delegations,
+                covered: sig.s_ss.covered,
+                value_sets: sig.s_ss.value_sets.clone(),
+                policy: sig.s_ss.policy.clone(),
+                commitments: sig.s_ss.commitments.clone(),
+                redacted: sig.s_ss.redacted.clone(),
+                slots: sig.s_ss.slots.clone(),
+                filled: sig.s_ss.filled.clone(),
+                limit: sig.s_ss.limit.clone(),
+                tags: sig.s_ss.tags.clone(),
+                windows: sig.s_ss.windows.clone(),
+                time: sig.s_ss.time,
+                thresholds: sig.s_ss.thresholds.clone()
+            },
+            s_vrs: sig.s_vrs.clone()
+        };
+        Ok(sigp)
+    }
+
+    pub fn verify<E: EQSScheme>(
+        pp: &PublicParams<E>, pk_s: &SignerPublicKey<E>,
+        san_pks: &[SanitizerPublicKey], m: &[String], sig: &Signature<E>
     ) -> Result<bool, String> {
-        let n = pp.bg.n as usize;
-        if m.len() != n - 1 {
+        let m_ = match Self::full_message(san_pks, m, sig) {
+            Some(m_) => m_,
+            None => return Ok(false),
+        };
+        let n = match Self::vector_len(pp, m_.len() - 1) {
+            Ok(n) => n,
+            Err(_) => return Ok(false),
+        };
+        if !Self::keys_well_formed(sig, n) {
+            return Ok(false);
+        }
+        if !Self::verify_delegations(pp, pk_s, san_pks, sig, n) || !Self::values_valid(sig, &m_) || 
+            !Self::commitments_valid(sig, &m_) {
             return Ok(false);
         }
-        let mut m_ = m.clone();
-        m_.push(Self::pkz_to_string(&san_pks));
         let t = Self::generate_t(&pk_s, &m_, &sig.s_ss);
-        let mut ring: Vec<BigInt> = san_pks.iter().map(|x| x.pkp.clone()).collect();
-        ring.push(pk_s.pkp.clone());
-        if !VRS::verify(&pp.p, &pp.q, &pp.g, &ring, &t, &sig.s_vrs) {
+        let ring = Self::ring(san_pks, &sig.s_ss, pk_s);
+        if !Self::verify_vrs(pp, sig, &ring, &t) {
             return Ok(false);
         }
-        if !EQS::verify(
-            &mut pp.bg, &pk_s.pk_eqs, &sig.s_ss.pk1_bls.iter().map(|x| x.into_affine()).collect(), 
+        if !E::verify(
+            &pp.bg, &pp.eqs, &pk_s.pk_eqs, &sig.s_ss.pk1_bls.iter().map(|x| x.into_affine()).collect::<Vec<_>>(), 
             &sig.s_ss.s_x_eqs
         ) {
             return Ok(false);
         }
-        if !EQS::verify(
-            &mut pp.bg, &pk_s.pk_eqs, &sig.s_ss.pk2_bls.iter().map(|y| y.into_affine()).collect(), 
+        if !E::verify(
+            &pp.bg, &pp.eqs, &pk_s.pk_eqs, &sig.s_ss.pk2_bls.iter().map(|y| y.into_affine()).collect::<Vec<_>>(), 
             &sig.s_ss.s_y_eqs
         ) {
             return Ok(false);
         }
-        for j in 0..n {
-            let mj = j.to_string() + m_[j].as_str();
-            if !BLS::verify(&mut pp.bg, &sig.s_ss.pk1_bls[j], &sig.s_ss.pk2_bls[j], &mj, &sig.s_ss.s_bls[j]) {
-                return Ok(false);
+        let bg = &pp.bg;
+        let b = all_range(n, |j| {
+            let mj = Self::input(sig, j, &m_[j]);
+            BLS::verify(bg, &sig.s_ss.pk1_bls[j], &sig.s_ss.pk2_bls[j], &mj, &sig.s_ss.s_bls[j])
+        });
+        Ok(b)
+    }
+
+    //verify, and checks that sig is not dated after the time now of the caller's clock.
+    pub fn verify_at<E: EQSScheme>(
+        pp: &PublicParams<E>, pk_s: &SignerPublicKey<E>,
+        san_pks: &[SanitizerPublicKey], m: &[String], sig: &Signature<E>, now: u64
+    ) -> Result<bool, String> {
+        if sig.s_ss.time.is_some_and(|t| t > now) {
+            return Ok(false);
+        }
+        Self::verify(pp, pk_s, san_pks, m, sig)
+    }
+
+    //Verifies many signatures of the same signer. The VRS signatures are checked one by one, the EQS signatures
+    //of all the signatures are checked with one multi-pairing and the BLS signatures of all the blocks of all the
+    //signatures with another one. If an aggregated check fails, each signature is verified on its own to find
+    //the invalid ones.
+    //Returns whether all the signatures are valid and the indexes of the invalid ones.
+    pub fn verify_batch<E: EQSScheme>(
+        pp: &PublicParams<E>, pk_s: &SignerPublicKey<E>,
+        san_pks: &[Vec<SanitizerPublicKey>], m: &[Vec<String>], sigs: &[Signature<E>]
+    ) -> Result<(bool, Vec<usize>), String> {
+        if san_pks.len() != sigs.len() || m.len() != sigs.len() {
+            return Err("san_pks, m and sigs must have the same length".to_string());
+        }
+        let mut invalid: Vec<usize> = Vec::new();
+        let mut candidates: Vec<usize> = Vec::with_capacity(sigs.len());
+        let mut eqs_ms: Vec<Vec<G1Affine>> = Vec::with_capacity(2 * sigs.len());
+        let mut eqs_ss: Vec<E::Signature> = Vec::with_capacity(2 * sigs.len());
+        let mut pk1s: Vec<G1Projective> = Vec::new();
+        let mut pk2s: Vec<G1Projective> = Vec::new();
+        let mut mjs: Vec<String> = Vec::new();
+        let mut s_bls: Vec<G2Projective> = Vec::new();
+        for x in 0..sigs.len() {
+            let sig = &sigs[x];
+            let m_ = match Self::full_message(&san_pks[x], &m[x], sig) {
+                Some(m_) => m_,
+                None => {
+                    invalid.push(x);
+                    continue;
+                }
+            };
+            let n = match Self::vector_len(pp, m_.len() - 1) {
+                Ok(n) => n,
+                Err(_) => {
+                    invalid.push(x);
+                    continue;
+                }
+            };
+            if !Self::keys_well_formed(sig, n) {
+                invalid.push(x);
+                continue;
+            }
+            if !Self::verify_delegations(pp, pk_s, &san_pks[x], sig, n) || !Self::values_valid(sig, &m_) || 
+                !Self::commitments_valid(sig, &m_) {
+                invalid.push(x);
+                continue;
+            }
+            let t = Self::generate_t(pk_s, &m_, &sig.s_ss);
+            let ring = Self::ring(&san_pks[x], &sig.s_ss, pk_s);
+            if !Self::verify_vrs(pp, sig, &ring, &t) {
+                invalid.push(x);
+                continue;
+            }
+            candidates.push(x);
+            eqs_ms.push(sig.s_ss.pk1_bls.iter().map(|y| y.into_affine()).collect());
+            eqs_ss.push(sig.s_ss.s_x_eqs.clone());
+            eqs_ms.push(sig.s_ss.pk2_bls.iter().map(|y| y.into_affine()).collect());
+            eqs_ss.push(sig.s_ss.s_y_eqs.clone());
+            for (j, mj) in m_.iter().take(n).enumerate() {
+                pk1s.push(sig.s_ss.pk1_bls[j]);
+                pk2s.push(sig.s_ss.pk2_bls[j]);
+                mjs.push(Self::input(sig, j, mj));
+                s_bls.push(sig.s_ss.s_bls[j]);
+            }
+        }
+        if !E::verify_batch(&pp.bg, &pp.eqs, &pk_s.pk_eqs, &eqs_ms, &eqs_ss) || 
+            !BLS::verify_batch(&pp.bg, &pk1s, &pk2s, &mjs, &s_bls) {
+            for x in candidates {
+                if !Self::verify(pp, pk_s, &san_pks[x], &m[x], &sigs[x])? {
+                    invalid.push(x);
+                }
             }
+            invalid.sort();
         }
-        return Ok(true);
+        Ok((invalid.is_empty(), invalid))
     }
 
-    pub fn prove(
-        pp: &mut PublicParams, sk_s: &SignerSecretKey, pk_s: &SignerPublicKey,
-        san_pks: &Vec<SanitizerPublicKey>, m: &Vec<String>, sig: &Signature, _j: Option<&usize>
+    pub fn prove<E: EQSScheme>(
+        pp: &PublicParams<E>, sk_s: &SignerSecretKey<E>, pk_s: &SignerPublicKey<E>,
+        san_pks: &[SanitizerPublicKey], m: &[String], sig: &Signature<E>, _j: Option<&usize>
     ) -> Result<Proof, String> {
-        let mut m_ = m.clone();
-        m_.push(Self::pkz_to_string(&san_pks));
+        let m_ = match Self::full_message(san_pks, m, sig) {
+            Some(m_) => m_,
+            None => return Err("The signature does not match the length of m".to_string()),
+        };
         let t = Self::generate_t(&pk_s, &m_, &sig.s_ss);
-        let mut ring: Vec<BigInt> = san_pks.iter().map(|x| x.pkp.clone()).collect();
-        ring.push(pk_s.pkp.clone());
-        let pr = VRS::prove(&pp.p, &pp.q, &pp.g, &ring, &t, &sig.s_vrs, &pk_s.pkp, &sk_s.skp);
+        let pr = match Self::vrs_tag(&sig.s_ss) {
+            Some(tag) => VRS::prove_linkable(&pp.p, &pp.q, &pp.g, &tag, &pk_s.pkp, &sk_s.skp),
+            None => VRS::prove(&pp.p, &pp.q, &pp.g, &t, &sig.s_vrs, &pk_s.pkp, &sk_s.skp),
+        };
         return Ok(Proof {
             pr: pr
         });
     }
 
-    pub fn judge(
-        pp: &mut PublicParams, pk_s: &SignerPublicKey, san_pks: &Vec<SanitizerPublicKey>,
-        m: &Vec<String>, sig: &Signature, p: &Proof, _j: Option<&usize>
+    pub fn judge<E: EQSScheme>(
+        pp: &PublicParams<E>, pk_s: &SignerPublicKey<E>, san_pks: &[SanitizerPublicKey],
+        m: &[String], sig: &Signature<E>, p: &Proof, _j: Option<&usize>
     ) -> Result<char, String> {
-        let mut m_ = m.clone();
-        m_.push(Self::pkz_to_string(&san_pks));
+        let m_ = match Self::full_message(san_pks, m, sig) {
+            Some(m_) => m_,
+            None => return Err("The signature does not match the length of m".to_string()),
+        };
         let t = Self::generate_t(&pk_s, &m_, &sig.s_ss);
-        let mut ring: Vec<BigInt> = san_pks.iter().map(|x| x.pkp.clone()).collect();
-        ring.push(pk_s.pkp.clone());
-        let b = VRS::judge(&pp.p, &pp.q, &pp.g, &ring, &t, &sig.s_vrs, &pk_s.pkp, &p.pr);
+        let b = match Self::vrs_tag(&sig.s_ss) {
+            Some(tag) => VRS::judge_linkable(&pp.p, &pp.q, &pp.g, &tag, &sig.s_vrs, &pk_s.pkp, &p.pr),
+            None => VRS::judge(&pp.p, &pp.q, &pp.g, &t, &sig.s_vrs, &pk_s.pkp, &p.pr),
+        };
         if b.is_err() {
             return Ok('E');
         }
@@ -272,35 +1181,542 @@ impl KSan {
         return Ok('Z');
     }
 
-    fn generate_t(pk_s: &SignerPublicKey, m: &Vec<String>, s_ss: &SignatureSS) -> String {
+    //Identifies a sanitizer that went over the limit: sig_a and sig_b are versions of the same limited signature
+    //whose VRS ring signatures use the same count of the same sanitizer on different messages, which exposes its
+    //VRS key. None if the signatures do not reuse a count.
+    pub fn judge_overuse<E: EQSScheme>(
+        pp: &PublicParams<E>, pk_s: &SignerPublicKey<E>, san_pks: &[SanitizerPublicKey], sig_a: &Signature<E>,
+        sig_b: &Signature<E>
+    ) -> Result<Option<SanitizerPublicKey>, String> {
+        let (la, lb) = match (&sig_a.s_ss.limit, &sig_b.s_ss.limit) {
+            (Some(la), Some(lb)) => (la, lb),
+            _ => return Err("The signatures have no limit".to_string()),
+        };
+        if la.id != lb.id {
+            return Err("The signatures are not versions of the same signature".to_string());
+        }
+        if sig_a.s_vrs.z != sig_b.s_vrs.z {
+            return Ok(None);
+        }
+        let mut pks = Self::active(san_pks, &sig_a.s_ss);
+        pks.extend(sig_a.s_ss.delegations[..sig_a.s_ss.covered].iter().map(|d| d.pk_d.clone()));
+        let ring = Self::ring(san_pks, &sig_a.s_ss, pk_s);
+        Ok(match VRS::extract(&pp.q, &pp.g, &ring, &sig_a.s_vrs, &sig_b.s_vrs) {
+            Some(i) if i < pks.len() => Some(pks[i].clone()),
+            _ => None,
+        })
+    }
+
+    //Encoded size of a signature: EQS signatures, BLS signatures and public keys, ciphertexts of the BLS secret
+    //keys and VRS ring signature.
+    pub fn signature_size<E: EQSScheme>(sig: &Signature<E>) -> SizeReport {
+        let mut r = SizeReport::new();
+        r.add("eqs", E::sig_size(&sig.s_ss.s_x_eqs) + E::sig_size(&sig.s_ss.s_y_eqs));
+        for s in sig.s_ss.s_bls.iter() {
+            r.add("bls_signatures", ark_size(&s.into_affine()));
+        }
+        for pk in sig.s_ss.pk1_bls.iter().chain(sig.s_ss.pk2_bls.iter()) {
+            r.add("bls_public_keys", ark_size(&pk.into_affine()));
+        }
+        for c in sig.s_ss.secrets.iter().flatten() {
+            r.add("ciphertexts", ciphertext_size(c));
+        }
+        r.add("redaction", (2 * sig.s_ss.policy.len()).div_ceil(8) + 8 * sig.s_ss.redacted.len());
+        r.add("slots", 8 * (sig.s_ss.slots.len() + sig.s_ss.filled.len()));
+        if let Some(lim) = &sig.s_ss.limit {
+            r.add("limit", 8 + lim.id.len() + bigint_size(&lim.z));
+        }
+        for x in sig.s_ss.tags.iter() {
+            r.add("limit", 8 + bigint_size(&x.z));
+        }
+        r.add("windows", 16 * sig.s_ss.windows.iter().flatten().count() + sig.s_ss.windows.len().div_ceil(8));
+        r.add("windows", sig.s_ss.time.map_or(0, |_| 8));
+        r.add("thresholds", sig.s_ss.thresholds.len().div_ceil(8));
+        for th in sig.s_ss.thresholds.iter().flatten() {
+            r.add("thresholds", 8 + 8 * th.group.members.len() + 
+                th.shares.iter().map(ciphertext_size).sum::<usize>());
+        }
+        for c in sig.s_ss.commitments.iter().flatten() {
+            r.add("commitments", c.c.len() + c.salt.as_ref().map(|x| x.len()).unwrap_or(0));
+        }
+        for vs in sig.s_ss.value_sets.iter().flatten() {
+            r.add("value_sets", vs.root.len() + 8 + vs.membership.path.iter().map(|x| x.len()).sum::<usize>());
+        }
+        for d in sig.s_ss.delegations.iter() {
+            r.add("delegations", Self::sanitizer_pk_size(&d.pk_d).total() + 8 * d.blocks.len() + 
+                d.secrets.iter().map(ciphertext_size).sum::<usize>() + vrs_signature_size(&d.cert));
+        }
+        r.add("vrs_ring_signature", vrs_signature_size(&sig.s_vrs));
+        r
+    }
+
+    pub fn proof_size(p: &Proof) -> SizeReport {
+        let mut r = SizeReport::new();
+        r.add("vrs_proof", vrs_proof_size(&p.pr));
+        r
+    }
+
+    pub fn signer_pk_size<E: EQSScheme>(pk_s: &SignerPublicKey<E>) -> SizeReport {
+        let mut r = SizeReport::new();
+        r.add("eqs", E::pk_size(&pk_s.pk_eqs));
+        r.add("vrs", bigint_size(&pk_s.pkp));
+        r.add("schnorr", pk_s.pk.len());
+        r
+    }
+
+    pub fn sanitizer_pk_size(pk_z: &SanitizerPublicKey) -> SizeReport {
+        let mut r = SizeReport::new();
+        r.add("paillier", pk_z.pke.n.to_bytes().len());
+        r.add("vrs", bigint_size(&pk_z.pkp));
+        r
+    }
+
+    fn sign_ss<E: EQSScheme>(
+        pp: &PublicParams<E>, sk_s: &SignerSecretKey<E>, pk_s: &SignerPublicKey<E>, san_pks: &[SanitizerPublicKey],
+        m_: &[String], s_ss: SignatureSS<E>
+    ) -> Signature<E> {
+        let t = Self::generate_t(pk_s, m_, &s_ss);
+        let ring = Self::ring(san_pks, &s_ss, pk_s);
+        let s_vrs = match Self::vrs_tag(&s_ss) {
+            //The signer is always the last member of the ring.
+            Some(tag) => VRS::sign_linkable(&pp.q, &pp.g, &sk_s.skp, &ring, &VRS::link_base(&pp.p, &pp.q, &tag), &t,
+                pp.fb_vrs.as_ref()).unwrap(),
+            None => VRS::sign(&pp.p, &pp.q, &pp.g, &sk_s.skp, &ring, &t, pp.fb_vrs.as_ref()),
+        };
+        Signature {
+            s_ss,
+            s_vrs
+        }
+    }
+
+    fn generate_t<E: EQSScheme>(pk_s: &SignerPublicKey<E>, m: &[String], s_ss: &SignatureSS<E>) -> String {
         let mut t = String::new();
         t.push_str(pk_s.pkp.to_str_radix(36).as_str());
         for j in 0..m.len() {
             t.push_str(m[j].as_str());
         }
         let mut bytes = Vec::new();
-        for p in pk_s.pk_eqs.0.iter() {
-            p.serialize_compressed(&mut bytes).unwrap();
-        }
-        s_ss.s_x_eqs.Z.serialize_compressed(&mut bytes).unwrap();
-        s_ss.s_x_eqs.Y.serialize_compressed(&mut bytes).unwrap();
-        s_ss.s_x_eqs.Y_tilde.serialize_compressed(&mut bytes).unwrap();
-        s_ss.s_y_eqs.Z.serialize_compressed(&mut bytes).unwrap();
-        s_ss.s_y_eqs.Y.serialize_compressed(&mut bytes).unwrap();
-        s_ss.s_y_eqs.Y_tilde.serialize_compressed(&mut bytes).unwrap();
+        E::pk_bytes(&pk_s.pk_eqs, &mut bytes);
+        E::sig_bytes(&s_ss.s_x_eqs, &mut bytes);
+        E::sig_bytes(&s_ss.s_y_eqs, &mut bytes);
         for j in 0..s_ss.pk1_bls.len() {
             s_ss.pk1_bls[j].serialize_compressed(&mut bytes).unwrap();
             s_ss.pk2_bls[j].serialize_compressed(&mut bytes).unwrap();
             s_ss.s_bls[j].serialize_compressed(&mut bytes).unwrap();
             for i in 0..s_ss.secrets.len() {
-                t.push_str(s_ss.secrets[i][j].0.as_ref().to_str_radix(36).as_str());
+                t.push_str(s_ss.secrets[i][j].to_str_radix(36).as_str());
+            }
+        }
+        for j in s_ss.redacted.iter() {
+            t.push_str(":r");
+            t.push_str(j.to_string().as_str());
+        }
+        for j in s_ss.filled.iter() {
+            t.push_str(":f");
+            t.push_str(j.to_string().as_str());
+        }
+        if let Some(time) = s_ss.time {
+            t.push_str(":t");
+            t.push_str(time.to_string().as_str());
+        }
+        for x in s_ss.tags.iter() {
+            t.push_str(":c");
+            t.push_str(x.c.to_string().as_str());
+            t.push(',');
+            t.push_str(x.z.to_str_radix(36).as_str());
+        }
+        for (j, th) in s_ss.thresholds.iter().enumerate() {
+            if let Some(th) = th {
+                t.push_str(":g");
+                t.push_str(j.to_string().as_str());
+                for c in th.shares.iter() {
+                    t.push(',');
+                    t.push_str(c.to_str_radix(36).as_str());
+                }
+            }
+        }
+        for d in s_ss.delegations[..s_ss.covered].iter() {
+            t.push(':');
+            t.push_str(d.pk_d.pkp.to_str_radix(36).as_str());
+            t.push_str(d.pk_d.pke.n.to_str_radix(36).as_str());
+            for (j, c) in d.blocks.iter().zip(d.secrets.iter()) {
+                t.push(',');
+                t.push_str(j.to_string().as_str());
+                t.push_str(c.to_str_radix(36).as_str());
             }
         }
         t.push_str(encode(bytes.as_slice()).as_str());
         return t;
     }
 
-    fn pkz_to_string(san_pks: &Vec<SanitizerPublicKey>) -> String {
+    //VRS ring of a signature: the sanitizers whose rights are valid at the time of the signature, the delegates
+    //and the signer.
+    fn ring<E: EQSScheme>(
+        san_pks: &[SanitizerPublicKey], s_ss: &SignatureSS<E>, pk_s: &SignerPublicKey<E>
+    ) -> Vec<BigInt> {
+        let mut ring: Vec<BigInt> = Self::active(san_pks, s_ss).iter().map(|x| x.pkp.clone()).collect();
+        ring.extend(s_ss.delegations[..s_ss.covered].iter().map(|d| d.pk_d.pkp.clone()));
+        ring.push(pk_s.pkp.clone());
+        ring
+    }
+
+    //Sanitizers whose rights are valid at the time of the signature: all of them without windows, none without a
+    //time.
+    fn active<E: EQSScheme>(san_pks: &[SanitizerPublicKey], s_ss: &SignatureSS<E>) -> Vec<SanitizerPublicKey> {
+        if s_ss.windows.is_empty() {
+            return san_pks.to_vec();
+        }
+        let t = match s_ss.time {
+            Some(t) => t,
+            None => return Vec::new(),
+        };
+        san_pks.iter().zip(s_ss.windows.iter())
+            .filter(|(_, w)| in_window(w, t))
+            .map(|(x, _)| x.clone())
+            .collect()
+    }
+
+    //Every threshold block has a threshold between 1 and the size of its group, distinct members in san_pks and a
+    //share per member.
+    fn thresholds_well_formed<E: EQSScheme>(s_ss: &SignatureSS<E>, k: usize) -> bool {
+        s_ss.thresholds.iter().flatten().all(|th| {
+            th.group.t >= 1 && th.group.t <= th.group.members.len() &&
+                members_valid(&th.group.members, k) && th.shares.len() == th.group.members.len()
+        })
+    }
+
+    //Order of the BLS groups, the modulus of the key shares.
+    fn order() -> BigInt {
+        BigInt::from_biguint(Sign::Plus, BigUint::from(Fr::MODULUS))
+    }
+
+    //Tag of the linkable VRS ring signature of a limited signature: the count of the last log entry, or 0 for the
+    //signer when the log is empty. None without a limit.
+    fn vrs_tag<E: EQSScheme>(s_ss: &SignatureSS<E>) -> Option<String> {
+        let lim = match &s_ss.limit {
+            Some(lim) => lim,
+            None => return None,
+        };
+        Some(Self::limit_tag(&lim.id, s_ss.tags.last().map(|x| x.c).unwrap_or(0)))
+    }
+
+    fn limit_tag(id: &[u8], c: usize) -> String {
+        "limit:".to_string() + encode(id).as_str() + ":" + c.to_string().as_str()
+    }
+
+    //Limit of a new signature of sk_s, with a fresh identifier.
+    fn new_limit<E: EQSScheme>(pp: &PublicParams<E>, sk_s: &SignerSecretKey<E>, max: usize) -> Limit {
+        let mut rng = thread_rng();
+        let id: [u8; 32] = rng.gen();
+        let z = VRS::link(&pp.p, &pp.q, &sk_s.skp, &Self::limit_tag(id.as_ref(), 0));
+        Limit { max, id: id.to_vec(), z }
+    }
+
+    //Log entry of the next sanitization by sk_z: the smallest count for which the sanitizer has no entry yet.
+    fn next_tag<E: EQSScheme>(
+        pp: &PublicParams<E>, sk_z: &SanitizerSecretKey, lim: &Limit, tags: &[LimitTag]
+    ) -> Result<LimitTag, String> {
+        let next = (1..=lim.max)
+            .map(|c| LimitTag { c, z: VRS::link(&pp.p, &pp.q, &sk_z.skp, &Self::limit_tag(&lim.id, c)) })
+            .find(|x| !tags.contains(x));
+        match next {
+            Some(x) => Ok(x),
+            None => Err("The sanitization limit of the document is reached".to_string()),
+        }
+    }
+
+    //Checks the VRS ring signature of sig on t. With a limit, the counts of the log must be within the limit and
+    //used once by each sanitizer, and the linking value of the signature must be the one of the last log entry,
+    //or the signer's one when the log is empty.
+    fn verify_vrs<E: EQSScheme>(pp: &PublicParams<E>, sig: &Signature<E>, ring: &[BigInt], t: &str) -> bool {
+        let tags = &sig.s_ss.tags;
+        let lim = match &sig.s_ss.limit {
+            Some(lim) => lim,
+            None => {
+                return tags.is_empty() && VRS::verify(&pp.p, &pp.q, &pp.g, ring, t, &sig.s_vrs, pp.fb_vrs.as_ref());
+            },
+        };
+        if tags.iter().enumerate().any(|(e, x)| x.c == 0 || x.c > lim.max || tags[..e].contains(x)) {
+            return false;
+        }
+        if sig.s_vrs.z != tags.last().map(|x| x.z.clone()).unwrap_or(lim.z.clone()) {
+            return false;
+        }
+        let h = VRS::link_base(&pp.p, &pp.q, &Self::vrs_tag(&sig.s_ss).unwrap());
+        VRS::verify_linkable(&pp.q, &pp.g, ring, &h, t, &sig.s_vrs, pp.fb_vrs.as_ref())
+    }
+
+    //BLS secret key of block j (multiplied by the randomizers of the signature) for the sanitizer ip of san_pks,
+    //or for the delegate pk_z when ip is None (the last delegation of block j to pk_z). Zero when the block is not
+    //admissible for the sanitizer.
+    fn secret<E: EQSScheme>(
+        sk_z: &SanitizerSecretKey, pk_z: &SanitizerPublicKey, ip: Option<usize>, sig: &Signature<E>, j: usize
+    ) -> BigInt {
+        if let Some(ip) = ip {
+            return PKE::decrypt(&sk_z.ske, &sig.s_ss.secrets[ip][j]);
+        }
+        match sig.s_ss.delegations.iter().rev().find(|d| d.pk_d.pkp == pk_z.pkp && d.blocks.contains(&j)) {
+            Some(d) => PKE::decrypt(&sk_z.ske, &d.secrets[d.blocks.iter().position(|b| *b == j).unwrap()]),
+            None => BigInt::from(0),
+        }
+    }
+
+    //The EQS signatures only cover the first n elements of the signer's key, so identity BLS keys appended to a
+    //signature would not change them. Rejecting identity keys and signatures binds the number of blocks.
+    fn keys_well_formed<E: EQSScheme>(sig: &Signature<E>, n: usize) -> bool {
+        sig.s_ss.pk1_bls.len() == n && sig.s_ss.pk2_bls.len() == n && sig.s_ss.s_bls.len() == n && 
+            !sig.s_ss.pk1_bls.iter().any(|x| x.is_zero()) && !sig.s_ss.pk2_bls.iter().any(|y| y.is_zero()) && 
+            !sig.s_ss.s_bls.iter().any(|s| s.is_zero())
+    }
+
+    //Message of the certificate of a delegation to pk_d made after the first x delegations of sig. It covers the
+    //VRS ring signature of sig and the certificates of the delegations that it does not cover yet.
+    fn delegation_message<E: EQSScheme>(
+        sig: &Signature<E>, x: usize, pk_d: &SanitizerPublicKey, blocks: &[usize], secrets: &[Ciphertext]
+    ) -> String {
+        let mut t = "delegation".to_string();
+        t.push_str(sig.s_vrs.r.to_str_radix(36).as_str());
+        t.push_str(sig.s_vrs.z.to_str_radix(36).as_str());
+        for d in sig.s_ss.delegations[sig.s_ss.covered..x].iter() {
+            t.push(':');
+            t.push_str(d.cert.r.to_str_radix(36).as_str());
+            t.push_str(d.cert.z.to_str_radix(36).as_str());
+        }
+        t.push(':');
+        t.push_str(pk_d.pkp.to_str_radix(36).as_str());
+        t.push_str(pk_d.pke.n.to_str_radix(36).as_str());
+        for (j, c) in blocks.iter().zip(secrets.iter()) {
+            t.push(':');
+            t.push_str(j.to_string().as_str());
+            t.push_str(c.to_str_radix(36).as_str());
+        }
+        t
+    }
+
+    //Checks the delegations of sig, and the certificates of the ones that are not covered by its VRS ring signature
+    //yet, each one over san_pks and the previous delegates.
+    fn verify_delegations<E: EQSScheme>(
+        pp: &PublicParams<E>, pk_s: &SignerPublicKey<E>, san_pks: &[SanitizerPublicKey], sig: &Signature<E>, n: usize
+    ) -> bool {
+        let delegations = &sig.s_ss.delegations;
+        if sig.s_ss.covered > delegations.len() || !delegations.iter().all(|d| {
+            !d.blocks.is_empty() && d.blocks.iter().all(|j| *j < n - 1) && d.secrets.len() == d.blocks.len() && 
+                d.pk_d.pkp != pk_s.pkp && san_pks.iter().all(|x| x.pkp != d.pk_d.pkp)
+        }) {
+            return false;
+        }
+        let mut ring: Vec<BigInt> = Self::active(san_pks, &sig.s_ss).iter().map(|x| x.pkp.clone()).collect();
+        ring.extend(delegations[..sig.s_ss.covered].iter().map(|d| d.pk_d.pkp.clone()));
+        for (x, d) in delegations.iter().enumerate().skip(sig.s_ss.covered) {
+            let t = Self::delegation_message(sig, x, &d.pk_d, &d.blocks, &d.secrets);
+            if !VRS::verify(&pp.p, &pp.q, &pp.g, &ring, &t, &d.cert, pp.fb_vrs.as_ref()) {
+                return false;
+            }
+            ring.push(d.pk_d.pkp.clone());
+        }
+        true
+    }
+
+    fn revocation_message<E: EQSScheme>(pk_s: &SignerPublicKey<E>, epoch: u64, revoked: &[BigInt]) -> String {
+        let mut t = "revocation".to_string();
+        t.push_str(pk_s.pkp.to_str_radix(36).as_str());
+        t.push(':');
+        t.push_str(epoch.to_string().as_str());
+        for pkp in revoked.iter() {
+            t.push(':');
+            t.push_str(pkp.to_str_radix(36).as_str());
+        }
+        t
+    }
+
+    //Checks that td are the BLS secret keys of sig, a signature on m that has not been sanitized yet.
+    fn check_trapdoors<E: EQSScheme>(
+        pp: &PublicParams<E>, san_pks: &[SanitizerPublicKey], m: &[String], sig: &Signature<E>, td: &Trapdoors
+    ) -> Result<(), String> {
+        let k = san_pks.len();
+        let n = Self::vector_len(pp, m.len())?;
+        if sig.s_ss.pk1_bls.len() != n || sig.s_ss.pk2_bls.len() != n || sig.s_ss.s_bls.len() != n || 
+            sig.s_ss.secrets.len() != k || sig.s_ss.secrets.iter().any(|c| c.len() != n) || 
+            td.sk2_bls.len() != n || td.adm.len() != k || td.adm.iter().any(|a| a.len() != n - 1) {
+            return Err("The trapdoors do not match the signature".to_string());
+        }
+        if !sig.s_ss.delegations.is_empty() {
+            return Err("The signature has delegations".to_string());
+        }
+        if !sig.s_ss.slots.is_empty() {
+            return Err("Signatures with append slots are not supported".to_string());
+        }
+        if sig.s_ss.thresholds.iter().any(|th| th.is_some()) {
+            return Err("Signatures with threshold blocks are not supported".to_string());
+        }
+        let bg = &pp.bg;
+        let b = all_range(n, |j| {
+            if sig.s_ss.pk1_bls[j] * td.sk2_bls[j] != sig.s_ss.pk2_bls[j] {
+                return false;
+            }
+            if j == n - 1 {
+                return true;
+            }
+            let mj = Self::input(sig, j, &m[j]);
+            BLS::verify(bg, &sig.s_ss.pk1_bls[j], &sig.s_ss.pk2_bls[j], &mj, &sig.s_ss.s_bls[j])
+        });
+        if !b {
+            return Err("The trapdoors do not match the signature".to_string());
+        }
+        Ok(())
+    }
+
+    //Length of the EQS vectors for a message of l blocks: one BLS key pair per block and one for the last block.
+    //The signer key supports any length up to bg.n, l must be at least 1 since all the vectors of length 1 are in
+    //the same class.
+    fn vector_len<E: EQSScheme>(pp: &PublicParams<E>, l: usize) -> Result<usize, String> {
+        if l == 0 || l >= pp.bg.n as usize {
+            return Err(format!("m must have between 1 and {} elements", pp.bg.n - 1));
+        }
+        Ok(l + 1)
+    }
+
+    //Last block of the message, never admissible: the number of blocks, the sanitizer public keys and the roots
+    //of the value sets.
+    fn last_block(san_pks: &[SanitizerPublicKey], l: usize, v: &Bound) -> String {
+        let Bound { value_sets, policy, commitments, slots, limit, windows, thresholds } = *v;
+        let mut s = l.to_string() + ":" + Self::pkz_to_string(san_pks).as_str();
+        s.push(':');
+        for p in policy.iter() {
+            s.push_str(if p.modify { "m" } else { "-" });
+            s.push_str(if p.redact { "r" } else { "-" });
+        }
+        for (j, c) in commitments.iter().enumerate() {
+            if let Some(c) = c {
+                s.push(':');
+                s.push_str(j.to_string().as_str());
+                s.push('#');
+                s.push_str(encode(&c.c).as_str());
+            }
+        }
+        for (j, vs) in value_sets.iter().enumerate() {
+            if let Some(vs) = vs {
+                s.push(':');
+                s.push_str(j.to_string().as_str());
+                s.push('=');
+                s.push_str(encode(&vs.root).as_str());
+            }
+        }
+        for i in slots.iter() {
+            s.push_str(":s");
+            s.push_str(i.to_string().as_str());
+        }
+        if let Some(lim) = limit {
+            s.push_str(":l");
+            s.push_str(lim.max.to_string().as_str());
+            s.push(':');
+            s.push_str(encode(&lim.id).as_str());
+            s.push(':');
+            s.push_str(lim.z.to_str_radix(36).as_str());
+        }
+        for w in windows.iter() {
+            s.push_str(":w");
+            if let Some(w) = w {
+                s.push_str(w.from.to_string().as_str());
+                s.push('-');
+                s.push_str(w.until.to_string().as_str());
+            }
+        }
+        for (j, th) in thresholds.iter().enumerate() {
+            if let Some(th) = th {
+                s.push_str(":g");
+                s.push_str(j.to_string().as_str());
+                s.push('/');
+                s.push_str(th.group.t.to_string().as_str());
+                for i in th.group.members.iter() {
+                    s.push(',');
+                    s.push_str(i.to_string().as_str());
+                }
+            }
+        }
+        s
+    }
+
+    fn bound<E: EQSScheme>(s_ss: &SignatureSS<E>) -> Bound<'_> {
+        Bound {
+            value_sets: &s_ss.value_sets,
+            policy: &s_ss.policy,
+            commitments: &s_ss.commitments,
+            slots: &s_ss.slots,
+            limit: s_ss.limit.as_ref(),
+            windows: &s_ss.windows,
+            thresholds: &s_ss.thresholds
+        }
+    }
+
+    //m is the full message, its last block is not checked.
+    fn values_valid<E: EQSScheme>(sig: &Signature<E>, m: &[String]) -> bool {
+        let l = m.len() - 1;
+        sig.s_ss.value_sets.len() == l && (0..l).all(|j| match &sig.s_ss.value_sets[j] {
+            Some(vs) => sig.s_ss.redacted.contains(&j) || Merkle::verify(&vs.root, &m[j], &vs.membership),
+            None => true,
+        })
+    }
+
+    //The committed blocks that are not redacted must open their commitment. m is the full message.
+    fn commitments_valid<E: EQSScheme>(sig: &Signature<E>, m: &[String]) -> bool {
+        (0..m.len() - 1).all(|j| match &sig.s_ss.commitments[j] {
+            Some(c) if !sig.s_ss.redacted.contains(&j) => match &c.salt {
+                Some(salt) => Self::commit(salt, j, &m[j]) == c.c,
+                None => false,
+            },
+            _ => true,
+        })
+    }
+
+    fn commit(salt: &[u8], j: usize, mj: &String) -> Vec<u8> {
+        let mut hasher = Sha256::new();
+        hasher.update(salt);
+        hasher.update(j.to_string().as_bytes());
+        hasher.update(b":");
+        hasher.update(mj.as_bytes());
+        hasher.finalize().to_vec()
+    }
+
+    //Full message of sig from the remaining blocks m: the redacted blocks are empty and the last block is added.
+    //None if the lengths do not match or if the redacted blocks are not sorted or not redactable.
+    fn full_message<E: EQSScheme>(
+        san_pks: &[SanitizerPublicKey], m: &[String], sig: &Signature<E>
+    ) -> Option<Vec<String>> {
+        let s_ss = &sig.s_ss;
+        let l = s_ss.policy.len();
+        if s_ss.slots.len() > l || s_ss.filled.windows(2).any(|w| w[0] >= w[1]) || 
+            s_ss.filled.iter().any(|j| *j >= l || *j + s_ss.slots.len() < l) {
+            return None;
+        }
+        let unfilled = s_ss.slots.len() - s_ss.filled.len();
+        if m.len() + s_ss.redacted.len() + unfilled != l || s_ss.commitments.len() != l || 
+            (!s_ss.windows.is_empty() && s_ss.windows.len() != san_pks.len()) || s_ss.thresholds.len() != l || 
+            !Self::thresholds_well_formed(s_ss, san_pks.len()) || 
+            s_ss.redacted.windows(2).any(|w| w[0] >= w[1]) || s_ss.redacted.iter().any(|j| *j >= l || !s_ss.policy[*j].redact) {
+            return None;
+        }
+        let mut blocks = m.iter();
+        let mut m_: Vec<String> = (0..l).map(|j| {
+            if s_ss.redacted.contains(&j) || Self::unfilled(sig, j) {
+                return String::new();
+            }
+            blocks.next().unwrap().clone()
+        }).collect();
+        m_.push(Self::last_block(san_pks, l, &Self::bound(s_ss)));
+        Some(m_)
+    }
+
+    fn input<E: EQSScheme>(sig: &Signature<E>, j: usize, mj: &str) -> String {
+        block_input(j, mj, sig.s_ss.redacted.contains(&j), Self::unfilled(sig, j))
+    }
+
+    fn unfilled<E: EQSScheme>(sig: &Signature<E>, j: usize) -> bool {
+        let l = sig.s_ss.policy.len();
+        j < l && j + sig.s_ss.slots.len() >= l && !sig.s_ss.filled.contains(&j)
+    }
+
+    fn pkz_to_string(san_pks: &[SanitizerPublicKey]) -> String {
         let mut s: String = String::new();
         for p in san_pks.iter() {
             s.push_str(p.pkp.to_str_radix(36).as_str());
@@ -1,10 +1,13 @@
-use delegatable_credentials::mercurial_sig::{PreparedPublicKey, SecretKey, Signature as EQS_Signature};
-use ark_bls12_381::{Bls12_381, G1Projective, G2Projective};
+use ark_bls12_381::{Fr, G1Projective, G2Projective};
 use num_bigint::BigInt;
-use kzen_paillier::RawCiphertext;
-use crate::blocks::eqs::BG;
+use crate::blocks::pke::Ciphertext;
+use crate::blocks::eqs::{BG, EQS, EQSScheme};
+use crate::blocks::fixed_base::FixedBase;
 use crate::blocks::vrs::{VRSSignature, VRSProof};
+pub use crate::ksan::common::{ValueSet, Policy, Window, Group};
+use crate::ksan::common::default_policy;
 
+//n is the maximum number of message blocks, signatures can have from 1 to n blocks.
 #[derive(Clone, Debug)]
 pub struct SecParams{
     pub bits_vrs: usize,
@@ -13,25 +16,30 @@ pub struct SecParams{
     pub dst: String
 }
 
+//The EQS backend defaults to blocks::eqs::EQS, its own parameters are in eqs.
 #[derive(Clone, Debug)]
-pub struct PublicParams{
+pub struct PublicParams<E: EQSScheme = EQS>{
     pub secp: SecParams,
     pub bg: BG,
+    pub eqs: E::Params,
     pub p: BigInt,
     pub q: BigInt,
-    pub g: BigInt
+    pub g: BigInt,
+    pub fb_vrs: Option<FixedBase>
 }
 
 #[derive(Clone, Debug)]
-pub struct SignerPublicKey{
-    pub pk_eqs: PreparedPublicKey<Bls12_381>,
-    pub pkp: BigInt
+pub struct SignerPublicKey<E: EQSScheme = EQS>{
+    pub pk_eqs: E::PublicKey,
+    pub pkp: BigInt,
+    pub pk: k256::FieldBytes
 }
 
-#[derive(Clone, Debug)]
-pub struct SignerSecretKey{
-    pub sk_eqs: SecretKey<Bls12_381>,
-    pub skp: BigInt
+#[derive(Clone)]
+pub struct SignerSecretKey<E: EQSScheme = EQS>{
+    pub sk_eqs: E::SecretKey,
+    pub skp: BigInt,
+    pub sk: k256::schnorr::SigningKey
 }
 
 #[derive(Clone, Debug)]
@@ -53,22 +61,219 @@ pub struct Mod {
 }
 
 #[derive(Clone, Debug)]
-pub struct SignatureSS<'d>{
-    pub s_x_eqs: EQS_Signature<Bls12_381>,
-    pub s_y_eqs: EQS_Signature<Bls12_381>,
+pub struct SignatureSS<E: EQSScheme = EQS>{
+    pub s_x_eqs: E::Signature,
+    pub s_y_eqs: E::Signature,
     pub s_bls: Vec<G2Projective>,
     pub pk1_bls: Vec<G1Projective>,
     pub pk2_bls: Vec<G1Projective>,
-    pub secrets: Vec<Vec<RawCiphertext<'d>>>
+    pub secrets: Vec<Vec<Ciphertext>>,
+    //Delegations of sanitization rights to keys that are not in san_pks (KSan::delegate), in order. The first
+    //covered ones are covered by the VRS ring signature, the later ones were made after the last sanitization.
+    pub delegations: Vec<Delegation>,
+    pub covered: usize,
+    //Allowed values of each message block (KSan::sign_with_values), None for any value.
+    pub value_sets: Vec<Option<ValueSet>>,
+    //Operations allowed on each message block (KSan::sign_with_policy), commitments of the blocks that may be
+    //redacted but not modified, and the original indexes of the redacted blocks.
+    pub policy: Vec<Policy>,
+    pub commitments: Vec<Option<Commitment>>,
+    pub redacted: Vec<usize>,
+    //Owner (index in san_pks) of each append slot (KSan::sign_with_slots), the slots are the last blocks of the
+    //message before the signer's last block. filled holds the original indexes of the slots that have been filled.
+    pub slots: Vec<usize>,
+    pub filled: Vec<usize>,
+    //Maximum number of sanitizations of the document by each sanitizer (KSan::sign_with_limit), bound into the
+    //last block, and log of the counts used by the sanitizations, in order.
+    pub limit: Option<Limit>,
+    pub tags: Vec<LimitTag>,
+    //Validity window of the rights of each sanitizer (SignOptions::windows), bound into the last block and
+    //empty for no windows, and time of the signature (SanitizeOptions::now).
+    pub windows: Vec<Option<Window>>,
+    pub time: Option<u64>,
+    //Group and encrypted key shares of each threshold block (KSan::sign_with_thresholds), None for the others.
+    pub thresholds: Vec<Option<Threshold>>
 }
 
+//Shamir shares of the BLS secret key of a threshold block (multiplied by the randomizers of the signature, like
+//the secrets), shares[x] is encrypted under the sanitizer group.members[x] and is the value of the sharing
+//polynomial at x + 1. The group is bound into the last block.
 #[derive(Clone, Debug)]
-pub struct Signature<'d>{
-    pub s_ss: SignatureSS<'d>,
+pub struct Threshold{
+    pub group: Group,
+    pub shares: Vec<Ciphertext>
+}
+
+//Partial BLS signatures of the sanitizer member (index in san_pks) on the new values of the given threshold
+//blocks, for a sanitization in which the sanitizers signers cooperate (KSan::sanitize_partial).
+#[derive(Clone, Debug)]
+pub struct PartialAdaptation{
+    pub member: usize,
+    pub signers: Vec<usize>,
+    pub blocks: Vec<usize>,
+    pub s_bls: Vec<G2Projective>
+}
+
+//Limit of a signature: the identifier of the signature in the tags of its linkable VRS signatures and the linking
+//value z of the signer on the count 0.
+#[derive(Clone, Debug)]
+pub struct Limit{
+    pub max: usize,
+    pub id: Vec<u8>,
+    pub z: BigInt
+}
+
+//Count c used by a sanitization, with the linking value z of the sanitizer on c.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LimitTag{
+    pub c: usize,
+    pub z: BigInt
+}
+
+//Salted hash of a block bound into the last block, the salt is removed when the block is redacted.
+#[derive(Clone, Debug)]
+pub struct Commitment{
+    pub c: Vec<u8>,
+    pub salt: Option<Vec<u8>>
+}
+
+//Rights of a sanitizer over some blocks handed to pk_d: the BLS secret keys of the blocks encrypted under pk_d and a
+//certificate of the delegating sanitizer (VRS ring signature over san_pks and the previous delegates).
+#[derive(Clone, Debug)]
+pub struct Delegation{
+    pub pk_d: SanitizerPublicKey,
+    pub blocks: Vec<usize>,
+    pub secrets: Vec<Ciphertext>,
+    pub cert: VRSSignature
+}
+
+#[derive(Clone, Debug)]
+pub struct Signature<E: EQSScheme = EQS>{
+    pub s_ss: SignatureSS<E>,
     pub s_vrs: VRSSignature
 }
 
 #[derive(Clone, Debug)]
 pub struct Proof{
     pub pr: VRSProof
+}
+
+//BLS secret keys of the blocks of a signature (the last one included) and its admissibility matrix, returned by
+//KSan::sign_with_trapdoors. They stay with the signer.
+#[derive(Clone, Debug)]
+pub struct Trapdoors{
+    pub sk2_bls: Vec<Fr>,
+    pub adm: Vec<Vec<bool>>
+}
+
+//Sanitizer added to a signature by KSan::extend_sanitizers: its keys, the blocks granted to it and the trapdoors
+//returned with the signature.
+#[derive(Clone, Debug)]
+pub struct Extension{
+    pub pk_z: SanitizerPublicKey,
+    pub adm_z: Vec<bool>,
+    pub td: Trapdoors
+}
+
+//Sanitizer removed from a signature by KSan::revoke: its keys and the trapdoors returned with the signature.
+#[derive(Clone, Debug)]
+pub struct Revocation{
+    pub pk_z: SanitizerPublicKey,
+    pub td: Trapdoors
+}
+
+//Sanitizers revoked by a signer (VRS public keys) at a given epoch, signed by the signer with a VRS ring
+//signature whose ring is its own key (KSan::revocation_list).
+#[derive(Clone, Debug)]
+pub struct RevocationList{
+    pub epoch: u64,
+    pub revoked: Vec<BigInt>,
+    pub s: k256::schnorr::Signature
+}
+
+//Message independent material of one signature, computed by KSan::sign_offline: the BLS key pairs of the n
+//blocks, the EQS signatures on them and the encryptions of the BLS secret keys and of zero (c[i][j]).
+#[derive(Clone, Debug)]
+pub struct PreSig<E: EQSScheme = EQS>{
+    pub sk2_bls: Vec<Fr>,
+    pub pk1_bls: Vec<G1Projective>,
+    pub pk2_bls: Vec<G1Projective>,
+    pub s_x_eqs: E::Signature,
+    pub s_y_eqs: E::Signature,
+    pub c_trap: Vec<Vec<Ciphertext>>,
+    pub c_zero: Vec<Vec<Ciphertext>>
+}
+
+//Pool of precomputed signatures of one signer for a given set of sanitizers. KSan::sign_online consumes one
+//element per signature.
+#[derive(Clone, Debug)]
+pub struct SignPool<E: EQSScheme = EQS>{
+    pub san_pks: Vec<SanitizerPublicKey>,
+    pub sigs: Vec<PreSig<E>>
+}
+
+//Options of a signature on l blocks (KSan::sign_with_options): the allowed values (None for any value), policy and
+//threshold group of each block, the append slots and validity window of each sanitizer (empty for none), the
+//limit of sanitizations of the document and the time of the signature, needed with windows. SignOptions::new(l)
+//has no option set.
+#[derive(Clone, Debug)]
+pub struct SignOptions{
+    pub values: Vec<Option<Vec<String>>>,
+    pub policy: Vec<Policy>,
+    pub slots: Vec<usize>,
+    pub limit: Option<usize>,
+    pub windows: Vec<Option<Window>>,
+    pub now: Option<u64>,
+    pub groups: Vec<Option<Group>>
+}
+
+impl SignOptions {
+    pub fn new(l: usize) -> Self {
+        SignOptions {
+            values: vec![None; l],
+            policy: default_policy(l),
+            slots: Vec::new(),
+            limit: None,
+            windows: Vec::new(),
+            now: None,
+            groups: vec![None; l]
+        }
+    }
+}
+
+//Inputs of a sanitization of the message m (KSan::sanitize_with_options): the modifications, the blocks to remove,
+//which must be redactable by the policy of the signature, the blocks that fill the next unfilled slots of the
+//sanitizer in order, and the value sets given by the signer for the modified blocks (empty if the blocks have none).
+//m is the current message (without the unfilled slots), the filled slots are at the end of the message. A redacted
+//block gets a BLS signature on a redaction marker and its commitment loses its salt. After a redaction, m is the list
+//of the remaining blocks while the block indexes (Mod::i, redact) keep referring to the original positions.
+//SanitizeOptions::new(m, modif) redacts and appends nothing and has no value set, time or partial adaptation.
+#[derive(Clone, Debug)]
+pub struct SanitizeOptions{
+    pub m: Vec<String>,
+    pub modif: Vec<Mod>,
+    pub redact: Vec<usize>,
+    pub append: Vec<String>,
+    pub values: Vec<Option<Vec<String>>>,
+    //Time of the sanitization on the caller's clock, needed with windows and checked against the validity window
+    //of the sanitizer. This only enforces expiry against an honest clock: the sanitizer picks now and can backdate
+    //it into its window.
+    pub now: Option<u64>,
+    //Partial adaptations of the threshold blocks modified by modif, one by each of the sanitizers that cooperate
+    //(KSan::sanitize_partial), empty if no threshold block is modified.
+    pub partials: Vec<PartialAdaptation>
+}
+
+impl SanitizeOptions {
+    pub fn new(m: &[String], modif: &[Mod]) -> Self {
+        SanitizeOptions {
+            m: m.to_vec(),
+            modif: modif.to_vec(),
+            redact: Vec::new(),
+            append: Vec::new(),
+            values: Vec::new(),
+            now: None,
+            partials: Vec::new()
+        }
+    }
 }
\ No newline at end of file
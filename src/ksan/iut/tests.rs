@@ -2,19 +2,33 @@
 mod tests {
     use crate::blocks::bls::BLS;
     use crate::blocks::eqs::EQS;
+    use crate::blocks::mercurial::Mercurial;
+    use crate::blocks::merkle::Merkle;
     use crate::ksan::iut::ksan::KSan;
-    use crate::ksan::iut::params::{SecParams, Mod, Proof};
+    use crate::ksan::iut::params::{SecParams, Mod, Policy, Proof, Window, Group, SignOptions, SanitizeOptions, Extension,
+        Revocation, PublicParams, SignerSecretKey, SignerPublicKey, SanitizerSecretKey, SanitizerPublicKey};
+    use ark_bls12_381::{G1Projective, G2Projective};
     use ark_ec::CurveGroup;
+    use ark_ff::Zero;
     use num_bigint::BigInt;
 
+    //512-bit public parameters for messages of up to n blocks, a signer key pair and N sanitizer key pairs.
+    fn fixture<const N: usize>(n: u32) -> (PublicParams, SignerSecretKey, SignerPublicKey, [(SanitizerSecretKey, SanitizerPublicKey); N]) {
+        let secp = SecParams { bits_vrs: 512, bits_pke: 520, n, dst: "k-SAN test".to_string() };
+        let pp = KSan::setup(&secp).unwrap();
+        let (sk_s, pk_s) = KSan::kgen_s(&pp).unwrap();
+        let sans = std::array::from_fn(|_| KSan::kgen_z(&pp).unwrap());
+        (pp, sk_s, pk_s, sans)
+    }
+
     #[test]
     fn test_iut_ksan() {
         let secp = SecParams { bits_vrs: 2048, bits_pke: 2056, n: 3, dst: "k-SAN test".to_string() };
-        let mut pp = KSan::setup(&secp).unwrap();
-        let (sk_s, pk_s) = KSan::kgen_s(&mut pp).unwrap();
-        let (sk_z1, pk_z1) = KSan::kgen_z(&mut pp).unwrap();
-        let (sk_z2, pk_z2) = KSan::kgen_z(&mut pp).unwrap();
-        let (_sk_z3, pk_z3) = KSan::kgen_z(&mut pp).unwrap();
+        let pp = KSan::setup(&secp).unwrap();
+        let (sk_s, pk_s) = KSan::kgen_s(&pp).unwrap();
+        let (sk_z1, pk_z1) = KSan::kgen_z(&pp).unwrap();
+        let (sk_z2, pk_z2) = KSan::kgen_z(&pp).unwrap();
+        let (_sk_z3, pk_z3) = KSan::kgen_z(&pp).unwrap();
 
         let mut m = vec!["not_adm".to_string(), "adm1".to_string(), "adm2+3".to_string()];
         let mut adm = vec![vec![false; 3]; 3];
@@ -24,78 +38,80 @@ mod tests {
         let san_pks = vec![pk_z1.clone(), pk_z2.clone(), pk_z3.clone()];
         let mut ring: Vec<BigInt> = san_pks.iter().map(|x| x.pkp.clone()).collect();
         ring.push(pk_s.pkp.clone());
-        let sig = KSan::sign(&mut pp, &sk_s, &pk_s, &san_pks, &m, &adm).unwrap();
+        let sig = KSan::sign(&pp, &sk_s, &pk_s, &san_pks, &m, &adm).unwrap();
 
         //Test verification of a non-sanitized signature
-        let b = KSan::verify(&mut pp, &pk_s, &san_pks, &m, &sig).unwrap();
+        let b = KSan::verify(&pp, &pk_s, &san_pks, &m, &sig).unwrap();
         assert!(b, "Verification should succeed for non-sanitized signatures");
 
         //Test judge on a non-sanitized signature
-        let pr: Proof = KSan::prove(&mut pp, &sk_s, &pk_s, &san_pks, &m, &sig, None).unwrap();
-        let d = KSan::judge(&mut pp, &pk_s, &san_pks, &m, &sig, &pr, None).unwrap();
+        let pr: Proof = KSan::prove(&pp, &sk_s, &pk_s, &san_pks, &m, &sig, None).unwrap();
+        let d = KSan::judge(&pp, &pk_s, &san_pks, &m, &sig, &pr, None).unwrap();
         assert_eq!(d, 'S', "Judge should return 'S' for a non-sanitized signature");
 
         //Test verification of a partially sanitized signature
         let modif = vec![Mod { i: 1, m: "modadm1".to_string() }];
-        let sigp1 = KSan::sanitize(&mut pp, &sk_z1, &pk_s, &pk_z1, 
+        let sigp1 = KSan::sanitize(&pp, &sk_z1, &pk_s, &pk_z1, 
             &san_pks, &m, &modif, &sig).unwrap();
         m[1] = "modadm1".to_string();
-        let b = KSan::verify(&mut pp, &pk_s, &san_pks, &m, &sigp1).unwrap();
+        let b = KSan::verify(&pp, &pk_s, &san_pks, &m, &sigp1).unwrap();
         assert!(b, "Verification should succeed for partially sanitized signatures");
 
         //Test verification of a fully sanitized signature
         let modif = vec![Mod { i: 2, m: "modadm2+3".to_string() }];
-        let sigp2 = KSan::sanitize(&mut pp, &sk_z2, &pk_s, &pk_z2, 
+        let sigp2 = KSan::sanitize(&pp, &sk_z2, &pk_s, &pk_z2, 
             &san_pks, &m, &modif, &sigp1).unwrap();
         m[2] = "modadm2+3".to_string();
-        let b = KSan::verify(&mut pp, &pk_s, &san_pks, &m, &sigp2).unwrap();
+        let b = KSan::verify(&pp, &pk_s, &san_pks, &m, &sigp2).unwrap();
         assert!(b, "Verification should succeed for fully sanitized signatures");
 
         //Test judge on a sanitized signature
-        let pr: Proof = KSan::prove(&mut pp, &sk_s, &pk_s, &san_pks, &m, &sigp2, None).unwrap();
-        let d = KSan::judge(&mut pp, &pk_s, &san_pks, &m, &sigp2, &pr, None).unwrap();
+        let pr: Proof = KSan::prove(&pp, &sk_s, &pk_s, &san_pks, &m, &sigp2, None).unwrap();
+        let d = KSan::judge(&pp, &pk_s, &san_pks, &m, &sigp2, &pr, None).unwrap();
         assert_eq!(d, 'Z', "Judge should return 'Z' for a sanitized signature");
 
         //Test modification of a message block
         m[2] = "testverfalse".to_string();
-        let b = KSan::verify(&mut pp, &pk_s, &san_pks, &m, &sigp2).unwrap();
+        let b = KSan::verify(&pp, &pk_s, &san_pks, &m, &sigp2).unwrap();
         assert!(!b, "Verification should fail if an adversary modifies a message block");
         m[2] = "modadm2+3".to_string();
 
         //Test modification of the signer public key
-        let (_sk_s1, pk_s1) = KSan::kgen_s(&mut pp).unwrap();
-        let b = KSan::verify(&mut pp, &pk_s1, &san_pks, &m, &sigp2).unwrap();
+        let (_sk_s1, pk_s1) = KSan::kgen_s(&pp).unwrap();
+        let b = KSan::verify(&pp, &pk_s1, &san_pks, &m, &sigp2).unwrap();
         assert!(!b, "Verification should fail if an adversary modifies the signer public key");
 
         //Test modification of a sanitizer public key
-        let (_sk_z4, pk_z4) = KSan::kgen_z(&mut pp).unwrap();
-        let b = KSan::verify(&mut pp, &pk_s, &vec![pk_z1.clone(), pk_z2.clone(), pk_z4.clone()], &m, &sigp2).unwrap();
+        let (_sk_z4, pk_z4) = KSan::kgen_z(&pp).unwrap();
+        let b = KSan::verify(&pp, &pk_s, &[pk_z1.clone(), pk_z2.clone(), pk_z4.clone()], &m, &sigp2).unwrap();
         assert!(!b, "Verification should fail if an adversary modifies a sanitizer public key");
 
         //Test modification of a BLS (inner) signature to another one that is correct over the same message but with a different keys
         let mut sig_false = sigp2.clone();
         let mj = "0".to_string() + m[0].as_str();
-        let (_sk1, sk2, pk1, pk2) = BLS::k_gen(&mut pp.bg);
-        let s_bls = BLS::sign(&mut pp.bg, &sk2, &mj);
+        let (_sk1, sk2, pk1, pk2) = BLS::k_gen(&pp.bg);
+        let s_bls = BLS::sign(&pp.bg, &sk2, &mj);
         sig_false.s_ss.pk1_bls[0] = pk1;
         sig_false.s_ss.pk2_bls[0] = pk2;
         sig_false.s_ss.s_bls[0] = s_bls;
-        let b = KSan::verify(&mut pp, &pk_s, &san_pks, &m, &sig_false).unwrap();
+        let b = KSan::verify(&pp, &pk_s, &san_pks, &m, &sig_false).unwrap();
         assert!(!b, "Verification should fail if an adversary modifies a BLS (inner) signature");
         
         //Test modification of the EQS (outer) signatures to ones that are correct over the same pk1_bls and pk2_bls
         let mut sig_false = sigp2.clone(); 
-        let s_x_eqs = EQS::sign(&mut pp.bg, &sk_s.sk_eqs, &sigp2.s_ss.pk1_bls.iter().map(|x| x.into_affine()).collect());
-        let s_y_eqs = EQS::sign(&mut pp.bg, &sk_s.sk_eqs, &sigp2.s_ss.pk2_bls.iter().map(|y| y.into_affine()).collect());
+        let s_x_eqs = EQS::sign(&pp.bg, &sk_s.sk_eqs, 
+            &sigp2.s_ss.pk1_bls.iter().map(|x| x.into_affine()).collect::<Vec<_>>());
+        let s_y_eqs = EQS::sign(&pp.bg, &sk_s.sk_eqs, 
+            &sigp2.s_ss.pk2_bls.iter().map(|y| y.into_affine()).collect::<Vec<_>>());
         sig_false.s_ss.s_x_eqs = s_x_eqs;
         sig_false.s_ss.s_y_eqs = s_y_eqs;
-        let b = KSan::verify(&mut pp, &pk_s, &san_pks, &m, &sig_false).unwrap();
+        let b = KSan::verify(&pp, &pk_s, &san_pks, &m, &sig_false).unwrap();
         assert!(!b, "Verification should fail if an adversary modifies an EQS (outer) signature");
 
         //Test modification of the ciphertexts matrix
         let mut sig_false = sigp2.clone();
         sig_false.s_ss.secrets[0][0] = sig_false.s_ss.secrets[0][1].clone();
-        let b = KSan::verify(&mut pp, &pk_s, &san_pks, &m, &sig_false).unwrap();
+        let b = KSan::verify(&pp, &pk_s, &san_pks, &m, &sig_false).unwrap();
         assert!(!b, "Verification should fail if an adversary modifies the ciphertexts matrix");
         
         //Test adding a block to the message
@@ -107,11 +123,9 @@ mod tests {
         sig_false.s_ss.secrets[0].push(sigp2.s_ss.secrets[0][1].clone());
         sig_false.s_ss.secrets[1].push(sigp2.s_ss.secrets[1][1].clone());
         sig_false.s_ss.secrets[2].push(sigp2.s_ss.secrets[2][1].clone());
-        pp.bg.n = 4;
-        let b = KSan::verify(&mut pp, &pk_s, &san_pks, &m, &sig_false).unwrap();
+        let b = KSan::verify(&pp, &pk_s, &san_pks, &m, &sig_false).unwrap();
         assert!(!b, "Verification should fail if an adversary adds a block to the message");
         m.pop();
-        pp.bg.n = 3;
 
         //Test removing a block from the message
         let mut sig_false = sigp2.clone(); 
@@ -122,22 +136,857 @@ mod tests {
         sig_false.s_ss.secrets[0].pop();
         sig_false.s_ss.secrets[1].pop();
         sig_false.s_ss.secrets[2].pop();
-        pp.bg.n = 2;
-        let b = KSan::verify(&mut pp, &pk_s, &san_pks, &m, &sig_false).unwrap();
+        let b = KSan::verify(&pp, &pk_s, &san_pks, &m, &sig_false).unwrap();
         assert!(!b, "Verification should fail if an adversary removes a block from the message");
         m.push("modadm2+3".to_string());
-        pp.bg.n = 3;
 
         //Test adding a sanitizer public key
         let mut sig_false = sigp2.clone();
         sig_false.s_ss.secrets.push(sigp2.s_ss.secrets[2].clone());
-        let b = KSan::verify(&mut pp, &pk_s, &vec![pk_z1.clone(), pk_z2.clone(), pk_z3.clone(), pk_z4.clone()], &m, &sig_false).unwrap();
+        let b = KSan::verify(&pp, &pk_s, &[pk_z1.clone(), pk_z2.clone(), pk_z3.clone(), pk_z4.clone()], &m, &sig_false).unwrap();
         assert!(!b, "Verification should fail if an adversary adds a sanitizer public key");
 
         //Test removing a sanitizer public key
         let mut sig_false = sigp2.clone();
         sig_false.s_ss.secrets.pop();
-        let b = KSan::verify(&mut pp, &pk_s, &vec![pk_z1.clone(), pk_z2.clone()], &m, &sig_false).unwrap();
+        let b = KSan::verify(&pp, &pk_s, &[pk_z1.clone(), pk_z2.clone()], &m, &sig_false).unwrap();
         assert!(!b, "Verification should fail if an adversary removes a sanitizer public key");
     }
+
+    #[test]
+    fn test_iut_verify_batch() {
+        let (mut pp, sk_s, pk_s, [(sk_z1, pk_z1), (_sk_z2, pk_z2)]) = fixture(2);
+        KSan::precompute(&mut pp, 5).unwrap();
+        let san_pks = vec![pk_z1.clone(), pk_z2.clone()];
+        let mut adm = vec![vec![false; 2]; 2];
+        adm[0][1] = true;
+
+        let mut ms: Vec<Vec<String>> = Vec::new();
+        let mut sigs = Vec::new();
+        for i in 0..3 {
+            let m = vec![format!("not_adm{}", i), format!("adm{}", i)];
+            let sig = KSan::sign(&pp, &sk_s, &pk_s, &san_pks, &m, &adm).unwrap();
+            if i == 0 {
+                ms.push(m);
+                sigs.push(sig);
+                continue;
+            }
+            let modif = vec![Mod { i: 1, m: format!("modadm{}", i) }];
+            let sigp = KSan::sanitize(&pp, &sk_z1, &pk_s, &pk_z1, 
+                &san_pks, &m, &modif, &sig).unwrap();
+            ms.push(vec![m[0].clone(), format!("modadm{}", i)]);
+            sigs.push(sigp);
+        }
+        let san_pks_v = vec![san_pks.clone(); 3];
+
+        //Test batch verification of valid signatures
+        let (b, invalid) = KSan::verify_batch(&pp, &pk_s, &san_pks_v, &ms, &sigs).unwrap();
+        assert!(b, "Batch verification should succeed for valid signatures");
+        assert!(invalid.is_empty(), "Batch verification should not report valid signatures");
+
+        //Test batch verification with a modified message block
+        ms[2][0] = "testverfalse".to_string();
+        let (b, invalid) = KSan::verify_batch(&pp, &pk_s, &san_pks_v, &ms, &sigs).unwrap();
+        assert!(!b, "Batch verification should fail if an adversary modifies a message block");
+        assert_eq!(invalid, vec![2], "Batch verification should report the signature with a modified block");
+        ms[2][0] = "not_adm2".to_string();
+
+        //Test batch verification with a BLS (inner) signature swapped between two signatures
+        let s_bls = sigs[0].s_ss.s_bls[0];
+        sigs[0].s_ss.s_bls[0] = sigs[1].s_ss.s_bls[0];
+        sigs[1].s_ss.s_bls[0] = s_bls;
+        let (b, invalid) = KSan::verify_batch(&pp, &pk_s, &san_pks_v, &ms, &sigs).unwrap();
+        assert!(!b, "Batch verification should fail if an adversary swaps BLS (inner) signatures");
+        assert_eq!(invalid, vec![0, 1], "Batch verification should report both signatures with swapped BLS signatures");
+    }
+
+    #[test]
+    fn test_iut_sign_online() {
+        let (pp, sk_s, pk_s, [(sk_z1, pk_z1), (sk_z2, pk_z2)]) = fixture(2);
+        let san_pks = vec![pk_z1.clone(), pk_z2.clone()];
+        let mut adm = vec![vec![false; 2]; 2];
+        adm[0][1] = true;
+        let m = vec!["not_adm".to_string(), "adm".to_string()];
+        let mut pool = KSan::sign_offline(&pp, &sk_s, &san_pks, 2, 2).unwrap();
+
+        //Test that a signature computed from the pool is sanitized and verified like a regular one
+        let sig = KSan::sign_online(&pp, &sk_s, &pk_s, &san_pks, &m, &adm, &mut pool).unwrap();
+        assert_eq!(pool.sigs.len(), 1, "Online signing should consume one precomputed signature");
+        let b = KSan::verify(&pp, &pk_s, &san_pks, &m, &sig).unwrap();
+        assert!(b, "Verification of an online signature failed");
+        let modif = vec![Mod { i: 1, m: "modadm".to_string() }];
+        let sigp = KSan::sanitize(&pp, &sk_z1, &pk_s, &pk_z1, &san_pks, &m, &modif, &sig).unwrap();
+        let mp = vec!["not_adm".to_string(), "modadm".to_string()];
+        let b = KSan::verify(&pp, &pk_s, &san_pks, &mp, &sigp).unwrap();
+        assert!(b, "Verification of a sanitized online signature failed");
+
+        //Test that the admissible blocks are respected
+        let r = KSan::sanitize(&pp, &sk_z2, &pk_s, &pk_z2, &san_pks, &m, &modif, &sig);
+        assert!(r.is_err(), "Sanitizing a non-admissible block of an online signature should fail");
+
+        //Test that the pool is exhausted after its last signature
+        let _sig2 = KSan::sign_online(&pp, &sk_s, &pk_s, &san_pks, &m, &adm, &mut pool).unwrap();
+        let r = KSan::sign_online(&pp, &sk_s, &pk_s, &san_pks, &m, &adm, &mut pool);
+        assert!(r.is_err(), "Online signing should fail when the pool is exhausted");
+
+        //Test that the pool is bound to its set of sanitizers
+        let mut pool = KSan::sign_offline(&pp, &sk_s, &san_pks, 2, 1).unwrap();
+        let r = KSan::sign_online(&pp, &sk_s, &pk_s, &[pk_z2.clone(), pk_z1.clone()], &m, &adm, &mut pool);
+        assert!(r.is_err(), "Online signing should fail for another set of sanitizers");
+
+        //Test that the pool is bound to its message length
+        let mut pool = KSan::sign_offline(&pp, &sk_s, &san_pks, 1, 1).unwrap();
+        let r = KSan::sign_online(&pp, &sk_s, &pk_s, &san_pks, &m, &adm, &mut pool);
+        assert!(r.is_err(), "Online signing should fail for another message length");
+        assert_eq!(pool.sigs.len(), 1, "A failed online signing should not consume the pool");
+
+        //Test that adm must have one row per sanitizer and one element per block
+        let mut pool = KSan::sign_offline(&pp, &sk_s, &san_pks, 2, 1).unwrap();
+        let r = KSan::sign_online(&pp, &sk_s, &pk_s, &san_pks, &m, &adm[..1], &mut pool);
+        assert!(r.is_err(), "Online signing should fail for a missing row of adm");
+        let r = KSan::sign_online(&pp, &sk_s, &pk_s, &san_pks, &m, &[vec![true], vec![false]], &mut pool);
+        assert!(r.is_err(), "Online signing should fail for a short row of adm");
+        assert_eq!(pool.sigs.len(), 1, "A failed online signing should not consume the pool");
+    }
+
+    #[test]
+    fn test_iut_sizes() {
+        let (pp, sk_s, pk_s, [(_sk_z1, pk_z1), (_sk_z2, pk_z2)]) = fixture(2);
+        let san_pks = vec![pk_z1.clone(), pk_z2.clone()];
+        let mut adm = vec![vec![false; 2]; 2];
+        adm[0][1] = true;
+        let m = vec!["not_adm".to_string(), "adm".to_string()];
+        let sig = KSan::sign(&pp, &sk_s, &pk_s, &san_pks, &m, &adm).unwrap();
+
+        //Test the size of the curve points of a signature with n = 3 blocks (the last one is the sanitizer keys)
+        let size = KSan::signature_size(&sig);
+        assert_eq!(size.get("eqs"), 2 * (48 + 48 + 96), "Invalid size of the EQS signatures");
+        assert_eq!(size.get("bls_signatures"), 3 * 96, "Invalid size of the BLS signatures");
+        assert_eq!(size.get("bls_public_keys"), 2 * 3 * 48, "Invalid size of the BLS public keys");
+        assert!(size.get("ciphertexts") > 0 && size.get("vrs_ring_signature") > 0, "Missing size components");
+        assert_eq!(size.total(), size.components.iter().map(|c| c.1).sum::<usize>(), "Total size should sum the components");
+
+        //Test the size of the keys and of a proof
+        assert_eq!(KSan::signer_pk_size(&pk_s).get("eqs"), 3 * 96, "Invalid size of the EQS public key");
+        let pr = KSan::prove(&pp, &sk_s, &pk_s, &san_pks, &m, &sig, None).unwrap();
+        assert!(KSan::proof_size(&pr).get("vrs_proof") > 0, "Missing size of the VRS proof");
+        assert!(KSan::sanitizer_pk_size(&pk_z1).get("paillier") >= 64, "Invalid size of the Paillier public key");
+    }
+
+    #[test]
+    fn test_iut_mercurial() {
+        let secp = SecParams { bits_vrs: 512, bits_pke: 520, n: 2, dst: "k-SAN test".to_string() };
+        let pp = KSan::setup_with::<Mercurial>(&secp).unwrap();
+        let (sk_s, pk_s) = KSan::kgen_s(&pp).unwrap();
+        let (sk_z1, pk_z1) = KSan::kgen_z(&pp).unwrap();
+        let (sk_z2, pk_z2) = KSan::kgen_z(&pp).unwrap();
+        let san_pks = vec![pk_z1.clone(), pk_z2.clone()];
+        let mut adm = vec![vec![false; 2]; 2];
+        adm[0][1] = true;
+        let mut m = vec!["not_adm".to_string(), "adm".to_string()];
+
+        //Test sign, sanitize and verify with the mercurial-signature backend
+        let sig = KSan::sign(&pp, &sk_s, &pk_s, &san_pks, &m, &adm).unwrap();
+        let b = KSan::verify(&pp, &pk_s, &san_pks, &m, &sig).unwrap();
+        assert!(b, "Verification with the Mercurial backend failed");
+        let modif = vec![Mod { i: 1, m: "modadm".to_string() }];
+        let sigp = KSan::sanitize(&pp, &sk_z1, &pk_s, &pk_z1, &san_pks, &m, &modif, &sig).unwrap();
+        let r = KSan::sanitize(&pp, &sk_z2, &pk_s, &pk_z2, &san_pks, &m, &modif, &sig);
+        assert!(r.is_err(), "Sanitizing a non-admissible block with the Mercurial backend should fail");
+        m[1] = "modadm".to_string();
+        let b = KSan::verify(&pp, &pk_s, &san_pks, &m, &sigp).unwrap();
+        assert!(b, "Verification of a sanitized signature with the Mercurial backend failed");
+
+        //Test judge with the mercurial-signature backend
+        let pr: Proof = KSan::prove(&pp, &sk_s, &pk_s, &san_pks, &m, &sigp, None).unwrap();
+        let d = KSan::judge(&pp, &pk_s, &san_pks, &m, &sigp, &pr, None).unwrap();
+        assert_eq!(d, 'Z', "Judge should return 'Z' for a sanitized signature with the Mercurial backend");
+
+        //Test that the EQS signatures are checked
+        let mut sig_false = sigp.clone();
+        sig_false.s_ss.s_x_eqs = sig.s_ss.s_x_eqs.clone();
+        let b = KSan::verify(&pp, &pk_s, &san_pks, &m, &sig_false).unwrap();
+        assert!(!b, "Verification with the Mercurial backend should fail if an EQS signature is replaced");
+        let (_sk_s1, pk_s1) = KSan::kgen_s(&pp).unwrap();
+        let b = KSan::verify(&pp, &pk_s1, &san_pks, &m, &sigp).unwrap();
+        assert!(!b, "Verification with the Mercurial backend should fail for another signer public key");
+
+        //Test batch verification with the mercurial-signature backend
+        let (b, invalid) = KSan::verify_batch(&pp, &pk_s, &vec![san_pks.clone(); 2], &vec![m.clone(); 2], 
+            &[sigp.clone(), sig_false.clone()]).unwrap();
+        assert!(!b && invalid == vec![1], "Batch verification with the Mercurial backend should report the invalid signature");
+    }
+
+    #[test]
+    fn test_iut_shared_params() {
+        fn assert_send_sync<T: Send + Sync>(_: &T) {}
+        let secp = SecParams { bits_vrs: 512, bits_pke: 520, n: 2, dst: "k-SAN test".to_string() };
+        let pp = KSan::setup(&secp).unwrap();
+        assert_send_sync(&pp);
+        let (sk_s, pk_s) = KSan::kgen_s(&pp).unwrap();
+        let (sk_z1, pk_z1) = KSan::kgen_z(&pp).unwrap();
+        let (_sk_z2, pk_z2) = KSan::kgen_z(&pp).unwrap();
+        let san_pks = vec![pk_z1.clone(), pk_z2.clone()];
+        let mut adm = vec![vec![false; 2]; 2];
+        adm[0][1] = true;
+        let m = vec!["not_adm".to_string(), "adm".to_string()];
+
+        //Test that signatures produced and verified from several threads share the same parameters
+        let sigs = std::thread::scope(|s| {
+            let handles: Vec<_> = (0..4).map(|_| s.spawn(|| KSan::sign(&pp, &sk_s, &pk_s, &san_pks, &m, &adm).unwrap())).collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect::<Vec<_>>()
+        });
+        let modif = vec![Mod { i: 1, m: "modadm".to_string() }];
+        let mp = vec!["not_adm".to_string(), "modadm".to_string()];
+        std::thread::scope(|s| {
+            for sig in sigs.iter() {
+                s.spawn(|| {
+                    let b = KSan::verify(&pp, &pk_s, &san_pks, &m, sig).unwrap();
+                    assert!(b, "Verification from another thread failed");
+                    let sigp = KSan::sanitize(&pp, &sk_z1, &pk_s, &pk_z1, &san_pks, &m, &modif, sig).unwrap();
+                    let b = KSan::verify(&pp, &pk_s, &san_pks, &mp, &sigp).unwrap();
+                    assert!(b, "Verification of a signature sanitized from another thread failed");
+                });
+            }
+        });
+    }
+
+    #[test]
+    fn test_iut_variable_length() {
+        let (pp, sk_s, pk_s, [(sk_z1, pk_z1), (_sk_z2, pk_z2)]) = fixture(4);
+        let san_pks = vec![pk_z1.clone(), pk_z2.clone()];
+
+        //Test sign and verify for every length up to the maximum with the same parameters
+        let mut ms: Vec<Vec<String>> = Vec::new();
+        let mut sigs = Vec::new();
+        for l in 1..5 {
+            let m: Vec<String> = (0..l).map(|j| format!("block{}", j)).collect();
+            let mut adm = vec![vec![false; l]; 2];
+            adm[0][l - 1] = true;
+            let sig = KSan::sign(&pp, &sk_s, &pk_s, &san_pks, &m, &adm).unwrap();
+            let b = KSan::verify(&pp, &pk_s, &san_pks, &m, &sig).unwrap();
+            assert!(b, "Verification of a signature on {} blocks failed", l);
+            ms.push(m);
+            sigs.push(sig);
+        }
+        let (b, invalid) = KSan::verify_batch(&pp, &pk_s, &vec![san_pks.clone(); 4], &ms, &sigs).unwrap();
+        assert!(b && invalid.is_empty(), "Batch verification of signatures of different lengths failed");
+
+        //Test that the lengths are bounded
+        let r = KSan::sign(&pp, &sk_s, &pk_s, &san_pks, &[], &vec![vec![]; 2]);
+        assert!(r.is_err(), "Signing an empty message should fail");
+        let m: Vec<String> = (0..5).map(|j| format!("block{}", j)).collect();
+        let r = KSan::sign(&pp, &sk_s, &pk_s, &san_pks, &m, &vec![vec![false; 5]; 2]);
+        assert!(r.is_err(), "Signing a message longer than the maximum should fail");
+
+        //Test sanitization of a shorter signature
+        let modif = vec![Mod { i: 1, m: "modadm".to_string() }];
+        let sigp = KSan::sanitize(&pp, &sk_z1, &pk_s, &pk_z1, &san_pks, &ms[1], &modif, &sigs[1]).unwrap();
+        let mp = vec!["block0".to_string(), "modadm".to_string()];
+        let b = KSan::verify(&pp, &pk_s, &san_pks, &mp, &sigp).unwrap();
+        assert!(b, "Verification of a sanitized signature on 2 blocks failed");
+
+        //Test that the length is authenticated
+        let b = KSan::verify(&pp, &pk_s, &san_pks, &ms[2], &sigs[1]).unwrap();
+        assert!(!b, "Verification should fail for a signature on another number of blocks");
+        let mut sig_false = sigs[2].clone();
+        sig_false.s_ss.pk1_bls.drain(2..3);
+        sig_false.s_ss.pk2_bls.drain(2..3);
+        sig_false.s_ss.s_bls.drain(2..3);
+        for c in sig_false.s_ss.secrets.iter_mut() {
+            c.drain(2..3);
+        }
+        let b = KSan::verify(&pp, &pk_s, &san_pks, &ms[1], &sig_false).unwrap();
+        assert!(!b, "Verification should fail if an adversary truncates a signature");
+
+        //Test that a signature cannot be extended with identity BLS keys, which leave the EQS signatures unchanged,
+        //even if a sanitizer signs the extended message
+        let mut sig_false = sigs[1].clone();
+        sig_false.s_ss.pk1_bls.push(G1Projective::zero());
+        sig_false.s_ss.pk2_bls.push(G1Projective::zero());
+        sig_false.s_ss.s_bls.push(G2Projective::zero());
+        for c in sig_false.s_ss.secrets.iter_mut() {
+            c.push(c[0].clone());
+        }
+        sig_false.s_ss.policy.push(Policy { modify: false, redact: false });
+        sig_false.s_ss.value_sets.push(None);
+        sig_false.s_ss.commitments.push(None);
+        sig_false.s_ss.thresholds.push(None);
+        let mut m_false = ms[1].clone();
+        m_false.push("forged".to_string());
+        let b = KSan::verify(&pp, &pk_s, &san_pks, &m_false, &sig_false).unwrap();
+        assert!(!b, "Verification should fail if an adversary appends identity keys to a signature");
+        let sig_false = KSan::sanitize(&pp, &sk_z1, &pk_s, &pk_z1, &san_pks, &m_false, &[], &sig_false).unwrap();
+        let b = KSan::verify(&pp, &pk_s, &san_pks, &m_false, &sig_false).unwrap();
+        assert!(!b, "Verification should fail if a sanitizer signs a signature extended with identity keys");
+        let (b, _) = KSan::verify_batch(&pp, &pk_s, std::slice::from_ref(&san_pks), &[m_false], &[sig_false])
+            .unwrap();
+        assert!(!b, "Batch verification should fail if a sanitizer signs a signature extended with identity keys");
+    }
+
+    #[test]
+    fn test_iut_extend_sanitizers() {
+        let (pp, sk_s, pk_s, [(_sk_z1, pk_z1), (sk_z2, pk_z2), (_sk_z3, pk_z3)]) = fixture(2);
+        let san_pks = vec![pk_z1.clone()];
+        let adm = vec![vec![false, true]];
+        let m = vec!["not_adm".to_string(), "adm".to_string()];
+        let (sig, td) = KSan::sign_with_trapdoors(&pp, &sk_s, &pk_s, &san_pks, &m, &adm).unwrap();
+
+        //Test that a new sanitizer can be granted a block that was not admissible
+        let ext = Extension { pk_z: pk_z2.clone(), adm_z: vec![true, false], td: td.clone() };
+        let (sige, _tde) = KSan::extend_sanitizers(&pp, &sk_s, &pk_s, &san_pks, &m, &sig, &ext).unwrap();
+        let san_pks_e = vec![pk_z1.clone(), pk_z2.clone()];
+        let b = KSan::verify(&pp, &pk_s, &san_pks_e, &m, &sige).unwrap();
+        assert!(b, "Verification of an extended signature failed");
+        let b = KSan::verify(&pp, &pk_s, &san_pks, &m, &sige).unwrap();
+        assert!(!b, "Verification of an extended signature should fail for the original sanitizers");
+        let modif = vec![Mod { i: 0, m: "modnot_adm".to_string() }];
+        let sigp = KSan::sanitize(&pp, &sk_z2, &pk_s, &pk_z2, &san_pks_e, &m, &modif, &sige).unwrap();
+        let mp = vec!["modnot_adm".to_string(), "adm".to_string()];
+        let b = KSan::verify(&pp, &pk_s, &san_pks_e, &mp, &sigp).unwrap();
+        assert!(b, "Verification of a signature sanitized by an added sanitizer failed");
+        let pr: Proof = KSan::prove(&pp, &sk_s, &pk_s, &san_pks_e, &mp, &sigp, None).unwrap();
+        let d = KSan::judge(&pp, &pk_s, &san_pks_e, &mp, &sigp, &pr, None).unwrap();
+        assert_eq!(d, 'Z', "Judge should return 'Z' for a signature sanitized by an added sanitizer");
+
+        //Test that the new sanitizer only gets the granted blocks
+        let modif = vec![Mod { i: 1, m: "modadm".to_string() }];
+        let r = KSan::sanitize(&pp, &sk_z2, &pk_s, &pk_z2, &san_pks_e, &m, &modif, &sige);
+        assert!(r.is_err(), "Sanitizing a block that was not granted to the added sanitizer should fail");
+
+        //Test that the trapdoors must match an unsanitized signature
+        let ext = Extension { pk_z: pk_z3.clone(), adm_z: vec![true; 2], td: td.clone() };
+        let r = KSan::extend_sanitizers(&pp, &sk_s, &pk_s, &san_pks_e, &mp, &sigp, &ext);
+        assert!(r.is_err(), "Extending a sanitized signature should fail");
+    }
+
+    #[test]
+    fn test_iut_revoke() {
+        let (pp, sk_s, pk_s, [(sk_z1, pk_z1), (sk_z2, pk_z2)]) = fixture(3);
+        let san_pks = vec![pk_z1.clone(), pk_z2.clone()];
+        let adm = vec![vec![false, true, true], vec![false, false, true]];
+        let m = vec!["not_adm".to_string(), "adm1".to_string(), "adm1+2".to_string()];
+        let (sig, td) = KSan::sign_with_trapdoors(&pp, &sk_s, &pk_s, &san_pks, &m, &adm).unwrap();
+
+        //Test that only the blocks of the revoked sanitizer get new BLS keys
+        let rev = Revocation { pk_z: pk_z1.clone(), td: td.clone() };
+        let (sigr, _tdr) = KSan::revoke(&pp, &sk_s, &pk_s, &san_pks, &m, &sig, &rev).unwrap();
+        assert_eq!(sigr.s_ss.pk2_bls[0], sig.s_ss.pk2_bls[0], "A block of no revoked sanitizer should be kept");
+        assert_ne!(sigr.s_ss.pk2_bls[2], sig.s_ss.pk2_bls[2], "A block of the revoked sanitizer should get a new key");
+        let san_pks_r = vec![pk_z2.clone()];
+        let b = KSan::verify(&pp, &pk_s, &san_pks_r, &m, &sigr).unwrap();
+        assert!(b, "Verification of a signature after revocation failed");
+
+        //Test that the remaining sanitizers keep their rights and the revoked one lost them
+        let modif = vec![Mod { i: 2, m: "modadm1+2".to_string() }];
+        let sigp = KSan::sanitize(&pp, &sk_z2, &pk_s, &pk_z2, &san_pks_r, &m, &modif, &sigr).unwrap();
+        let mp = vec!["not_adm".to_string(), "adm1".to_string(), "modadm1+2".to_string()];
+        let b = KSan::verify(&pp, &pk_s, &san_pks_r, &mp, &sigp).unwrap();
+        assert!(b, "Verification of a signature sanitized by a remaining sanitizer failed");
+        let modif1 = vec![Mod { i: 1, m: "modadm1".to_string() }];
+        let r = KSan::sanitize(&pp, &sk_z2, &pk_s, &pk_z2, &san_pks_r, &m, &modif1, &sigr);
+        assert!(r.is_err(), "The block of the revoked sanitizer should not be admissible anymore");
+        let r = KSan::sanitize(&pp, &sk_z1, &pk_s, &pk_z1, &san_pks_r, &m, &modif, &sigr);
+        assert!(r.is_err(), "A revoked sanitizer should not be able to sanitize the new signature");
+        let r = KSan::sanitize(&pp, &sk_z1, &pk_s, &pk_z1, &san_pks, &m, &modif, &sigr);
+        assert!(r.is_err(), "A revoked sanitizer should not be able to sanitize the new signature with the old sanitizers");
+
+        //Test the revocation list
+        let rl = KSan::revocation_list(&pp, &sk_s, &pk_s, 1, std::slice::from_ref(&pk_z1)).unwrap();
+        assert!(KSan::verify_revocation_list(&pp, &pk_s, &rl).unwrap(), "Verification of a revocation list failed");
+        let mut rl_false = rl.clone();
+        rl_false.epoch = 2;
+        assert!(!KSan::verify_revocation_list(&pp, &pk_s, &rl_false).unwrap(), 
+            "Verification should fail if an adversary changes the epoch of the revocation list");
+        let b = KSan::verify_with_revocation(&pp, &pk_s, &san_pks_r, &mp, &sigp, &rl).unwrap();
+        assert!(b, "Verification against the revocation list should succeed for the new signature");
+        let b = KSan::verify_with_revocation(&pp, &pk_s, &san_pks, &m, &sig, &rl).unwrap();
+        assert!(!b, "Verification against the revocation list should fail for a signature of a revoked sanitizer");
+    }
+
+    #[test]
+    fn test_iut_delegate() {
+        let (pp, sk_s, pk_s, [(sk_z1, pk_z1), (_sk_z2, pk_z2), (sk_d1, pk_d1), (sk_d2, pk_d2)]) = fixture(3);
+        let san_pks = vec![pk_z1.clone(), pk_z2.clone()];
+        let adm = vec![vec![false, true], vec![false, false]];
+        let m = vec!["not_adm".to_string(), "adm1".to_string()];
+        let sig = KSan::sign(&pp, &sk_s, &pk_s, &san_pks, &m, &adm).unwrap();
+
+        //Test that only admissible blocks can be delegated, and not to a sanitizer
+        assert!(KSan::delegate(&pp, &sk_z1, &pk_z1, &san_pks, &pk_d1, &[0], &sig).is_err(), 
+            "A sanitizer should not be able to delegate a block it cannot modify");
+        assert!(KSan::delegate(&pp, &sk_z1, &pk_z1, &san_pks, &pk_z2, &[1], &sig).is_err(), 
+            "A sanitizer should not be able to delegate to another sanitizer");
+        let modif = vec![Mod { i: 1, m: "modadm1".to_string() }];
+        assert!(KSan::sanitize(&pp, &sk_d1, &pk_s, &pk_d1, &san_pks, &m, &modif, &sig).is_err(), 
+            "A key without delegation should not be able to sanitize");
+
+        //Test a delegation chain: z1 delegates block 1 to d1, who delegates it to d2
+        let sigd = KSan::delegate(&pp, &sk_z1, &pk_z1, &san_pks, &pk_d1, &[1], &sig).unwrap();
+        assert!(KSan::verify(&pp, &pk_s, &san_pks, &m, &sigd).unwrap(), "Verification of a delegated signature failed");
+        let sigd = KSan::delegate(&pp, &sk_d1, &pk_d1, &san_pks, &pk_d2, &[1], &sigd).unwrap();
+        assert_eq!(sigd.s_ss.delegations.len(), 2, "The signature should hold both delegations");
+        let sigp = KSan::sanitize(&pp, &sk_d2, &pk_s, &pk_d2, &san_pks, &m, &modif, &sigd).unwrap();
+        let mp = vec!["not_adm".to_string(), "modadm1".to_string()];
+        let b = KSan::verify(&pp, &pk_s, &san_pks, &mp, &sigp).unwrap();
+        assert!(b, "Verification of a signature sanitized by a delegate failed");
+        let (b, _) = KSan::verify_batch(&pp, &pk_s, std::slice::from_ref(&san_pks), std::slice::from_ref(&mp), 
+            std::slice::from_ref(&sigp)).unwrap();
+        assert!(b, "Batch verification of a signature sanitized by a delegate failed");
+        let p = KSan::prove(&pp, &sk_s, &pk_s, &san_pks, &mp, &sigp, None).unwrap();
+        let j = KSan::judge(&pp, &pk_s, &san_pks, &mp, &sigp, &p, None).unwrap();
+        assert_eq!(j, 'Z', "Judge should blame the sanitizers for a signature sanitized by a delegate");
+
+        //Test that the delegation chain is bound into the signature
+        let mut sig_false = sigp.clone();
+        sig_false.s_ss.delegations.remove(0);
+        assert!(!KSan::verify(&pp, &pk_s, &san_pks, &mp, &sig_false).unwrap(), 
+            "Verification should fail if an adversary removes a delegation");
+        let mut sig_false = sigp.clone();
+        sig_false.s_ss.delegations[1].blocks = vec![0];
+        assert!(!KSan::verify(&pp, &pk_s, &san_pks, &mp, &sig_false).unwrap(), 
+            "Verification should fail if an adversary changes the delegated blocks");
+
+        //Test that a delegation that is not covered yet needs the certificate of the delegator
+        let sigd = KSan::delegate(&pp, &sk_z1, &pk_z1, &san_pks, &pk_d1, &[1], &sig).unwrap();
+        let mut sig_false = sigd.clone();
+        sig_false.s_ss.delegations[0].pk_d = pk_d2.clone();
+        assert!(!KSan::verify(&pp, &pk_s, &san_pks, &m, &sig_false).unwrap(), 
+            "Verification should fail if an adversary changes the delegate");
+        assert!(KSan::sanitize(&pp, &sk_d2, &pk_s, &pk_d2, &san_pks, &m, &modif, &sig_false).is_err(), 
+            "Sanitization should fail for a delegation without a valid certificate");
+
+        //Test that a delegation does not use a sanitization of the limit
+        let sig = KSan::sign_with_limit(&pp, &sk_s, &pk_s, &san_pks, &m, &adm, 1).unwrap();
+        let sigd = KSan::delegate(&pp, &sk_z1, &pk_z1, &san_pks, &pk_d1, &[1], &sig).unwrap();
+        assert!(sigd.s_ss.tags.is_empty(), "A delegation should not add a limit tag");
+        assert!(KSan::verify(&pp, &pk_s, &san_pks, &m, &sigd).unwrap(), 
+            "Verification of a delegated signature with a limit failed");
+        let sigp = KSan::sanitize(&pp, &sk_d1, &pk_s, &pk_d1, &san_pks, &m, &modif, &sigd).unwrap();
+        assert!(KSan::verify(&pp, &pk_s, &san_pks, &mp, &sigp).unwrap(), 
+            "Verification of a signature with a limit sanitized by a delegate failed");
+    }
+
+    #[test]
+    fn test_iut_value_sets() {
+        let (pp, sk_s, pk_s, [(sk_z, pk_z)]) = fixture(3);
+        let san_pks = vec![pk_z.clone()];
+        let adm = vec![vec![false, true]];
+        let set: Vec<String> = ["draft", "submitted", "approved"].iter().map(|v| v.to_string()).collect();
+        let values = vec![None, Some(set.clone())];
+        let m = vec!["form-42".to_string(), "draft".to_string()];
+
+        //Test that the value of a restricted block must be in its set
+        let m_false = vec!["form-42".to_string(), "deleted".to_string()];
+        assert!(KSan::sign_with_values(&pp, &sk_s, &pk_s, &san_pks, &m_false, &adm, &values).is_err(), 
+            "Signing a value out of the set should fail");
+        let sig = KSan::sign_with_values(&pp, &sk_s, &pk_s, &san_pks, &m, &adm, &values).unwrap();
+        assert!(sig.s_ss.value_sets[0].is_none() && sig.s_ss.value_sets[1].is_some(), "Only the restricted block should have a value set");
+
+        //Test that a sanitizer can write a value of the set and no other one
+        let modif = vec![Mod { i: 1, m: "approved".to_string() }];
+        let san_opts = SanitizeOptions { values: values.clone(), ..SanitizeOptions::new(&m, &modif) };
+        let sigp = KSan::sanitize_with_options(&pp, &sk_z, &pk_s, &pk_z, &san_pks, &san_opts, &sig).unwrap();
+        let mp = vec!["form-42".to_string(), "approved".to_string()];
+        let b = KSan::verify(&pp, &pk_s, &san_pks, &mp, &sigp).unwrap();
+        assert!(b, "Verification of a signature sanitized with an allowed value failed");
+        let (b, _) = KSan::verify_batch(&pp, &pk_s, std::slice::from_ref(&san_pks), std::slice::from_ref(&mp), 
+            std::slice::from_ref(&sigp)).unwrap();
+        assert!(b, "Batch verification of a signature sanitized with an allowed value failed");
+        let modif_false = vec![Mod { i: 1, m: "deleted".to_string() }];
+        let san_opts = SanitizeOptions { values: values.clone(), ..SanitizeOptions::new(&m, &modif_false) };
+        let r = KSan::sanitize_with_options(&pp, &sk_z, &pk_s, &pk_z, &san_pks, &san_opts, &sig);
+        assert!(r.is_err(), "Sanitizing with a value out of the set should fail");
+        let r = KSan::sanitize(&pp, &sk_z, &pk_s, &pk_z, &san_pks, &m, &modif, &sig);
+        assert!(r.is_err(), "Sanitizing a restricted block without its value set should fail");
+        let mut set_false = set.clone();
+        set_false.push("deleted".to_string());
+        let values_false = vec![None, Some(set_false.clone())];
+        let san_opts = SanitizeOptions { values: values_false, ..SanitizeOptions::new(&m, &modif_false) };
+        let r = KSan::sanitize_with_options(&pp, &sk_z, &pk_s, &pk_z, &san_pks, &san_opts, &sig);
+        assert!(r.is_err(), "Sanitizing with another value set should fail");
+
+        //Test that the roots are bound into the signature
+        let mut sig_false = sigp.clone();
+        sig_false.s_ss.value_sets[1] = None;
+        assert!(!KSan::verify(&pp, &pk_s, &san_pks, &mp, &sig_false).unwrap(), 
+            "Verification should fail if an adversary removes a value set");
+        let mut sig_false = sigp.clone();
+        sig_false.s_ss.value_sets[1].as_mut().unwrap().root = Merkle::root(&set_false);
+        sig_false.s_ss.value_sets[1].as_mut().unwrap().membership = Merkle::prove(&set_false, &"approved".to_string()).unwrap();
+        assert!(!KSan::verify(&pp, &pk_s, &san_pks, &mp, &sig_false).unwrap(), 
+            "Verification should fail if an adversary replaces a value set");
+    }
+
+    #[test]
+    fn test_iut_redact() {
+        let (pp, sk_s, pk_s, [(sk_z, pk_z)]) = fixture(4);
+        let san_pks = vec![pk_z.clone()];
+        let adm = vec![vec![true, true, true]];
+        let policy = vec![
+            Policy { modify: true, redact: false },
+            Policy { modify: false, redact: true },
+            Policy { modify: true, redact: true }
+        ];
+        let m = vec!["name".to_string(), "ssn".to_string(), "address".to_string()];
+        let sig = KSan::sign_with_policy(&pp, &sk_s, &pk_s, &san_pks, &m, &adm, &policy).unwrap();
+        assert!(KSan::verify(&pp, &pk_s, &san_pks, &m, &sig).unwrap(), "Verification of a signature with a policy failed");
+        assert!(sig.s_ss.commitments[1].is_some() && sig.s_ss.commitments[2].is_none(), 
+            "Only the blocks that are only redactable should be committed");
+
+        //Test that a redactable block can be redacted and the remaining blocks verified
+        let san_opts = SanitizeOptions { redact: vec![1], ..SanitizeOptions::new(&m, &[]) };
+        let sigp = KSan::sanitize_with_options(&pp, &sk_z, &pk_s, &pk_z, &san_pks, &san_opts, &sig).unwrap();
+        let mp = vec!["name".to_string(), "address".to_string()];
+        assert_eq!(sigp.s_ss.redacted, vec![1], "The redacted block should be recorded");
+        assert!(sigp.s_ss.commitments[1].as_ref().unwrap().salt.is_none(), "The salt of a redacted block should be removed");
+        assert!(KSan::verify(&pp, &pk_s, &san_pks, &mp, &sigp).unwrap(), "Verification of a redacted signature failed");
+        let (b, _) = KSan::verify_batch(&pp, &pk_s, std::slice::from_ref(&san_pks), std::slice::from_ref(&mp), 
+            std::slice::from_ref(&sigp)).unwrap();
+        assert!(b, "Batch verification of a redacted signature failed");
+        assert!(!KSan::verify(&pp, &pk_s, &san_pks, &m, &sigp).unwrap(), 
+            "Verification with the redacted block should fail");
+        let pr: Proof = KSan::prove(&pp, &sk_s, &pk_s, &san_pks, &mp, &sigp, None).unwrap();
+        let d = KSan::judge(&pp, &pk_s, &san_pks, &mp, &sigp, &pr, None).unwrap();
+        assert_eq!(d, 'Z', "Judge should blame the sanitizers for a redacted signature");
+
+        //Test that the policy is enforced
+        let san_opts = SanitizeOptions { redact: vec![0], ..SanitizeOptions::new(&m, &[]) };
+        let r = KSan::sanitize_with_options(&pp, &sk_z, &pk_s, &pk_z, &san_pks, &san_opts, &sig);
+        assert!(r.is_err(), "Redacting a block that is not redactable should fail");
+        let modif = vec![Mod { i: 1, m: "000-00-0000".to_string() }];
+        let r = KSan::sanitize(&pp, &sk_z, &pk_s, &pk_z, &san_pks, &m, &modif, &sig);
+        assert!(r.is_err(), "Modifying a block that is only redactable should fail");
+        let m_false = vec!["name".to_string(), "000-00-0000".to_string(), "address".to_string()];
+        assert!(!KSan::verify(&pp, &pk_s, &san_pks, &m_false, &sig).unwrap(), 
+            "Verification should fail if a block that is only redactable is modified");
+
+        //Test that the redacted blocks are bound into the signature
+        let mut sig_false = sigp.clone();
+        sig_false.s_ss.redacted = vec![2];
+        let mp_false = vec!["name".to_string(), "ssn".to_string()];
+        assert!(!KSan::verify(&pp, &pk_s, &san_pks, &mp_false, &sig_false).unwrap(), 
+            "Verification should fail if an adversary changes the redacted blocks");
+        let mut sig_false = sigp.clone();
+        sig_false.s_ss.policy[0].redact = true;
+        assert!(!KSan::verify(&pp, &pk_s, &san_pks, &mp, &sig_false).unwrap(), 
+            "Verification should fail if an adversary changes the policy");
+    }
+
+    #[test]
+    fn test_iut_append() {
+        let (pp, sk_s, pk_s, [(sk_z1, pk_z1), (sk_z2, pk_z2)]) = fixture(5);
+        let san_pks = vec![pk_z1.clone(), pk_z2.clone()];
+        let adm = vec![vec![false], vec![false]];
+        let m = vec!["report".to_string()];
+        let sig = KSan::sign_with_slots(&pp, &sk_s, &pk_s, &san_pks, &m, &adm, &[2, 1]).unwrap();
+        assert!(KSan::verify(&pp, &pk_s, &san_pks, &m, &sig).unwrap(), "Verification of a signature with unfilled slots failed");
+        assert_eq!(KSan::unfilled_slots(&san_pks, &pk_z1, &sig), vec![1, 2], "The first sanitizer should own two slots");
+        assert_eq!(KSan::unfilled_slots(&san_pks, &pk_z2, &sig), vec![3], "The second sanitizer should own one slot");
+
+        //Test that a sanitizer can fill its own slots
+        let san_opts = SanitizeOptions { append: vec!["comment".to_string()], ..SanitizeOptions::new(&m, &[]) };
+        let sigp = KSan::sanitize_with_options(&pp, &sk_z1, &pk_s, &pk_z1, &san_pks, &san_opts, &sig).unwrap();
+        let mp = vec!["report".to_string(), "comment".to_string()];
+        assert_eq!(sigp.s_ss.filled, vec![1], "The filled slot should be recorded");
+        assert!(KSan::verify(&pp, &pk_s, &san_pks, &mp, &sigp).unwrap(), "Verification of a filled slot failed");
+        let (b, _) = KSan::verify_batch(&pp, &pk_s, std::slice::from_ref(&san_pks), std::slice::from_ref(&mp), 
+            std::slice::from_ref(&sigp)).unwrap();
+        assert!(b, "Batch verification of a filled slot failed");
+        assert!(!KSan::verify(&pp, &pk_s, &san_pks, &m, &sigp).unwrap(), "Verification without the filled slot should fail");
+        let pr: Proof = KSan::prove(&pp, &sk_s, &pk_s, &san_pks, &mp, &sigp, None).unwrap();
+        let d = KSan::judge(&pp, &pk_s, &san_pks, &mp, &sigp, &pr, None).unwrap();
+        assert_eq!(d, 'Z', "Judge should blame the sanitizers for a filled slot");
+
+        //Test that the slots of another sanitizer and extra slots cannot be filled
+        let modif = vec![Mod { i: 1, m: "forged".to_string() }];
+        let r = KSan::sanitize(&pp, &sk_z2, &pk_s, &pk_z2, &san_pks, &m, &modif, &sig);
+        assert!(r.is_err(), "Filling the slot of another sanitizer should fail");
+        let san_opts = SanitizeOptions { append: vec!["a".to_string(), "b".to_string()], ..SanitizeOptions::new(&m, &[]) };
+        let r = KSan::sanitize_with_options(&pp, &sk_z2, &pk_s, &pk_z2, &san_pks, &san_opts, &sig);
+        assert!(r.is_err(), "Appending more blocks than unfilled slots should fail");
+
+        //Test that the filled slots and the owners are bound into the signature
+        let mut sig_false = sigp.clone();
+        sig_false.s_ss.filled = vec![1, 2];
+        let m_false = vec!["report".to_string(), "comment".to_string(), "forged".to_string()];
+        assert!(!KSan::verify(&pp, &pk_s, &san_pks, &m_false, &sig_false).unwrap(), 
+            "Verification should fail if an adversary marks a slot as filled");
+        let mut sig_false = sigp.clone();
+        sig_false.s_ss.filled = Vec::new();
+        assert!(!KSan::verify(&pp, &pk_s, &san_pks, &m, &sig_false).unwrap(), 
+            "Verification should fail if an adversary marks a filled slot as unfilled");
+        let mut sig_false = sig.clone();
+        sig_false.s_ss.slots = vec![1, 1, 0];
+        assert!(!KSan::verify(&pp, &pk_s, &san_pks, &m, &sig_false).unwrap(), 
+            "Verification should fail if an adversary changes the owners of the slots");
+    }
+    #[test]
+    fn test_iut_limits() {
+        let (pp, sk_s, pk_s, [(sk_z1, pk_z1), (sk_z2, pk_z2)]) = fixture(3);
+        let san_pks = vec![pk_z1.clone(), pk_z2.clone()];
+        let adm = vec![vec![true, false], vec![true, false]];
+        let m = vec!["draft".to_string(), "footer".to_string()];
+        let sig = KSan::sign_with_limit(&pp, &sk_s, &pk_s, &san_pks, &m, &adm, 1).unwrap();
+        assert!(KSan::verify(&pp, &pk_s, &san_pks, &m, &sig).unwrap(), "Verification of a limited signature failed");
+        let r = KSan::sign_with_limit(&pp, &sk_s, &pk_s, &san_pks, &m, &adm, 0);
+        assert!(r.is_err(), "A limit of zero should be rejected");
+        let pr: Proof = KSan::prove(&pp, &sk_s, &pk_s, &san_pks, &m, &sig, None).unwrap();
+        let d = KSan::judge(&pp, &pk_s, &san_pks, &m, &sig, &pr, None).unwrap();
+        assert_eq!(d, 'S', "Judge should blame the signer for a limited signature it produced");
+
+        //Test that a sanitizer can sanitize up to the limit
+        let modif = vec![Mod { i: 0, m: "v1".to_string() }];
+        let sig1 = KSan::sanitize(&pp, &sk_z1, &pk_s, &pk_z1, &san_pks, &m, &modif, &sig).unwrap();
+        let m1 = vec!["v1".to_string(), "footer".to_string()];
+        assert!(KSan::verify(&pp, &pk_s, &san_pks, &m1, &sig1).unwrap(), "Verification of a limited sanitization failed");
+        let (b, _) = KSan::verify_batch(&pp, &pk_s, std::slice::from_ref(&san_pks), std::slice::from_ref(&m1), std::slice::from_ref(&sig1)).unwrap();
+        assert!(b, "Batch verification of a limited sanitization failed");
+        let pr: Proof = KSan::prove(&pp, &sk_s, &pk_s, &san_pks, &m1, &sig1, None).unwrap();
+        let d = KSan::judge(&pp, &pk_s, &san_pks, &m1, &sig1, &pr, None).unwrap();
+        assert_eq!(d, 'Z', "Judge should blame the sanitizers for a limited sanitization");
+        let modif = vec![Mod { i: 0, m: "v2".to_string() }];
+        let r = KSan::sanitize(&pp, &sk_z1, &pk_s, &pk_z1, &san_pks, &m1, &modif, &sig1);
+        assert!(r.is_err(), "Sanitizing over the limit should fail");
+        let r = KSan::sanitize_with_options(&pp, &sk_z1, &pk_s, &pk_z1, &san_pks, &SanitizeOptions::new(&m1, &modif), &sig1);
+        assert!(r.is_err(), "Sanitizing over the limit with options should fail");
+
+        //Test that the limit is per sanitizer
+        let sig2 = KSan::sanitize(&pp, &sk_z2, &pk_s, &pk_z2, &san_pks, &m, &modif, &sig).unwrap();
+        let m2 = vec!["v2".to_string(), "footer".to_string()];
+        assert!(KSan::verify(&pp, &pk_s, &san_pks, &m2, &sig2).unwrap(), "Verification of another sanitizer's sanitization failed");
+
+        //Test that a count used twice on different versions exposes the sanitizer
+        let sig_b = KSan::sanitize(&pp, &sk_z1, &pk_s, &pk_z1, &san_pks, &m, &modif, &sig).unwrap();
+        let pk = KSan::judge_overuse(&pp, &pk_s, &san_pks, &sig1, &sig_b).unwrap();
+        assert!(pk.is_some() && pk.unwrap().pkp == pk_z1.pkp, "Judge should identify the sanitizer that reused a count");
+        let pk = KSan::judge_overuse(&pp, &pk_s, &san_pks, &sig1, &sig2).unwrap();
+        assert!(pk.is_none(), "Judge should not identify a sanitizer within its limit");
+
+        //Test that the log and the limit are bound into the signature
+        let mut sig_false = sig1.clone();
+        sig_false.s_ss.tags = Vec::new();
+        assert!(!KSan::verify(&pp, &pk_s, &san_pks, &m1, &sig_false).unwrap(), 
+            "Verification should fail if an adversary removes the log");
+        let mut sig_false = sig1.clone();
+        sig_false.s_ss.tags[0].c = 2;
+        assert!(!KSan::verify(&pp, &pk_s, &san_pks, &m1, &sig_false).unwrap(), 
+            "Verification should fail if an adversary changes a logged count");
+        let mut sig_false = sig1.clone();
+        sig_false.s_ss.limit.as_mut().unwrap().max = 2;
+        assert!(!KSan::verify(&pp, &pk_s, &san_pks, &m1, &sig_false).unwrap(), 
+            "Verification should fail if an adversary changes the limit");
+    }
+    #[test]
+    fn test_iut_windows() {
+        let (pp, sk_s, pk_s, [(sk_z1, pk_z1), (sk_z2, pk_z2)]) = fixture(3);
+        let san_pks = vec![pk_z1.clone(), pk_z2.clone()];
+        let adm = vec![vec![true, false], vec![true, false]];
+        let m = vec!["embargoed".to_string(), "footer".to_string()];
+        let windows = vec![Some(Window { from: 100, until: 200 }), None];
+        let opts = SignOptions { windows, now: Some(50), ..SignOptions::new(m.len()) };
+        let (sig, _td) = KSan::sign_with_options(&pp, &sk_s, &pk_s, &san_pks, &m, &adm, &opts).unwrap();
+        assert!(KSan::verify(&pp, &pk_s, &san_pks, &m, &sig).unwrap(), "Verification of a signature with windows failed");
+        let opts_false = SignOptions { windows: vec![None], now: Some(50), ..SignOptions::new(m.len()) };
+        let r = KSan::sign_with_options(&pp, &sk_s, &pk_s, &san_pks, &m, &adm, &opts_false);
+        assert!(r.is_err(), "Signing with a window count different from the sanitizer count should fail");
+
+        //Test that a sanitizer can sanitize within its window
+        let modif = vec![Mod { i: 0, m: "published".to_string() }];
+        let r = KSan::sanitize(&pp, &sk_z1, &pk_s, &pk_z1, &san_pks, &m, &modif, &sig);
+        assert!(r.is_err(), "Sanitizing a signature with windows without a time should fail");
+        let san_opts = SanitizeOptions { now: Some(150), ..SanitizeOptions::new(&m, &modif) };
+        let sig1 = KSan::sanitize_with_options(&pp, &sk_z1, &pk_s, &pk_z1, &san_pks, &san_opts, &sig).unwrap();
+        let m1 = vec!["published".to_string(), "footer".to_string()];
+        assert!(KSan::verify(&pp, &pk_s, &san_pks, &m1, &sig1).unwrap(), "Verification of a sanitization within the window failed");
+        let (b, _) = KSan::verify_batch(&pp, &pk_s, std::slice::from_ref(&san_pks), std::slice::from_ref(&m1), std::slice::from_ref(&sig1)).unwrap();
+        assert!(b, "Batch verification of a sanitization within the window failed");
+        assert!(KSan::verify_at(&pp, &pk_s, &san_pks, &m1, &sig1, 160).unwrap(), "Verification at a later time failed");
+        assert!(!KSan::verify_at(&pp, &pk_s, &san_pks, &m1, &sig1, 120).unwrap(), 
+            "Verification should fail for a sanitization dated after the clock");
+        let pr: Proof = KSan::prove(&pp, &sk_s, &pk_s, &san_pks, &m1, &sig1, None).unwrap();
+        let d = KSan::judge(&pp, &pk_s, &san_pks, &m1, &sig1, &pr, None).unwrap();
+        assert_eq!(d, 'Z', "Judge should blame the sanitizers for a sanitization within the window");
+
+        //Test that the rights expire with the window
+        let san_opts = SanitizeOptions { now: Some(250), ..SanitizeOptions::new(&m, &modif) };
+        let r = KSan::sanitize_with_options(&pp, &sk_z1, &pk_s, &pk_z1, &san_pks, &san_opts, &sig);
+        assert!(r.is_err(), "Sanitizing after the window should fail");
+        let san_opts = SanitizeOptions { now: Some(250), ..SanitizeOptions::new(&m1, &modif) };
+        let r = KSan::sanitize_with_options(&pp, &sk_z1, &pk_s, &pk_z1, &san_pks, &san_opts, &sig1);
+        assert!(r.is_err(), "Sanitizing a sanitized signature after the window should fail");
+        let san_opts = SanitizeOptions { now: Some(50), ..SanitizeOptions::new(&m, &modif) };
+        let r = KSan::sanitize_with_options(&pp, &sk_z1, &pk_s, &pk_z1, &san_pks, &san_opts, &sig);
+        assert!(r.is_err(), "Sanitizing before the window should fail");
+        let san_opts = SanitizeOptions { now: Some(250), ..SanitizeOptions::new(&m, &modif) };
+        let sig2 = KSan::sanitize_with_options(&pp, &sk_z2, &pk_s, &pk_z2, &san_pks, &san_opts, &sig).unwrap();
+        assert!(KSan::verify(&pp, &pk_s, &san_pks, &m1, &sig2).unwrap(), "Verification of a sanitizer without window failed");
+
+        //Test that the time and the windows are bound into the signature
+        let mut sig_false = sig1.clone();
+        sig_false.s_ss.time = Some(250);
+        assert!(!KSan::verify(&pp, &pk_s, &san_pks, &m1, &sig_false).unwrap(), 
+            "Verification should fail if an adversary changes the time of a sanitization");
+        let mut sig_false = sig1.clone();
+        sig_false.s_ss.windows[0] = Some(Window { from: 0, until: 1000 });
+        assert!(!KSan::verify(&pp, &pk_s, &san_pks, &m1, &sig_false).unwrap(), 
+            "Verification should fail if an adversary changes a window");
+    }
+
+    #[test]
+    fn test_iut_thresholds() {
+        let (pp, sk_s, pk_s, [(sk_z1, pk_z1), (sk_z2, pk_z2), (sk_z3, pk_z3), (sk_z4, pk_z4)]) = fixture(3);
+        let san_pks = vec![pk_z1.clone(), pk_z2.clone(), pk_z3.clone(), pk_z4.clone()];
+        let adm = vec![vec![true, true], vec![false, false], vec![false, false], vec![false, false]];
+        let m = vec!["amount: 1000".to_string(), "notes".to_string()];
+        let groups = vec![Some(Group { t: 2, members: vec![0, 1, 2] }), None];
+        let sig = KSan::sign_with_thresholds(&pp, &sk_s, &pk_s, &san_pks, &m, &adm, &groups).unwrap();
+        assert!(KSan::verify(&pp, &pk_s, &san_pks, &m, &sig).unwrap(), "Verification of a signature with thresholds failed");
+        let r = KSan::sign_with_thresholds(&pp, &sk_s, &pk_s, &san_pks, &m, &adm, 
+            &[Some(Group { t: 2, members: vec![0, 0, 1] }), None]);
+        assert!(r.is_err(), "Signing with a group that repeats a member should fail");
+
+        //Test that no sanitizer can modify a threshold block alone
+        let modif = vec![Mod { i: 0, m: "amount: 2000".to_string() }];
+        let r = KSan::sanitize(&pp, &sk_z1, &pk_s, &pk_z1, &san_pks, &m, &modif, &sig);
+        assert!(r.is_err(), "A member should not modify a threshold block alone");
+        let san_opts = SanitizeOptions::new(&m, &modif);
+        let r = KSan::sanitize_partial(&pp, &sk_z1, &pk_z1, &san_pks, &san_opts, &[0], &sig);
+        assert!(r.is_err(), "A partial adaptation with fewer signers than the threshold should fail");
+        let r = KSan::sanitize_partial(&pp, &sk_z4, &pk_z4, &san_pks, &san_opts, &[0, 3], &sig);
+        assert!(r.is_err(), "A partial adaptation by a sanitizer out of the group should fail");
+
+        //Test that t members can modify the block, the VRS signature being made by any sanitizer
+        let p1 = KSan::sanitize_partial(&pp, &sk_z1, &pk_z1, &san_pks, &san_opts, &[0, 2], &sig).unwrap();
+        let p3 = KSan::sanitize_partial(&pp, &sk_z3, &pk_z3, &san_pks, &san_opts, &[0, 2], &sig).unwrap();
+        let opts_p = SanitizeOptions { partials: vec![p1.clone()], ..san_opts.clone() };
+        let r = KSan::sanitize_with_options(&pp, &sk_z4, &pk_s, &pk_z4, &san_pks, &opts_p, &sig);
+        assert!(r.is_err(), "A threshold sanitization with t - 1 partial adaptations should fail");
+        let partials = vec![p1.clone(), p3.clone()];
+        let opts_p = SanitizeOptions { partials: partials.clone(), ..san_opts.clone() };
+        let sig1 = KSan::sanitize_with_options(&pp, &sk_z4, &pk_s, &pk_z4, &san_pks, &opts_p, &sig).unwrap();
+        let m1 = vec!["amount: 2000".to_string(), "notes".to_string()];
+        assert!(KSan::verify(&pp, &pk_s, &san_pks, &m1, &sig1).unwrap(), "Verification of a threshold sanitization failed");
+        let (b, _) = KSan::verify_batch(&pp, &pk_s, std::slice::from_ref(&san_pks), std::slice::from_ref(&m1), std::slice::from_ref(&sig1)).unwrap();
+        assert!(b, "Batch verification of a threshold sanitization failed");
+        let pr: Proof = KSan::prove(&pp, &sk_s, &pk_s, &san_pks, &m1, &sig1, None).unwrap();
+        let d = KSan::judge(&pp, &pk_s, &san_pks, &m1, &sig1, &pr, None).unwrap();
+        assert_eq!(d, 'Z', "Judge should blame the sanitizers for a threshold sanitization");
+
+        //Test that the other blocks are sanitized as usual in the same sanitization
+        let modif2 = vec![Mod { i: 0, m: "amount: 1500".to_string() }, Mod { i: 1, m: "paid".to_string() }];
+        let san_opts2 = SanitizeOptions::new(&m, &modif2);
+        let p1 = KSan::sanitize_partial(&pp, &sk_z1, &pk_z1, &san_pks, &san_opts2, &[0, 1], &sig).unwrap();
+        let p2 = KSan::sanitize_partial(&pp, &sk_z2, &pk_z2, &san_pks, &san_opts2, &[0, 1], &sig).unwrap();
+        let partials2 = vec![p2.clone(), p1.clone()];
+        let opts_p = SanitizeOptions { partials: partials2.clone(), ..san_opts2.clone() };
+        let sig2 = KSan::sanitize_with_options(&pp, &sk_z1, &pk_s, &pk_z1, &san_pks, &opts_p, &sig).unwrap();
+        let m2 = vec!["amount: 1500".to_string(), "paid".to_string()];
+        assert!(KSan::verify(&pp, &pk_s, &san_pks, &m2, &sig2).unwrap(), "Verification of a mixed sanitization failed");
+        let opts_p = SanitizeOptions { partials: partials2.clone(), ..san_opts2.clone() };
+        let r = KSan::sanitize_with_options(&pp, &sk_z2, &pk_s, &pk_z2, &san_pks, &opts_p, &sig);
+        assert!(r.is_err(), "A sanitizer without rights on the other blocks should not modify them");
+
+        //Test that partial adaptations for another modification or other signers are rejected
+        let modif3 = vec![Mod { i: 0, m: "amount: 9000".to_string() }];
+        let opts_p = SanitizeOptions { partials: partials.clone(), ..SanitizeOptions::new(&m, &modif3) };
+        let r = KSan::sanitize_with_options(&pp, &sk_z4, &pk_s, &pk_z4, &san_pks, &opts_p, &sig);
+        assert!(r.is_err(), "Partial adaptations for another modification should fail");
+        let mixed = vec![partials[0].clone(), p2];
+        let opts_p = SanitizeOptions { partials: mixed, ..san_opts.clone() };
+        let r = KSan::sanitize_with_options(&pp, &sk_z4, &pk_s, &pk_z4, &san_pks, &opts_p, &sig);
+        assert!(r.is_err(), "Partial adaptations for different signers should fail");
+        let mut p_false = partials.clone();
+        p_false[1].s_bls[0] = p_false[1].s_bls[0] + p_false[0].s_bls[0];
+        let opts_p = SanitizeOptions { partials: p_false, ..san_opts.clone() };
+        let r = KSan::sanitize_with_options(&pp, &sk_z4, &pk_s, &pk_z4, &san_pks, &opts_p, &sig);
+        assert!(r.is_err(), "A wrong partial adaptation should fail");
+
+        //Test that the groups and the shares are bound into the signature
+        let mut sig_false = sig1.clone();
+        sig_false.s_ss.thresholds[0].as_mut().unwrap().group.t = 1;
+        assert!(!KSan::verify(&pp, &pk_s, &san_pks, &m1, &sig_false).unwrap(), 
+            "Verification should fail if an adversary changes the threshold of a block");
+        let mut sig_false = sig1.clone();
+        sig_false.s_ss.thresholds[0].as_mut().unwrap().shares.swap(0, 1);
+        assert!(!KSan::verify(&pp, &pk_s, &san_pks, &m1, &sig_false).unwrap(), 
+            "Verification should fail if an adversary changes the shares of a block");
+    }
+
+    #[test]
+    fn test_iut_sign_options() {
+        let (pp, sk_s, pk_s, [(sk_z1, pk_z1), (_sk_z2, pk_z2), (_sk_z3, pk_z3)]) = fixture(3);
+        let san_pks = vec![pk_z1.clone(), pk_z2.clone()];
+        let adm = vec![vec![false, true], vec![false, false]];
+        let set: Vec<String> = ["draft", "approved"].iter().map(|v| v.to_string()).collect();
+        let m = vec!["form-42".to_string(), "draft".to_string()];
+
+        //Test that the options can be combined
+        let mut opts = SignOptions::new(m.len());
+        opts.values[1] = Some(set.clone());
+        opts.limit = Some(1);
+        opts.policy[0] = Policy { modify: false, redact: false };
+        let (sig, _td) = KSan::sign_with_options(&pp, &sk_s, &pk_s, &san_pks, &m, &adm, &opts).unwrap();
+        assert!(KSan::verify(&pp, &pk_s, &san_pks, &m, &sig).unwrap(), "Verification of a signature with options failed");
+        let modif = vec![Mod { i: 1, m: "approved".to_string() }];
+        let san_opts = SanitizeOptions { values: opts.values.clone(), ..SanitizeOptions::new(&m, &modif) };
+        let sigp = KSan::sanitize_with_options(&pp, &sk_z1, &pk_s, &pk_z1, &san_pks, &san_opts, &sig).unwrap();
+        let mp = vec!["form-42".to_string(), "approved".to_string()];
+        assert!(KSan::verify(&pp, &pk_s, &san_pks, &mp, &sigp).unwrap(), "Verification of a sanitization with options failed");
+        let opts_false = SignOptions { limit: Some(0), ..SignOptions::new(m.len()) };
+        let r = KSan::sign_with_options(&pp, &sk_s, &pk_s, &san_pks, &m, &adm, &opts_false);
+        assert!(r.is_err(), "Signing with a limit of 0 should fail");
+        let opts_false = SignOptions { windows: vec![Some(Window { from: 100, until: 200 }), None], ..SignOptions::new(m.len()) };
+        let r = KSan::sign_with_options(&pp, &sk_s, &pk_s, &san_pks, &m, &adm, &opts_false);
+        assert!(r.is_err(), "Signing with windows without a time should fail");
+
+        //Test that slots and thresholds cannot be extended or revoked
+        let opts_s = SignOptions { slots: vec![1, 0], ..SignOptions::new(m.len()) };
+        let (sig_s, td_s) = KSan::sign_with_options(&pp, &sk_s, &pk_s, &san_pks, &m, &adm, &opts_s).unwrap();
+        let opts_t = SignOptions { groups: vec![None, Some(Group { t: 2, members: vec![0, 1] })], ..SignOptions::new(m.len()) };
+        let (sig_t, td_t) = KSan::sign_with_options(&pp, &sk_s, &pk_s, &san_pks, &m, &adm, &opts_t).unwrap();
+        for (sig_o, td_o) in [(&sig_s, &td_s), (&sig_t, &td_t)] {
+            let ext = Extension { pk_z: pk_z3.clone(), adm_z: vec![false, true], td: td_o.clone() };
+            let r = KSan::extend_sanitizers(&pp, &sk_s, &pk_s, &san_pks, &m, sig_o, &ext);
+            assert!(r.is_err(), "Extending a signature with slots or thresholds should fail");
+            let rev = Revocation { pk_z: pk_z2.clone(), td: td_o.clone() };
+            let r = KSan::revoke(&pp, &sk_s, &pk_s, &san_pks, &m, sig_o, &rev);
+            assert!(r.is_err(), "Revoking a sanitizer of a signature with slots or thresholds should fail");
+        }
+
+        //Test that a sanitizer of a signature with windows cannot delegate
+        let opts_w = SignOptions { windows: vec![Some(Window { from: 100, until: 200 }), None], now: Some(50), ..SignOptions::new(m.len()) };
+        let (sig_w, _td_w) = KSan::sign_with_options(&pp, &sk_s, &pk_s, &san_pks, &m, &adm, &opts_w).unwrap();
+        assert!(KSan::verify(&pp, &pk_s, &san_pks, &m, &sig_w).unwrap(), "Verification of a signature with windows failed");
+        let r = KSan::delegate(&pp, &sk_z1, &pk_z1, &san_pks, &pk_z3, &[1], &sig_w);
+        assert!(r.is_err(), "Delegating from a signature with windows should fail");
+    }
+
+    #[test]
+    fn test_iut_admissible_blocks() {
+        let (pp, sk_s, pk_s, [(sk_z1, pk_z1), (sk_z2, pk_z2), (sk_z3, pk_z3), (sk_d, pk_d)]) = fixture(3);
+        let san_pks = vec![pk_z1.clone(), pk_z2.clone(), pk_z3.clone()];
+        let adm = vec![vec![true, true], vec![false, true], vec![false, false]];
+        let m = vec!["a".to_string(), "b".to_string()];
+        let sig = KSan::sign(&pp, &sk_s, &pk_s, &san_pks, &m, &adm).unwrap();
+
+        //Test that each sanitizer gets the blocks of its column of adm
+        assert_eq!(KSan::admissible_blocks(&pp, &sk_z1, &pk_z1, &san_pks, &sig).unwrap(), vec![0, 1], "Wrong blocks for z1");
+        assert_eq!(KSan::admissible_blocks(&pp, &sk_z2, &pk_z2, &san_pks, &sig).unwrap(), vec![1], "Wrong blocks for z2");
+        assert!(KSan::admissible_blocks(&pp, &sk_z3, &pk_z3, &san_pks, &sig).unwrap().is_empty(), "z3 should have no block");
+        assert!(KSan::admissible_blocks(&pp, &sk_d, &pk_d, &san_pks, &sig).is_err(), 
+            "A key that is neither a sanitizer nor a delegate should fail");
+
+        //Test that the listed blocks are the ones that sanitize accepts
+        let modif = vec![Mod { i: 0, m: "a'".to_string() }];
+        assert!(KSan::sanitize(&pp, &sk_z2, &pk_s, &pk_z2, &san_pks, &m, &modif, &sig).is_err(), 
+            "A block that is not listed should not be sanitizable");
+
+        //Test that a delegate gets its delegated blocks
+        let sigd = KSan::delegate(&pp, &sk_z1, &pk_z1, &san_pks, &pk_d, &[0], &sig).unwrap();
+        assert_eq!(KSan::admissible_blocks(&pp, &sk_d, &pk_d, &san_pks, &sigd).unwrap(), vec![0], 
+            "Wrong blocks for the delegate");
+        assert_eq!(KSan::admissible_blocks(&pp, &sk_z1, &pk_z1, &san_pks, &sigd).unwrap(), vec![0, 1], 
+            "The delegation should not change the blocks of z1");
+    }
 }
\ No newline at end of file
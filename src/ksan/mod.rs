@@ -1,3 +1,5 @@
 pub mod hash;
+pub mod common;
 pub mod fsv;
-pub mod iut;
\ No newline at end of file
+pub mod iut;
+pub mod size;
\ No newline at end of file
@@ -0,0 +1,64 @@
+use num_bigint::BigInt;
+use crate::blocks::pke::Ciphertext;
+use ark_serialize::CanonicalSerialize;
+use crate::blocks::vrs::{VRSSignature, VRSProof, LogEqProof};
+use crate::blocks::hybrid::HybridCiphertext;
+
+//Encoded byte size of a signature, proof or key, broken down by component. Integers are counted with their
+//minimal big-endian encoding and curve points with their compressed encoding.
+#[derive(Clone, Debug, Default)]
+pub struct SizeReport {
+    pub components: Vec<(String, usize)>
+}
+
+impl SizeReport {
+    pub fn new() -> Self {
+        SizeReport { components: Vec::new() }
+    }
+
+    pub fn add(&mut self, name: &str, size: usize) {
+        for c in self.components.iter_mut() {
+            if c.0 == name {
+                c.1 += size;
+                return;
+            }
+        }
+        self.components.push((name.to_string(), size));
+    }
+
+    pub fn get(&self, name: &str) -> usize {
+        self.components.iter().filter(|c| c.0 == name).map(|c| c.1).sum()
+    }
+
+    pub fn total(&self) -> usize {
+        self.components.iter().map(|c| c.1).sum()
+    }
+}
+
+pub fn bigint_size(x: &BigInt) -> usize {
+    x.to_bytes_be().1.len()
+}
+
+pub fn ciphertext_size(c: &Ciphertext) -> usize {
+    c.to_bytes().len()
+}
+
+pub fn hybrid_size(c: &HybridCiphertext) -> usize {
+    c.ek.len() + c.ct.len()
+}
+
+pub fn ark_size<T: CanonicalSerialize>(x: &T) -> usize {
+    x.compressed_size()
+}
+
+pub fn vrs_signature_size(s: &VRSSignature) -> usize {
+    bigint_size(&s.r) + bigint_size(&s.z) + log_eq_size(&s.p)
+}
+
+pub fn vrs_proof_size(p: &VRSProof) -> usize {
+    bigint_size(&p.z) + log_eq_size(&p.p)
+}
+
+fn log_eq_size(p: &[LogEqProof]) -> usize {
+    p.iter().map(|x| bigint_size(&x.r) + bigint_size(&x.s) + bigint_size(&x.c) + bigint_size(&x.l)).sum()
+}
\ No newline at end of file
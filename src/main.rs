@@ -1,5 +1,9 @@
+#[allow(dead_code)]
 mod blocks;
+#[allow(dead_code)]
 mod ksan;
+mod par;
+mod stats;
 
 use crate::blocks::mercurial::Mercurial;
 use crate::ksan::fsv::ksan::KSan as FSVKSan;
@@ -20,12 +24,14 @@ use crate::ksan::iut::params::{
     SanitizerSecretKey as IUTSanitizerSecretKey
 };
 
+use blocks::bls::*;
 use blocks::chash::*;
 use blocks::eqs::*;
 use blocks::sig::*;
 use blocks::pke::*;
+use blocks::vrs::*;
 
-use num_bigint::{BigInt, BigUint};
+use num_bigint::{BigInt, BigUint, RandBigInt};
 use rand::{distributions::Alphanumeric, thread_rng, Rng};
 use ark_bls12_381::{Bls12_381, Fr, G1Affine};
 use ark_ff::UniformRand;
@@ -36,6 +42,9 @@ use std::fs::{File, create_dir_all};
 use std::io::Write;
 use std::path::Path;
 use std::env;
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::Serialize;
+use stats::Stats;
 
 use mercurial_signature::{Curve, CurveBls12_381};
 
@@ -47,6 +56,12 @@ fn main() {
     let mut call_perf = false;
     let mut call_perf_sec = false;
     let mut call_op_time = false;
+    let mut call_fixed_base = false;
+    let mut call_perf_size = false;
+    let mut call_bench = false;
+    let mut call_perf_eqs = false;
+    let mut call_op_cost = false;
+    let mut bench = BenchConfig::perf();
 
     let mut i = 1;
     while i < args.len() {
@@ -66,27 +81,94 @@ fn main() {
             "--op-time" => {
                 call_op_time = true;
             }
-            _ => {}
+            "--fixed-base" => {
+                call_fixed_base = true;
+            }
+            "--perf-size" => {
+                call_perf_size = true;
+            }
+            "--bench" => {
+                call_bench = true;
+            }
+            "--perf-eqs" => {
+                call_perf_eqs = true;
+            }
+            "--op-cost" => {
+                call_op_cost = true;
+            }
+            "--scheme" | "--n" | "--k" | "--fixed-n" | "--fixed-k" | "--num-adm" | "--bits" | "--warm-up" | "--out" | "--eqs" => {
+                if i + 1 >= args.len() {
+                    eprintln!("Missing value for {}", args[i]);
+                    std::process::exit(1);
+                }
+                if let Err(e) = set_bench_option(&mut bench, &args[i], &args[i + 1]) {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                }
+                i += 1;
+            }
+            _ => {
+                eprintln!("Unknown option: {}", args[i]);
+                print_usage();
+                std::process::exit(1);
+            }
         }
         i += 1;
     }
+    if call_bench || call_perf_eqs {
+        if let Err(e) = bench.check() {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    }
+    if call_perf_eqs && bench.n_range.len() < 2 {
+        eprintln!("The EQS comparison needs at least two vector lengths");
+        std::process::exit(1);
+    }
 
     println!("Number of executions: {}", num_exec);
-    println!("perf: {}, perf-sec: {}, op-time: {}", call_perf, call_perf_sec, call_op_time);
+    println!("perf: {}, perf-sec: {}, op-time: {}, fixed-base: {}, perf-size: {}, bench: {}, perf-eqs: {}, op-cost: {}", 
+        call_perf, call_perf_sec, call_op_time, call_fixed_base, call_perf_size, call_bench, call_perf_eqs, call_op_cost);
 
     if call_op_time {
         get_op_time(num_exec);
     }
     if call_perf {
-        test_perf(num_exec);
+        run_bench(&BenchConfig::perf(), num_exec);
     }
     if call_perf_sec {
-        test_perf_sec(num_exec);
+        run_bench(&BenchConfig::perf_sec(), num_exec);
+    }
+    if call_bench {
+        println!("{:?}", bench);
+        run_bench(&bench, num_exec);
+    }
+    if call_fixed_base {
+        test_perf_fixed_base(num_exec);
     }
+    if call_perf_size {
+        test_perf_size();
+    }
+    if call_perf_eqs {
+        test_perf_eqs_backends(num_exec, bench.warm_up, &bench.n_range);
+    }
+    if call_op_cost {
+        match bench.eqs.as_str() {
+            "mercurial" => test_op_cost::<Mercurial>(&bench, num_exec),
+            _ => test_op_cost::<EQS>(&bench, num_exec),
+        }
+    }
+}
+
+fn print_usage() {
+    eprintln!("Usage: ksan [--num-exec N] [--perf] [--perf-sec] [--op-time] [--fixed-base] [--perf-size] [--bench]");
+    eprintln!("            [--perf-eqs] [--op-cost] [--scheme fsv|iut|all] [--n RANGE] [--k RANGE] [--fixed-n N]");
+    eprintln!("            [--fixed-k K] [--num-adm A] [--bits B] [--warm-up W] [--out FILE] [--eqs eqs|mercurial]");
+    eprintln!("RANGE is \"a,b,c\", \"start:end[:step]\" or \"none\".");
 }
 
 fn get_op_time(num_exec: usize) {
-    let mut bg = EQS::setup(3, &"k-SAN test".to_string());
+    let bg = EQS::setup(3, &"k-SAN test".to_string());
     let mut g1_exec_time:Vec<f64> = Vec::new();
     let mut g2_exec_time:Vec<f64> = Vec::new();
     let mut pair_exec_time:Vec<f64> = Vec::new();
@@ -104,13 +186,13 @@ fn get_op_time(num_exec: usize) {
     let (ske, pke) = PKE::k_gen(520);
     let mut rng = thread_rng();
     for i in 0..num_exec {
-        let r = Fr::rand(&mut bg.rng);
+        let r = Fr::rand(&mut thread_rng());
         let start = Instant::now();
         let g1 = bg.p1 * r;
         let duration = start.elapsed();
         g1_exec_time.push(duration.as_micros() as f64);
 
-        let r = Fr::rand(&mut bg.rng);
+        let r = Fr::rand(&mut thread_rng());
         let start = Instant::now();
         let g2 = bg.p2 * r;
         let duration = start.elapsed();
@@ -209,343 +291,765 @@ fn get_op_time(num_exec: usize) {
             a_enc.round(),
             a_dec.round(),
             a_mul.round());
-    
-    test_perf_mercurial(num_exec, a_p);
-    
-    test_perf_eqs(num_exec, a_p);
-    
 }
 
-fn test_perf_eqs(num_exec: usize, _a_p: f64) {
-    let mut pair_cost = 0.0;
-    let mut last_verify = 0.0;
-    let mut last_n = 0.0;
+//Least-squares fit of y = a*x + b.
+fn fit_linear(xs: &[f64], ys: &[f64]) -> (f64, f64) {
+    let len = xs.len() as f64;
+    let mx = xs.iter().sum::<f64>() / len;
+    let my = ys.iter().sum::<f64>() / len;
+    let sxy: f64 = xs.iter().zip(ys.iter()).map(|(x, y)| (x - mx) * (y - my)).sum();
+    let sxx: f64 = xs.iter().map(|x| (x - mx) * (x - mx)).sum();
+    let a = if sxx == 0.0 { 0.0 } else { sxy / sxx };
+    (a, my - a * mx)
+}
 
-    for i in 0..5 {
+//Compares the EQS backends on sign, verify and chg_rep for vectors of each length in sizes, and fits the
+//verify time (in µs) to "a*n + b", a being the cost of the pairings added by each vector element.
+fn test_perf_eqs_backends(num_exec: usize, warm_up: usize, sizes: &[usize]) {
+    let data_dir = Path::new("data");
+    create_dir_all(data_dir).unwrap();
+    let out_file_path = data_dir.join("perf_eqs.txt");
+    let mut out_file = File::create(out_file_path).unwrap();
+
+    let backends = [
+        ("EQS", "blue", test_perf_eqs(num_exec, warm_up, sizes)),
+        ("Mercurial", "red", test_perf_mercurial(num_exec, warm_up, sizes))
+    ];
+    for (i, name) in ["Sign", "Verify", "ChgRep"].iter().enumerate() {
+        writeln!(out_file, "EQS_{}:", name.to_uppercase()).unwrap();
+        for (backend, color, times) in backends.iter() {
+            let coordinates: String = times.iter().map(|t| format!("({}, {})", t.0, t.1[i])).collect();
+            writeln!(out_file, "\\addplot[color={},mark=o]", color).unwrap();
+            writeln!(out_file, "coordinates {{{}}};", coordinates).unwrap();
+            writeln!(out_file, "%\\addlegendentry{{{}}}", backend).unwrap();
+        }
+        writeln!(out_file, "\n").unwrap();
+    }
+    for (backend, _color, times) in backends.iter() {
+        let xs: Vec<f64> = times.iter().map(|t| t.0 as f64).collect();
+        let ys: Vec<f64> = times.iter().map(|t| t.1[1]).collect();
+        let (a, b) = fit_linear(&xs, &ys);
+        println!("{} - Verify cost: {}*n + {}", backend, a.round(), b.round());
+        writeln!(out_file, "{} - Verify cost: {}*n + {}", backend, a.round(), b.round()).unwrap();
+    }
+}
+
+//Mean sign, verify and chg_rep times (µs) of the delegatable_credentials EQS for each vector length.
+fn test_perf_eqs(num_exec: usize, warm_up: usize, sizes: &[usize]) -> Vec<(usize, [f64; 3])> {
+    let mut res = Vec::with_capacity(sizes.len());
+    for n in sizes.iter() {
         let mut s_exec_time:Vec<f64> = Vec::new();
         let mut v_exec_time:Vec<f64> = Vec::new();
         let mut c_exec_time:Vec<f64> = Vec::new();
-        let n = 3 + i * 3;
-        let mut bg = EQS::setup(n, &"k-SAN test".to_string());
-        let (sk, pk) = EQS::k_gen(&mut bg);
+        let bg = EQS::setup(*n as u32, &"k-SAN test".to_string());
+        let (sk, pk) = EQS::k_gen(&bg);
         let mut m: Vec<G1Affine> = Vec::new();
-        for _j in 0..n {
-            let g1 = Fr::rand(&mut bg.rng);
+        for _j in 0..*n {
+            let g1 = Fr::rand(&mut thread_rng());
             m.push((bg.p1 * g1).into_affine());
         }
 
-        for _i in 0..num_exec {
+        for _i in 0..warm_up + num_exec {
             let start = Instant::now();
-            let s = EQS::sign(&mut bg, &sk, &m);
+            let s = EQS::sign(&bg, &sk, &m);
             let duration = start.elapsed();
             s_exec_time.push(duration.as_micros() as f64);
 
             let start = Instant::now();
-            let _b = EQS::verify(&mut bg, &pk, &m, &s);
+            let b = EQS::verify(&bg, &pk, &m, &s);
             let duration = start.elapsed();
             v_exec_time.push(duration.as_micros() as f64);
+            assert!(b, "EQS verify failed");
 
             let r = BigInt::from(50);
             let start = Instant::now();
-            let (_sp, _mp) = EQS::chg_rep(&mut bg, &pk, &m, &s, &r);
+            let (_sp, _mp) = EQS::chg_rep(&bg, &pk, &m, &s, &r);
             let duration = start.elapsed();
             c_exec_time.push(duration.as_micros() as f64);
         }
 
-        let x = average(v_exec_time) as f64;
-        println!("n: {}, S: {}, V: {}, C: {}",
-            n,
-            average(s_exec_time),
-            x,
-            average(c_exec_time));
-        
-        if last_verify != 0.0 {
-            pair_cost += (x - last_verify) / 3.0;
-        }
-        last_verify = x;
-        last_n = n as f64;
-    }
-    pair_cost = (pair_cost / 5.0).round();
-    let verify_constant = last_verify - (last_n * pair_cost);
-    // println!("Verify cost: {}*n + {}", (pair_cost / a_p).round(), (verify_constant / a_p).round());
-    println!("Verify cost: {}*n + {}", pair_cost.round(), verify_constant.round());
+        let t = [s_exec_time, v_exec_time, c_exec_time].map(|t| Stats::new(&t[warm_up..]).mean);
+        println!("EQS - n: {}, S: {}, V: {}, C: {}", n, t[0].round(), t[1].round(), t[2].round());
+        res.push((*n, t));
+    }
+    res
 }
 
-fn test_perf_mercurial(num_exec: usize, _a_p: f64) {
-    let mut pair_cost = 0.0;
-    let mut last_verify = 0.0;
-    let mut last_n = 0.0;
-
-    for i in 0..5 {
+//Mean sign, verify and chg_rep times (µs) of the mercurial-signature EQS for each vector length.
+fn test_perf_mercurial(num_exec: usize, warm_up: usize, sizes: &[usize]) -> Vec<(usize, [f64; 3])> {
+    let mut res = Vec::with_capacity(sizes.len());
+    for n in sizes.iter() {
         let mut s_exec_time:Vec<f64> = Vec::new();
         let mut v_exec_time:Vec<f64> = Vec::new();
         let mut c_exec_time:Vec<f64> = Vec::new();
-        let n = 3 + i * 3;
-        let mut bg = Mercurial::setup(n, &"k-SAN test".to_string());
-        let (mut sk, pk) = Mercurial::k_gen(&mut bg);
+        let bg = Mercurial::setup(*n as u32, &"k-SAN test".to_string());
+        let (mut sk, pk) = Mercurial::k_gen(&bg);
         let mut m: Vec<<CurveBls12_381 as Curve>::G1> = Vec::new();
-        for _j in 0..n {
-            let g1 = <CurveBls12_381 as Curve>::Fr::rand(&mut bg.rng);
+        for _j in 0..*n {
+            let g1 = <CurveBls12_381 as Curve>::Fr::rand(&mut thread_rng());
             m.push(bg.pp.p1 * g1);
         }
 
-        for _i in 0..num_exec {
+        for _i in 0..warm_up + num_exec {
             let start = Instant::now();
-            let mut s = Mercurial::sign(&mut bg, &mut sk, &m);
+            let mut s = Mercurial::sign(&bg, &mut sk, &m);
             let duration = start.elapsed();
             s_exec_time.push(duration.as_micros() as f64);
 
             let start = Instant::now();
-            let _b = Mercurial::verify(&mut bg, &pk, &m, &s);
+            let b = Mercurial::verify(&bg, &pk, &m, &s);
             let duration = start.elapsed();
             v_exec_time.push(duration.as_micros() as f64);
+            assert!(b, "Mercurial verify failed");
 
             let r = BigInt::from(50);
             let start = Instant::now();
-            let (mut _sp, mut _mp) = Mercurial::chg_rep(&mut bg, &pk, &mut m, &mut s, &r);
+            let (mut _sp, mut _mp) = Mercurial::chg_rep(&bg, &pk, &mut m, &mut s, &r);
             let duration = start.elapsed();
             c_exec_time.push(duration.as_micros() as f64);
         }
 
-        let x = average(v_exec_time) as f64;
-        println!("n: {}, S: {}, V: {}, C: {}",
+        let t = [s_exec_time, v_exec_time, c_exec_time].map(|t| Stats::new(&t[warm_up..]).mean);
+        println!("Mercurial - n: {}, S: {}, V: {}, C: {}", n, t[0].round(), t[1].round(), t[2].round());
+        res.push((*n, t));
+    }
+    res
+}
+
+//Parameters of the benchmark driver. The n sweep uses fixed_k sanitizers and the k sweep uses fixed_n blocks,
+//an empty range skips the sweep.
+#[derive(Clone, Debug)]
+struct BenchConfig {
+    fsv: bool,
+    iut: bool,
+    n_range: Vec<usize>,
+    k_range: Vec<usize>,
+    fixed_n: usize,
+    fixed_k: usize,
+    num_adm: usize,
+    bits: usize,
+    warm_up: usize,
+    out: String,
+    eqs: String
+}
+
+impl BenchConfig {
+    //Parameters of `--perf`, also the defaults of `--bench`.
+    fn perf() -> Self {
+        BenchConfig {
+            fsv: true,
+            iut: true,
+            n_range: vec![3, 6, 9, 12, 15],
+            k_range: vec![3, 6, 9, 12, 15],
+            fixed_n: 9,
+            fixed_k: 9,
+            num_adm: 3,
+            bits: 512,
+            warm_up: 0,
+            out: "data/perf.txt".to_string(),
+            eqs: "eqs".to_string()
+        }
+    }
+
+    //Parameters of `--perf-sec`.
+    fn perf_sec() -> Self {
+        BenchConfig {
+            fsv: true,
+            iut: true,
+            n_range: vec![5, 7, 9, 11, 13, 15],
+            k_range: Vec::new(),
+            fixed_n: 5,
+            fixed_k: 5,
+            num_adm: 5,
+            bits: 2048,
+            warm_up: 0,
+            out: "data/perf_sec.txt".to_string(),
+            eqs: "eqs".to_string()
+        }
+    }
+
+    fn check(&self) -> Result<(), String> {
+        if !self.fsv && !self.iut {
+            return Err("No scheme selected".to_string());
+        }
+        if self.num_adm == 0 {
+            return Err("The number of admissible blocks must be positive".to_string());
+        }
+        if self.n_range.iter().any(|n| *n < self.num_adm) || (!self.k_range.is_empty() && self.fixed_n < self.num_adm) {
+            return Err("The number of admissible blocks is larger than the number of blocks".to_string());
+        }
+        if self.k_range.contains(&0) || (!self.n_range.is_empty() && self.fixed_k == 0) {
+            return Err("The number of sanitizers must be positive".to_string());
+        }
+        if self.bits < 512 {
+            return Err("The security level must be at least 512 bits".to_string());
+        }
+        Ok(())
+    }
+}
+
+//Parse "a,b,c" or "start:end:step" (end included), "none" is the empty range.
+fn parse_range(s: &str) -> Result<Vec<usize>, String> {
+    if s == "none" {
+        return Ok(Vec::new());
+    }
+    let parse = |x: &str| x.trim().parse::<usize>().map_err(|_| format!("Invalid number: {}", x));
+    if s.contains(':') {
+        let p: Vec<&str> = s.split(':').collect();
+        if p.len() < 2 || p.len() > 3 {
+            return Err(format!("Invalid range: {}", s));
+        }
+        let start = parse(p[0])?;
+        let end = parse(p[1])?;
+        let step = if p.len() == 3 { parse(p[2])? } else { 1 };
+        if step == 0 || end < start {
+            return Err(format!("Invalid range: {}", s));
+        }
+        return Ok((start..=end).step_by(step).collect());
+    }
+    s.split(',').map(parse).collect()
+}
+
+fn set_bench_option(cfg: &mut BenchConfig, name: &str, value: &str) -> Result<(), String> {
+    let parse = |x: &str| x.parse::<usize>().map_err(|_| format!("Invalid value for {}: {}", name, x));
+    match name {
+        "--scheme" => {
+            match value {
+                "fsv" => { cfg.fsv = true; cfg.iut = false; }
+                "iut" => { cfg.fsv = false; cfg.iut = true; }
+                "all" => { cfg.fsv = true; cfg.iut = true; }
+                _ => return Err(format!("Unknown scheme: {}", value)),
+            }
+        }
+        "--n" => cfg.n_range = parse_range(value)?,
+        "--k" => cfg.k_range = parse_range(value)?,
+        "--fixed-n" => cfg.fixed_n = parse(value)?,
+        "--fixed-k" => cfg.fixed_k = parse(value)?,
+        "--num-adm" => cfg.num_adm = parse(value)?,
+        "--bits" => cfg.bits = parse(value)?,
+        "--warm-up" => cfg.warm_up = parse(value)?,
+        "--out" => cfg.out = value.to_string(),
+        "--eqs" => {
+            if value != "eqs" && value != "mercurial" {
+                return Err(format!("Unknown EQS backend: {}", value));
+            }
+            cfg.eqs = value.to_string();
+        }
+        _ => return Err(format!("Unknown option: {}", name)),
+    }
+    Ok(())
+}
+
+fn write_plots(out_file: &mut File, title: &str, style: &str, plots: &Vec<(&str, &str, String)>) {
+    writeln!(out_file, "{}:", title).unwrap();
+    for (color, name, coordinates) in plots {
+        writeln!(out_file, "\\addplot[color={},{}]", color, style).unwrap();
+        writeln!(out_file, "coordinates {{{}}};", coordinates).unwrap();
+        writeln!(out_file, "%\\addlegendentry{{{}}}", name).unwrap();
+    }
+    writeln!(out_file, "\n").unwrap();
+}
+
+//One measured point of the benchmark driver, in milliseconds.
+#[derive(Serialize)]
+struct BenchRecord {
+    scheme: String,
+    algorithm: String,
+    n: usize,
+    k: usize,
+    num_adm: usize,
+    stats: Stats
+}
+
+#[derive(Serialize)]
+struct BenchMetadata {
+    host: String,
+    os: String,
+    arch: String,
+    cpus: usize,
+    parallel: bool,
+    version: String,
+    timestamp: u64,
+    bits_chash_vrs: usize,
+    bits_pke: usize,
+    n_range: Vec<usize>,
+    k_range: Vec<usize>,
+    fixed_n: usize,
+    fixed_k: usize,
+    num_adm: usize,
+    num_exec: usize,
+    warm_up: usize,
+    eqs: String,
+    //Blocks modified by each measured sanitization.
+    sanitized_blocks: usize,
+    unit: String
+}
+
+#[derive(Serialize)]
+struct BenchReport {
+    metadata: BenchMetadata,
+    results: Vec<BenchRecord>
+}
+
+impl BenchMetadata {
+    fn new(cfg: &BenchConfig, num_exec: usize) -> Self {
+        let host = env::var("HOSTNAME").ok()
+            .or_else(|| std::fs::read_to_string("/etc/hostname").ok().map(|h| h.trim().to_string()))
+            .unwrap_or_default();
+        BenchMetadata {
+            host,
+            os: env::consts::OS.to_string(),
+            arch: env::consts::ARCH.to_string(),
+            cpus: std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+            parallel: cfg!(feature = "parallel"),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            timestamp: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+            bits_chash_vrs: cfg.bits,
+            bits_pke: cfg.bits + 8,
+            n_range: cfg.n_range.clone(),
+            k_range: cfg.k_range.clone(),
+            fixed_n: cfg.fixed_n,
+            fixed_k: cfg.fixed_k,
+            num_adm: cfg.num_adm,
+            num_exec,
+            warm_up: cfg.warm_up,
+            eqs: cfg.eqs.clone(),
+            sanitized_blocks: 1,
+            unit: "ms".to_string()
+        }
+    }
+}
+
+fn write_csv(path: &Path, results: &Vec<BenchRecord>) {
+    let mut out_file = File::create(path).unwrap();
+    writeln!(out_file, "scheme,algorithm,n,k,num_adm,count,mean,median,std,min,max,p5,p25,p75,p95,p99").unwrap();
+    for r in results {
+        let s = &r.stats;
+        writeln!(out_file, "{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+            r.scheme, r.algorithm, r.n, r.k, r.num_adm, s.count, s.mean, s.median, s.std, s.min, s.max,
+            s.p5, s.p25, s.p75, s.p95, s.p99).unwrap();
+    }
+}
+
+fn record(results: &mut Vec<BenchRecord>, scheme: &str, n: usize, k: usize, num_adm: usize, samples: Vec<(&str, Vec<f64>)>) {
+    for (algorithm, s) in samples {
+        results.push(BenchRecord {
+            scheme: scheme.to_string(),
+            algorithm: algorithm.to_string(),
             n,
-            average(s_exec_time),
-            x,
-            average(c_exec_time));
-        
-        if last_verify != 0.0 {
-            pair_cost += (x - last_verify) / 3.0;
-        }
-        last_verify = x;
-        last_n = n as f64;
-    }
-    pair_cost = (pair_cost / 5.0).round();
-    let verify_constant = last_verify - (last_n * pair_cost);
-    //println!("Verify cost: {}*n + {}", (pair_cost / a_p).round(), (verify_constant / a_p).round());
-    println!("Verify cost: {}*n + {}", pair_cost.round(), verify_constant.round());
+            k,
+            num_adm,
+            stats: Stats::new(&s)
+        });
+    }
 }
 
-fn test_perf(num_exec: usize) {
-    let sizes = [3, 6, 9, 12, 15];
+fn bench_iut<E: EQSScheme>(cfg: &BenchConfig, num_exec: usize, out_file: &mut File, results: &mut Vec<BenchRecord>) {
+    let iut_style = "mark=triangle*, dashed, mark options={solid}";
+    let scheme = if cfg.eqs == "mercurial" { "IUT-Mercurial" } else { "IUT" };
+    //One set of parameters for all the points, sized for the largest message
+    let max_n = cfg.n_range.iter().copied().chain([cfg.fixed_n]).max().unwrap();
+    let secp = IUTSecParams { bits_vrs: cfg.bits, bits_pke: cfg.bits + 8, n: max_n as u32, dst: "k-SAN test".to_string() };
+    let pp = IUTKSan::setup_with::<E>(&secp).unwrap();
+    let sweeps = [("IUT_N", &cfg.n_range, true), ("IUT_K", &cfg.k_range, false)];
+    for (title, range, sweep_n) in sweeps {
+        if range.is_empty() {
+            continue;
+        }
+        let mut times = vec![String::new(); 5];
+        for s in range.iter() {
+            let (n, k) = if sweep_n { (*s, cfg.fixed_k) } else { (cfg.fixed_n, *s) };
+            let (sig_time, san_time, ver_time, prf_time, jdg_time) =
+                measure_ksan_iut_efficiency(n, k, num_exec, cfg.warm_up, &pp, cfg.num_adm);
+            let samples = vec![("Sign", sig_time), ("Sanitize", san_time), ("Verify", ver_time),
+                ("Prove", prf_time), ("Judge", jdg_time)];
+            for (t, x) in times.iter_mut().zip(samples.iter()) {
+                t.push_str(format!("({}, {})", s, average(x.1.clone())).as_str());
+            }
+            record(results, scheme, n, k, cfg.num_adm, samples);
+            println!("{} - s: {}", title, s);
+        }
+        let plots = vec![
+            ("blue", "Sign", times[0].clone()),
+            ("red", "Sanitize", times[1].clone()),
+            ("green!50!black", "Verify", times[2].clone()),
+            ("orange", "Prove", times[3].clone()),
+            ("purple", "Judge", times[4].clone())
+        ];
+        write_plots(out_file, title, iut_style, &plots);
+    }
+}
+
+//Writes the pgfplots file given by cfg.out (means), and the JSON and CSV files with the same stem.
+fn run_bench(cfg: &BenchConfig, num_exec: usize) {
+    let out_path = Path::new(&cfg.out);
+    if let Some(dir) = out_path.parent() {
+        create_dir_all(dir).unwrap();
+    }
+    let mut out_file = File::create(out_path).unwrap();
+    let fsv_style = "mark=o";
+    let mut results: Vec<BenchRecord> = Vec::new();
+
+    if cfg.fsv {
+        let secp = FSVSecParams { bits_chash_vrs: cfg.bits, bits_pke: cfg.bits + 8 };
+        let mut pp = FSVKSan::setup(&secp).unwrap();
+        let sweeps = [("FSV_N", &cfg.n_range, true), ("FSV_K", &cfg.k_range, false)];
+        for (title, range, sweep_n) in sweeps {
+            if range.is_empty() {
+                continue;
+            }
+            let mut times = vec![String::new(); 4];
+            for s in range.iter() {
+                let (n, k) = if sweep_n { (*s, cfg.fixed_k) } else { (cfg.fixed_n, *s) };
+                let (sig_time, san_time, ver_time, jdg_time) =
+                    measure_ksan_fsv_efficiency(n, k, num_exec, cfg.warm_up, &mut pp, cfg.num_adm);
+                let samples = vec![("Sign", sig_time), ("Sanitize", san_time), ("Verify", ver_time), ("Judge", jdg_time)];
+                for (t, x) in times.iter_mut().zip(samples.iter()) {
+                    t.push_str(format!("({}, {})", s, average(x.1.clone())).as_str());
+                }
+                record(&mut results, "FSV", n, k, cfg.num_adm, samples);
+                println!("{} - s: {}", title, s);
+            }
+            let plots = vec![
+                ("blue", "Sign", times[0].clone()),
+                ("red", "Sanitize", times[1].clone()),
+                ("green!50!black", "Verify", times[2].clone()),
+                ("purple", "Judge", times[3].clone())
+            ];
+            write_plots(&mut out_file, title, fsv_style, &plots);
+        }
+    }
+
+    if cfg.iut {
+        match cfg.eqs.as_str() {
+            "mercurial" => bench_iut::<Mercurial>(cfg, num_exec, &mut out_file, &mut results),
+            _ => bench_iut::<EQS>(cfg, num_exec, &mut out_file, &mut results),
+        }
+    }
+
+    let report = BenchReport { metadata: BenchMetadata::new(cfg, num_exec), results };
+    let json_file = File::create(out_path.with_extension("json")).unwrap();
+    serde_json::to_writer_pretty(json_file, &report).unwrap();
+    write_csv(&out_path.with_extension("csv"), &report.results);
+}
+
+//Timings (ms) of the building blocks with cfg.bits: CHash, BLS, EQS on vectors of n+1 elements for n in
+//cfg.n_range (the IUT blocks and the sanitizer keys) and VRS on rings of k+1 keys for k in cfg.k_range (the
+//sanitizers and the signer).
+fn test_op_cost<E: EQSScheme>(cfg: &BenchConfig, num_exec: usize) {
+    let data_dir = Path::new("data");
+    create_dir_all(data_dir).unwrap();
+    let out_path = data_dir.join("op_cost.txt");
+    let mut out_file = File::create(&out_path).unwrap();
+    let mut results: Vec<BenchRecord> = Vec::new();
+    let mut rng = thread_rng();
+    let one = BigInt::from(1);
+
+    // CHash
+    let (p, q, g) = CHash::setup(cfg.bits);
+    let (sk, pk) = CHash::k_gen(&p, &q, &g, None);
+    let mut hash_time: Vec<f64> = Vec::new();
+    let mut adapt_time: Vec<f64> = Vec::new();
+    let mut check_time: Vec<f64> = Vec::new();
+    for _x in 0..cfg.warm_up + num_exec {
+        let m = rng.gen_bigint_range(&one, &q);
+        let mp = rng.gen_bigint_range(&one, &q);
+        let start = Instant::now();
+        let (h, r) = CHash::hash(&p, &q, &g, &pk, &m, None);
+        hash_time.push(start.elapsed().as_secs_f64() * 1000.0);
+        let start = Instant::now();
+        let rp = CHash::adapt(&p, &q, &g, &sk, &m, &r, &mp);
+        adapt_time.push(start.elapsed().as_secs_f64() * 1000.0);
+        let start = Instant::now();
+        let b = CHash::check(&p, &g, &pk, &mp, &rp, &h, None);
+        check_time.push(start.elapsed().as_secs_f64() * 1000.0);
+        assert!(b, "CHash check failed");
+    }
+    let samples = [("Hash", hash_time), ("Adapt", adapt_time), ("Check", check_time)]
+        .map(|(a, t)| (a, t[cfg.warm_up..].to_vec())).to_vec();
+    record(&mut results, "CHash", 0, 0, 0, samples);
+
+    // BLS
+    let bg = EQS::setup(2, &"k-SAN test".to_string());
+    let (_sk1, sk2, pk1, pk2) = BLS::k_gen(&bg);
+    let mut sign_time: Vec<f64> = Vec::new();
+    let mut verify_time: Vec<f64> = Vec::new();
+    for _x in 0..cfg.warm_up + num_exec {
+        let m = generate_random_string(10);
+        let start = Instant::now();
+        let s = BLS::sign(&bg, &sk2, &m);
+        sign_time.push(start.elapsed().as_secs_f64() * 1000.0);
+        let start = Instant::now();
+        let b = BLS::verify(&bg, &pk1, &pk2, &m, &s);
+        verify_time.push(start.elapsed().as_secs_f64() * 1000.0);
+        assert!(b, "BLS verify failed");
+    }
+    let samples = [("Sign", sign_time), ("Verify", verify_time)]
+        .map(|(a, t)| (a, t[cfg.warm_up..].to_vec())).to_vec();
+    record(&mut results, "BLS", 0, 0, 0, samples);
+
+    // EQS
+    for n in cfg.n_range.iter() {
+        let bg = EQS::setup(*n as u32 + 1, &"k-SAN test".to_string());
+        let pp = E::setup(&bg);
+        let (sk, pk) = E::k_gen(&bg, &pp);
+        let m: Vec<G1Affine> = (0..n + 1).map(|_| (bg.p1 * Fr::rand(&mut thread_rng())).into_affine()).collect();
+        let mut sign_time: Vec<f64> = Vec::new();
+        let mut verify_time: Vec<f64> = Vec::new();
+        let mut chg_rep_time: Vec<f64> = Vec::new();
+        for _x in 0..cfg.warm_up + num_exec {
+            let start = Instant::now();
+            let s = E::sign(&bg, &pp, &sk, &m);
+            sign_time.push(start.elapsed().as_secs_f64() * 1000.0);
+            let start = Instant::now();
+            let b = E::verify(&bg, &pp, &pk, &m, &s);
+            verify_time.push(start.elapsed().as_secs_f64() * 1000.0);
+            assert!(b, "EQS verify failed");
+            let r = Fr::rand(&mut thread_rng()).to_string().parse::<BigInt>().unwrap();
+            let start = Instant::now();
+            let (_sp, _mp) = E::chg_rep(&bg, &pp, &pk, &m, &s, &r);
+            chg_rep_time.push(start.elapsed().as_secs_f64() * 1000.0);
+        }
+        let samples = [("Sign", sign_time), ("Verify", verify_time), ("ChgRep", chg_rep_time)]
+            .map(|(a, t)| (a, t[cfg.warm_up..].to_vec())).to_vec();
+        record(&mut results, "EQS", *n, 0, 0, samples);
+        println!("EQS - n: {}", n);
+    }
 
+    // VRS
+    let (p, q, g) = VRS::setup(cfg.bits);
+    for k in cfg.k_range.iter() {
+        let mut ring: Vec<BigInt> = Vec::with_capacity(k + 1);
+        let mut sk = BigInt::from(0);
+        for _i in 0..k + 1 {
+            let (skp, pkp) = VRS::kgen(&p, &q, &g, None);
+            ring.push(pkp);
+            sk = skp;
+        }
+        let pk = ring[*k].clone();
+        let mut sign_time: Vec<f64> = Vec::new();
+        let mut verify_time: Vec<f64> = Vec::new();
+        let mut prove_time: Vec<f64> = Vec::new();
+        let mut judge_time: Vec<f64> = Vec::new();
+        for _x in 0..cfg.warm_up + num_exec {
+            let m = generate_random_string(50);
+            let start = Instant::now();
+            let s = VRS::sign(&p, &q, &g, &sk, &ring, &m, None);
+            sign_time.push(start.elapsed().as_secs_f64() * 1000.0);
+            let start = Instant::now();
+            let b = VRS::verify(&p, &q, &g, &ring, &m, &s, None);
+            verify_time.push(start.elapsed().as_secs_f64() * 1000.0);
+            assert!(b, "VRS verify failed");
+            let start = Instant::now();
+            let pr = VRS::prove(&p, &q, &g, &m, &s, &pk, &sk);
+            prove_time.push(start.elapsed().as_secs_f64() * 1000.0);
+            let start = Instant::now();
+            let b = VRS::judge(&p, &q, &g, &m, &s, &pk, &pr);
+            judge_time.push(start.elapsed().as_secs_f64() * 1000.0);
+            assert!(b == Ok(true), "VRS judge failed");
+        }
+        let samples = [("Sign", sign_time), ("Verify", verify_time), ("Prove", prove_time), ("Judge", judge_time)]
+            .map(|(a, t)| (a, t[cfg.warm_up..].to_vec())).to_vec();
+        record(&mut results, "VRS", 0, *k, 0, samples);
+        println!("VRS - k: {}", k);
+    }
+
+    for r in results.iter() {
+        let line = format!("{} {} (n: {}, k: {}): mean {:.3}, median {:.3}, std {:.3}",
+            r.scheme, r.algorithm, r.n, r.k, r.stats.mean, r.stats.median, r.stats.std);
+        println!("{}", line);
+        writeln!(out_file, "{}", line).unwrap();
+    }
+    let report = BenchReport { metadata: BenchMetadata::new(cfg, num_exec), results };
+    let json_file = File::create(out_path.with_extension("json")).unwrap();
+    serde_json::to_writer_pretty(json_file, &report).unwrap();
+    write_csv(&out_path.with_extension("csv"), &report.results);
+}
+
+fn test_perf_fixed_base(num_exec: usize) {
     let data_dir = Path::new("data");
     create_dir_all(data_dir).unwrap();
-    let out_file_path = data_dir.join("perf.txt");
+    let out_file_path = data_dir.join("fixed_base.txt");
     let mut out_file = File::create(out_file_path).unwrap();
 
-    let mut fsv_sig_times_n = String::new();
-    let mut fsv_san_times_n = String::new();
-    let mut fsv_ver_times_n = String::new();
-    let mut fsv_jdg_times_n = String::new();
+    for bits in [512, 2048] {
+        let secp = FSVSecParams { bits_chash_vrs: bits, bits_pke: bits + 8 };
+        let mut pp = FSVKSan::setup(&secp).unwrap();
+        let start = Instant::now();
+        FSVKSan::precompute(&mut pp, 5).unwrap();
+        let pre_time = start.elapsed().as_micros();
+
+        let mut exp_time: Vec<f64> = Vec::new();
+        let mut exp_fb_time: Vec<f64> = Vec::new();
+        let mut hash_time: Vec<f64> = Vec::new();
+        let mut hash_fb_time: Vec<f64> = Vec::new();
+        let mut check_time: Vec<f64> = Vec::new();
+        let mut check_fb_time: Vec<f64> = Vec::new();
+        let mut sign_time: Vec<f64> = Vec::new();
+        let mut sign_fb_time: Vec<f64> = Vec::new();
+        let mut verify_time: Vec<f64> = Vec::new();
+        let mut verify_fb_time: Vec<f64> = Vec::new();
+
+        let fb_chash = pp.fb_chash.as_ref();
+        let fb_vrs = pp.fb_vrs.as_ref();
+        let (_skch, pkch) = CHash::k_gen(&pp.p, &pp.q, &pp.g, None);
+        let mut ring: Vec<BigInt> = Vec::new();
+        let mut sk = BigInt::from(0);
+        for _ in 0..5 {
+            let (skp, pkp) = VRS::kgen(&pp.p, &pp.q, &pp.g, None);
+            ring.push(pkp);
+            sk = skp;
+        }
+        let mut rng = thread_rng();
+        for i in 0..num_exec {
+            let e = rng.gen_bigint_range(&BigInt::from(1), &pp.q);
+            let start = Instant::now();
+            let _x = pp.g.modpow(&e, &pp.p);
+            exp_time.push(start.elapsed().as_micros() as f64);
+            let start = Instant::now();
+            let _x = fb_chash.unwrap().pow(&e);
+            exp_fb_time.push(start.elapsed().as_micros() as f64);
+
+            let start = Instant::now();
+            let (h, r) = CHash::hash(&pp.p, &pp.q, &pp.g, &pkch, &e, None);
+            hash_time.push(start.elapsed().as_micros() as f64);
+            let start = Instant::now();
+            let (_h, _r) = CHash::hash(&pp.p, &pp.q, &pp.g, &pkch, &e, fb_chash);
+            hash_fb_time.push(start.elapsed().as_micros() as f64);
 
-    let mut fsv_sig_times_k = String::new();
-    let mut fsv_san_times_k = String::new();
-    let mut fsv_ver_times_k = String::new();
-    let mut fsv_jdg_times_k = String::new();
+            let start = Instant::now();
+            let _b = CHash::check(&pp.p, &pp.g, &pkch, &e, &r, &h, None);
+            check_time.push(start.elapsed().as_micros() as f64);
+            let start = Instant::now();
+            let _b = CHash::check(&pp.p, &pp.g, &pkch, &e, &r, &h, fb_chash);
+            check_fb_time.push(start.elapsed().as_micros() as f64);
 
-    let mut iut_sig_times_n = String::new();
-    let mut iut_san_times_n = String::new();
-    let mut iut_ver_times_n = String::new();
-    let mut iut_prf_times_n = String::new();
-    let mut iut_jdg_times_n = String::new();
+            let m = generate_random_string(50);
+            let start = Instant::now();
+            let s = VRS::sign(&pp.p, &pp.q, &pp.g, &sk, &ring, &m, None);
+            sign_time.push(start.elapsed().as_micros() as f64);
+            let start = Instant::now();
+            let _s = VRS::sign(&pp.p, &pp.q, &pp.g, &sk, &ring, &m, fb_vrs);
+            sign_fb_time.push(start.elapsed().as_micros() as f64);
 
-    let mut iut_sig_times_k = String::new();
-    let mut iut_san_times_k = String::new();
-    let mut iut_ver_times_k = String::new();
-    let mut iut_prf_times_k = String::new();
-    let mut iut_jdg_times_k = String::new();
+            let start = Instant::now();
+            let _b = VRS::verify(&pp.p, &pp.q, &pp.g, &ring, &m, &s, None);
+            verify_time.push(start.elapsed().as_micros() as f64);
+            let start = Instant::now();
+            let _b = VRS::verify(&pp.p, &pp.q, &pp.g, &ring, &m, &s, fb_vrs);
+            verify_fb_time.push(start.elapsed().as_micros() as f64);
 
-    let secp = FSVSecParams { bits_chash_vrs: 512, bits_pke: 520 };
-    let mut pp = FSVKSan::setup(&secp).unwrap();
+            println!("num_exec: {}", i);
+        }
 
-    // FSV
-    for s in sizes {
-        let (sig_time, san_time, ver_time, jdg_time) = measure_ksan_fsv_efficiency(s, 9, num_exec, &mut pp, 3);
-        fsv_sig_times_n.push_str(format!("({}, {})", s, sig_time).as_str());
-        fsv_san_times_n.push_str(format!("({}, {})", s, san_time).as_str());
-        fsv_ver_times_n.push_str(format!("({}, {})", s, ver_time).as_str());
-        fsv_jdg_times_n.push_str(format!("({}, {})", s, jdg_time).as_str());
-        println!("FSV-N - s: {}", s);
-        let (sig_time, san_time, ver_time, jdg_time) = measure_ksan_fsv_efficiency(9, s, num_exec, &mut pp, 3);
-        fsv_sig_times_k.push_str(format!("({}, {})", s, sig_time).as_str());
-        fsv_san_times_k.push_str(format!("({}, {})", s, san_time).as_str());
-        fsv_ver_times_k.push_str(format!("({}, {})", s, ver_time).as_str());
-        fsv_jdg_times_k.push_str(format!("({}, {})", s, jdg_time).as_str());
-        println!("FSV-K - s: {}", s);
-    }
-
-    let mut secp = IUTSecParams { bits_vrs: 512, bits_pke: 520, n: 1 as u32, dst: "k-SAN test".to_string() };
-    let mut pp = IUTKSan::setup(&secp).unwrap();
-
-    // IUT
-    for s in sizes {
-        secp.n = s as u32;
-        let new_bg = EQS::setup(secp.n + 1, &secp.dst);
-        pp.bg = new_bg;
-        let (sig_time, san_time, ver_time, prf_time, jdg_time) = measure_ksan_iut_efficiency(s, 9, num_exec, &mut pp, 3);
-        iut_sig_times_n.push_str(format!("({}, {})", s, sig_time).as_str());
-        iut_san_times_n.push_str(format!("({}, {})", s, san_time).as_str());
-        iut_ver_times_n.push_str(format!("({}, {})", s, ver_time).as_str());
-        iut_prf_times_n.push_str(format!("({}, {})", s, prf_time).as_str());
-        iut_jdg_times_n.push_str(format!("({}, {})", s, jdg_time).as_str());
-        println!("IUT-N - s: {}", s);
-        secp.n = 9 as u32;
-        let new_bg = EQS::setup(secp.n + 1, &secp.dst);
-        pp.bg = new_bg;
-        let (sig_time, san_time, ver_time, prf_time, jdg_time) = measure_ksan_iut_efficiency(9, s, num_exec, &mut pp, 3);
-        iut_sig_times_k.push_str(format!("({}, {})", s, sig_time).as_str());
-        iut_san_times_k.push_str(format!("({}, {})", s, san_time).as_str());
-        iut_ver_times_k.push_str(format!("({}, {})", s, ver_time).as_str());
-        iut_prf_times_k.push_str(format!("({}, {})", s, prf_time).as_str());
-        iut_jdg_times_k.push_str(format!("({}, {})", s, jdg_time).as_str());
-        println!("IUT-K - s: {}", s);
-    }
-
-    writeln!(out_file, "FSV_N:").unwrap();
-    writeln!(out_file, "\\addplot[color=blue,mark=o]").unwrap();
-    writeln!(out_file, "coordinates {{{}}};", fsv_sig_times_n).unwrap();
-    writeln!(out_file, "%\\addlegendentry{{Sign}}").unwrap();
-    writeln!(out_file, "\\addplot[color=red,mark=o]").unwrap();
-    writeln!(out_file, "coordinates {{{}}};", fsv_san_times_n).unwrap();
-    writeln!(out_file, "%\\addlegendentry{{Sanitize}}").unwrap();
-    writeln!(out_file, "\\addplot[color=green!50!black,mark=o]").unwrap();
-    writeln!(out_file, "coordinates {{{}}};", fsv_ver_times_n).unwrap();
-    writeln!(out_file, "%\\addlegendentry{{Verify}}").unwrap();
-    writeln!(out_file, "\\addplot[color=purple,mark=o]").unwrap();
-    writeln!(out_file, "coordinates {{{}}};", fsv_jdg_times_n).unwrap();
-    writeln!(out_file, "%\\addlegendentry{{Judge}}").unwrap();
-
-    writeln!(out_file, "\n\nFSV_K:").unwrap();
-    writeln!(out_file, "\\addplot[color=blue,mark=o]").unwrap();
-    writeln!(out_file, "coordinates {{{}}};", fsv_sig_times_k).unwrap();
-    writeln!(out_file, "%\\addlegendentry{{Sign}}").unwrap();
-    writeln!(out_file, "\\addplot[color=red,mark=o]").unwrap();
-    writeln!(out_file, "coordinates {{{}}};", fsv_san_times_k).unwrap();
-    writeln!(out_file, "%\\addlegendentry{{Sanitize}}").unwrap();
-    writeln!(out_file, "\\addplot[color=green!50!black,mark=o]").unwrap();
-    writeln!(out_file, "coordinates {{{}}};", fsv_ver_times_k).unwrap();
-    writeln!(out_file, "%\\addlegendentry{{Verify}}").unwrap();
-    writeln!(out_file, "\\addplot[color=purple,mark=o]").unwrap();
-    writeln!(out_file, "coordinates {{{}}};", fsv_jdg_times_k).unwrap();
-    writeln!(out_file, "%\\addlegendentry{{Judge}}").unwrap();
-
-    writeln!(out_file, "\n\nIUT_N:").unwrap();
-    writeln!(out_file, "\\addplot[color=blue,mark=o]").unwrap();
-    writeln!(out_file, "coordinates {{{}}};", iut_sig_times_n).unwrap();
-    writeln!(out_file, "%\\addlegendentry{{Sign}}").unwrap();
-    writeln!(out_file, "\\addplot[color=red,mark=o]").unwrap();
-    writeln!(out_file, "coordinates {{{}}};", iut_san_times_n).unwrap();
-    writeln!(out_file, "%\\addlegendentry{{Sanitize}}").unwrap();
-    writeln!(out_file, "\\addplot[color=green!50!black,mark=o]").unwrap();
-    writeln!(out_file, "coordinates {{{}}};", iut_ver_times_n).unwrap();
-    writeln!(out_file, "%\\addlegendentry{{Verify}}").unwrap();
-    writeln!(out_file, "\\addplot[color=orange,mark=o]").unwrap();
-    writeln!(out_file, "coordinates {{{}}};", iut_prf_times_n).unwrap();
-    writeln!(out_file, "%\\addlegendentry{{Prove}}").unwrap();
-    writeln!(out_file, "\\addplot[color=purple,mark=o]").unwrap();
-    writeln!(out_file, "coordinates {{{}}};", iut_jdg_times_n).unwrap();
-    writeln!(out_file, "%\\addlegendentry{{Judge}}").unwrap();
-
-    writeln!(out_file, "\n\nIUT_K:").unwrap();
-    writeln!(out_file, "\\addplot[color=blue,mark=o]").unwrap();
-    writeln!(out_file, "coordinates {{{}}};", iut_sig_times_k).unwrap();
-    writeln!(out_file, "%\\addlegendentry{{Sign}}").unwrap();
-    writeln!(out_file, "\\addplot[color=red,mark=o]").unwrap();
-    writeln!(out_file, "coordinates {{{}}};", iut_san_times_k).unwrap();
-    writeln!(out_file, "%\\addlegendentry{{Sanitize}}").unwrap();
-    writeln!(out_file, "\\addplot[color=green!50!black,mark=o]").unwrap();
-    writeln!(out_file, "coordinates {{{}}};", iut_ver_times_k).unwrap();
-    writeln!(out_file, "%\\addlegendentry{{Verify}}").unwrap();
-    writeln!(out_file, "\\addplot[color=orange,mark=o]").unwrap();
-    writeln!(out_file, "coordinates {{{}}};", iut_prf_times_k).unwrap();
-    writeln!(out_file, "%\\addlegendentry{{Prove}}").unwrap();
-    writeln!(out_file, "\\addplot[color=purple,mark=o]").unwrap();
-    writeln!(out_file, "coordinates {{{}}};", iut_jdg_times_k).unwrap();
-    writeln!(out_file, "%\\addlegendentry{{Judge}}").unwrap();
+        let results = [
+            ("g^x", average(exp_time), average(exp_fb_time)),
+            ("CHash.Hash", average(hash_time), average(hash_fb_time)),
+            ("CHash.Check", average(check_time), average(check_fb_time)),
+            ("VRS.Sign (ring 5)", average(sign_time), average(sign_fb_time)),
+            ("VRS.Verify (ring 5)", average(verify_time), average(verify_fb_time))
+        ];
+        println!("Bits: {}, precomputation: {}", bits, pre_time);
+        writeln!(out_file, "Bits: {}, precomputation: {}", bits, pre_time).unwrap();
+        for (name, t, t_fb) in results {
+            println!("{}: modpow: {}, fixed-base: {}", name, t, t_fb);
+            writeln!(out_file, "{}: modpow: {}, fixed-base: {}", name, t, t_fb).unwrap();
+        }
 
+        let mut pp_none = pp.clone();
+        pp_none.fb_chash = None;
+        pp_none.fb_vrs = None;
+        let (sig_time, san_time, ver_time, jdg_time) = measure_ksan_fsv_efficiency(5, 5, num_exec, 0, &mut pp_none, 5);
+        let (sig_fb_time, san_fb_time, ver_fb_time, jdg_fb_time) = measure_ksan_fsv_efficiency(5, 5, num_exec, 0, &mut pp, 5);
+        let (sig_time, san_time, ver_time, jdg_time) = (average(sig_time), average(san_time), average(ver_time), average(jdg_time));
+        let (sig_fb_time, san_fb_time, ver_fb_time, jdg_fb_time) =
+            (average(sig_fb_time), average(san_fb_time), average(ver_fb_time), average(jdg_fb_time));
+        println!("FSV (n=5, k=5) - Sign: {} / {}, Sanitize: {} / {}, Verify: {} / {}, Judge: {} / {}",
+            sig_time, sig_fb_time, san_time, san_fb_time, ver_time, ver_fb_time, jdg_time, jdg_fb_time);
+        writeln!(out_file, "FSV (n=5, k=5) - Sign: {} / {}, Sanitize: {} / {}, Verify: {} / {}, Judge: {} / {}\n",
+            sig_time, sig_fb_time, san_time, san_fb_time, ver_time, ver_fb_time, jdg_time, jdg_fb_time).unwrap();
+    }
 }
 
-fn test_perf_sec(num_exec: usize) {
+//Signature sizes (in bytes) with the secure parameters, sweeping n with k = 5 and k with n = 5.
+fn test_perf_size() {
     let sizes = [5, 7, 9, 11, 13, 15];
+    let max_k = 15;
 
     let data_dir = Path::new("data");
     create_dir_all(data_dir).unwrap();
-    let out_file_path = data_dir.join("perf_sec.txt");
+    let out_file_path = data_dir.join("perf_size.txt");
     let mut out_file = File::create(out_file_path).unwrap();
 
-    let mut fsv_sig_times_n = String::new();
-    let mut fsv_san_times_n = String::new();
-    let mut fsv_ver_times_n = String::new();
-    let mut fsv_jdg_times_n = String::new();
-
-    let mut iut_sig_times_n = String::new();
-    let mut iut_san_times_n = String::new();
-    let mut iut_ver_times_n = String::new();
-    let mut iut_prf_times_n = String::new();
-    let mut iut_jdg_times_n = String::new();
+    let mut fsv_n = String::new();
+    let mut fsv_k = String::new();
+    let mut fsv_compact_n = String::new();
+    let mut fsv_compact_k = String::new();
+    let mut iut_n = String::new();
+    let mut iut_k = String::new();
 
     let secp = FSVSecParams { bits_chash_vrs: 2048, bits_pke: 2056 };
-    let mut pp = FSVKSan::setup(&secp).unwrap();
-
-    // FSV
+    let pp = FSVKSan::setup(&secp).unwrap();
+    let (sk_s, pk_s) = FSVKSan::kgen_s(&pp).unwrap();
+    let mut san_pks: Vec<FSVSanitizerPublicKey> = Vec::with_capacity(max_k);
+    for _i in 0..max_k {
+        let (_sk_z, pk_z) = FSVKSan::kgen_z(&pp).unwrap();
+        san_pks.push(pk_z);
+    }
+    let fsv_size = |n: usize, k: usize, compact: bool| {
+        let m: Vec<String> = (0..n).map(|_| generate_random_string(10)).collect();
+        let adm = vec![vec![true; n]; k];
+        let san_pks_k = san_pks[..k].to_vec();
+        let sig = if compact {
+            FSVKSan::sign_compact(&pp, &sk_s, &pk_s, &san_pks_k, &m, &adm).unwrap()
+        } else {
+            FSVKSan::sign(&pp, &sk_s, &pk_s, &san_pks_k, &m, &adm).unwrap()
+        };
+        let size = FSVKSan::signature_size(&sig);
+        println!("FSV{} n: {}, k: {}, {:?}", if compact { " compact" } else { "" }, n, k, size.components);
+        size.total()
+    };
     for s in sizes {
-        let (sig_time, san_time, ver_time, jdg_time) = measure_ksan_fsv_efficiency(s, 5, num_exec, &mut pp, 5);
-        fsv_sig_times_n.push_str(format!("({}, {})", s, sig_time).as_str());
-        fsv_san_times_n.push_str(format!("({}, {})", s, san_time).as_str());
-        fsv_ver_times_n.push_str(format!("({}, {})", s, ver_time).as_str());
-        fsv_jdg_times_n.push_str(format!("({}, {})", s, jdg_time).as_str());
-        println!("FSV-N - s: {}", s);
+        fsv_n.push_str(format!("({}, {})", s, fsv_size(s, 5, false)).as_str());
+        fsv_k.push_str(format!("({}, {})", s, fsv_size(5, s, false)).as_str());
+        fsv_compact_n.push_str(format!("({}, {})", s, fsv_size(s, 5, true)).as_str());
+        fsv_compact_k.push_str(format!("({}, {})", s, fsv_size(5, s, true)).as_str());
     }
 
-    let mut secp = IUTSecParams { bits_vrs: 2048, bits_pke: 2056, n: 1 as u32, dst: "k-SAN test".to_string() };
-    let mut pp = IUTKSan::setup(&secp).unwrap();
-
-    // IUT
+    let secp = IUTSecParams { bits_vrs: 2048, bits_pke: 2056, n: 15, dst: "k-SAN test".to_string() };
+    let pp = IUTKSan::setup(&secp).unwrap();
+    let (sk_s, pk_s) = IUTKSan::kgen_s(&pp).unwrap();
+    let mut san_pks: Vec<IUTSanitizerPublicKey> = Vec::with_capacity(max_k);
+    for _i in 0..max_k {
+        let (_sk_z, pk_z) = IUTKSan::kgen_z(&pp).unwrap();
+        san_pks.push(pk_z);
+    }
+    let iut_size = |n: usize, k: usize| {
+        let m: Vec<String> = (0..n).map(|_| generate_random_string(10)).collect();
+        let adm = vec![vec![true; n]; k];
+        let sig = IUTKSan::sign(&pp, &sk_s, &pk_s, &san_pks[..k], &m, &adm).unwrap();
+        let size = IUTKSan::signature_size(&sig);
+        println!("IUT n: {}, k: {}, {:?}", n, k, size.components);
+        size.total()
+    };
     for s in sizes {
-        secp.n = s as u32;
-        let new_bg = EQS::setup(secp.n + 1, &secp.dst);
-        pp.bg = new_bg;
-        let (sig_time, san_time, ver_time, prf_time, jdg_time) = measure_ksan_iut_efficiency(s, 5, num_exec, &mut pp, 5);
-        iut_sig_times_n.push_str(format!("({}, {})", s, sig_time).as_str());
-        iut_san_times_n.push_str(format!("({}, {})", s, san_time).as_str());
-        iut_ver_times_n.push_str(format!("({}, {})", s, ver_time).as_str());
-        iut_prf_times_n.push_str(format!("({}, {})", s, prf_time).as_str());
-        iut_jdg_times_n.push_str(format!("({}, {})", s, jdg_time).as_str());
-        println!("IUT-N - s: {}", s);
-    }
-
-    writeln!(out_file, "FSV_N:").unwrap();
-    writeln!(out_file, "\\addplot[color=blue,mark=o]").unwrap();
-    writeln!(out_file, "coordinates {{{}}};", fsv_sig_times_n).unwrap();
-    writeln!(out_file, "%\\addlegendentry{{Sign}}").unwrap();
-    writeln!(out_file, "\\addplot[color=red,mark=o]").unwrap();
-    writeln!(out_file, "coordinates {{{}}};", fsv_san_times_n).unwrap();
-    writeln!(out_file, "%\\addlegendentry{{Sanitize}}").unwrap();
-    writeln!(out_file, "\\addplot[color=green!50!black,mark=o]").unwrap();
-    writeln!(out_file, "coordinates {{{}}};", fsv_ver_times_n).unwrap();
-    writeln!(out_file, "%\\addlegendentry{{Verify}}").unwrap();
-    writeln!(out_file, "\\addplot[color=purple,mark=o]").unwrap();
-    writeln!(out_file, "coordinates {{{}}};", fsv_jdg_times_n).unwrap();
-    writeln!(out_file, "%\\addlegendentry{{Judge}}").unwrap();
-
-    writeln!(out_file, "\n\nIUT_N:").unwrap();
-    writeln!(out_file, "\\addplot[color=blue,mark=triangle*, dashed, mark options={{solid}}]").unwrap();
-    writeln!(out_file, "coordinates {{{}}};", iut_sig_times_n).unwrap();
-    writeln!(out_file, "%\\addlegendentry{{Sign}}").unwrap();
-    writeln!(out_file, "\\addplot[color=red,mark=triangle*, dashed, mark options={{solid}}]").unwrap();
-    writeln!(out_file, "coordinates {{{}}};", iut_san_times_n).unwrap();
-    writeln!(out_file, "%\\addlegendentry{{Sanitize}}").unwrap();
-    writeln!(out_file, "\\addplot[color=green!50!black,mark=triangle*, dashed, mark options={{solid}}]").unwrap();
-    writeln!(out_file, "coordinates {{{}}};", iut_ver_times_n).unwrap();
-    writeln!(out_file, "%\\addlegendentry{{Verify}}").unwrap();
-    writeln!(out_file, "\\addplot[color=orange,mark=triangle*, dashed, mark options={{solid}}]").unwrap();
-    writeln!(out_file, "coordinates {{{}}};", iut_prf_times_n).unwrap();
-    writeln!(out_file, "%\\addlegendentry{{Prove}}").unwrap();
-    writeln!(out_file, "\\addplot[color=purple,mark=triangle*, dashed, mark options={{solid}}]").unwrap();
-    writeln!(out_file, "coordinates {{{}}};", iut_jdg_times_n).unwrap();
-    writeln!(out_file, "%\\addlegendentry{{Judge}}").unwrap();
+        iut_n.push_str(format!("({}, {})", s, iut_size(s, 5)).as_str());
+        iut_k.push_str(format!("({}, {})", s, iut_size(5, s)).as_str());
+    }
 
+    for (name, fsv, fsv_compact, iut) in [("N", &fsv_n, &fsv_compact_n, &iut_n), ("K", &fsv_k, &fsv_compact_k, &iut_k)] {
+        writeln!(out_file, "SIZE_{}:", name).unwrap();
+        writeln!(out_file, "\\addplot[color=blue,mark=o]").unwrap();
+        writeln!(out_file, "coordinates {{{}}};", fsv).unwrap();
+        writeln!(out_file, "%\\addlegendentry{{FSV}}").unwrap();
+        writeln!(out_file, "\\addplot[color=red,mark=o]").unwrap();
+        writeln!(out_file, "coordinates {{{}}};", fsv_compact).unwrap();
+        writeln!(out_file, "%\\addlegendentry{{FSV compact}}").unwrap();
+        writeln!(out_file, "\\addplot[color=green!50!black,mark=triangle*, dashed, mark options={{solid}}]").unwrap();
+        writeln!(out_file, "coordinates {{{}}};", iut).unwrap();
+        writeln!(out_file, "%\\addlegendentry{{IUT}}\n\n").unwrap();
+    }
 }
 
 fn generate_random_string(length: usize) -> String {
@@ -557,9 +1061,12 @@ fn generate_random_string(length: usize) -> String {
     return rand_string;
 }
 
-fn measure_ksan_iut_efficiency(n: usize, k: usize, num_exec: usize, mut pp: &mut IUTPublicParams, num_adm: usize) -> (u64, u64, u64, u64, u64) {
+//Sign, sanitize, verify, prove and judge times of IUT (ms).
+type IUTTimes = (Vec<f64>, Vec<f64>, Vec<f64>, Vec<f64>, Vec<f64>);
+
+fn measure_ksan_iut_efficiency<E: EQSScheme>(n: usize, k: usize, num_exec: usize, warm_up: usize, pp: &IUTPublicParams<E>, num_adm: usize) -> IUTTimes {
     //Setup
-    let (sk_s, pk_s) = IUTKSan::kgen_s(&mut pp).unwrap();
+    let (sk_s, pk_s) = IUTKSan::kgen_s(pp).unwrap();
     let mut m: Vec<String> = Vec::with_capacity(n);
     for _j in 0..n {
         m.push(generate_random_string(10));
@@ -571,7 +1078,7 @@ fn measure_ksan_iut_efficiency(n: usize, k: usize, num_exec: usize, mut pp: &mut
         for j in 0..num_adm {
             adm[i][j] = true;
         }
-        let (sk_z, pk_z) = IUTKSan::kgen_z(&mut pp).unwrap();
+        let (sk_z, pk_z) = IUTKSan::kgen_z(pp).unwrap();
         secrets.push(sk_z);
         san_pks.push(pk_z);
     }
@@ -582,51 +1089,51 @@ fn measure_ksan_iut_efficiency(n: usize, k: usize, num_exec: usize, mut pp: &mut
     let mut prf_time: Vec<f64> = Vec::new();
     let mut jdg_time: Vec<f64> = Vec::new();
 
-    for _x in 0..num_exec {
+    //The first warm_up runs are not measured
+    for _x in 0..warm_up + num_exec {
         //Sign
         let start = Instant::now();
-        let mut sig = IUTKSan::sign(&mut pp, &sk_s, &pk_s, &san_pks, &m, &adm).unwrap();
+        let mut sig = IUTKSan::sign(pp, &sk_s, &pk_s, &san_pks, &m, &adm).unwrap();
         let duration = start.elapsed();
-        sig_time.push(duration.as_millis() as f64);
+        sig_time.push(duration.as_secs_f64() * 1000.0);
 
         //Sanitize
         let ns = generate_random_string(10);
         let modif = vec![IUTMod { i: 0, m: ns.clone() }];
         let start = Instant::now();
-        sig = IUTKSan::sanitize(&mut pp, &secrets[0], &pk_s, &san_pks[0], 
+        sig = IUTKSan::sanitize(pp, &secrets[0], &pk_s, &san_pks[0], 
             &san_pks, &m, &modif, &sig).unwrap();
         let duration = start.elapsed();
-        san_time.push(duration.as_millis() as f64);
+        san_time.push(duration.as_secs_f64() * 1000.0);
         m[0] = ns.clone();
 
         //Verify
         let start = Instant::now();
-        let b = IUTKSan::verify(&mut pp, &pk_s, &san_pks, &m, &sig).unwrap();
+        let b = IUTKSan::verify(pp, &pk_s, &san_pks, &m, &sig).unwrap();
         let duration = start.elapsed();
-        ver_time.push(duration.as_millis() as f64);
+        ver_time.push(duration.as_secs_f64() * 1000.0);
         assert!(b, "Verify failed");
 
         //Prove
         let start = Instant::now();
-        let pr: ksan::iut::params::Proof = IUTKSan::prove(&mut pp, &sk_s, &pk_s, &san_pks, &m, &sig, None).unwrap();
+        let pr: ksan::iut::params::Proof = IUTKSan::prove(pp, &sk_s, &pk_s, &san_pks, &m, &sig, None).unwrap();
         let duration = start.elapsed();
-        prf_time.push(duration.as_millis() as f64);
+        prf_time.push(duration.as_secs_f64() * 1000.0);
 
         //Judge
         let start = Instant::now();
-        let d = IUTKSan::judge(&mut pp, &pk_s, &san_pks, &m, &sig, &pr, None).unwrap();
+        let d = IUTKSan::judge(pp, &pk_s, &san_pks, &m, &sig, &pr, None).unwrap();
         let duration = start.elapsed();
-        jdg_time.push(duration.as_millis() as f64);
+        jdg_time.push(duration.as_secs_f64() * 1000.0);
         assert!(d == 'Z', "Judge failed");
     }
-    return (average(sig_time), 
-            average(san_time), 
-            average(ver_time), 
-            average(prf_time), 
-            average(jdg_time));
+    for t in [&mut sig_time, &mut san_time, &mut ver_time, &mut prf_time, &mut jdg_time] {
+        t.drain(..warm_up);
+    }
+    (sig_time, san_time, ver_time, prf_time, jdg_time)
 }
 
-fn measure_ksan_fsv_efficiency(n: usize, k: usize, num_exec: usize, mut pp: &mut FSVPublicParams, num_adm: usize) -> (u64, u64, u64, u64) {
+fn measure_ksan_fsv_efficiency(n: usize, k: usize, num_exec: usize, warm_up: usize, mut pp: &mut FSVPublicParams, num_adm: usize) -> (Vec<f64>, Vec<f64>, Vec<f64>, Vec<f64>) {
     //Setup
     let (sk_s, pk_s) = FSVKSan::kgen_s(&mut pp).unwrap();
     let mut m: Vec<String> = Vec::with_capacity(n);
@@ -650,63 +1157,50 @@ fn measure_ksan_fsv_efficiency(n: usize, k: usize, num_exec: usize, mut pp: &mut
     let mut ver_time: Vec<f64> = Vec::new();
     let mut jdg_time: Vec<f64> = Vec::new();
 
-    for _x in 0..num_exec {
+    //The first warm_up runs are not measured
+    for _x in 0..warm_up + num_exec {
         //Sign
         let start = Instant::now();
         let mut sig = FSVKSan::sign(&mut pp, &sk_s, &pk_s, &san_pks, &m, &adm).unwrap();
         let duration = start.elapsed();
-        sig_time.push(duration.as_millis() as f64);
+        sig_time.push(duration.as_secs_f64() * 1000.0);
 
-        //Sanitize\
-        for j in 0..num_adm {
+        //Sanitize: verify needs every admissible block to be sanitized, the blocks 1..num_adm are sanitized
+        //first without being measured and the measured sanitization modifies block 0 only, as for IUT.
+        for j in 1..num_adm {
             let ns = generate_random_string(10);
             let modif = vec![FSVMod { i: j, m: ns.clone() }];
-            let start = Instant::now();
             sig = FSVKSan::sanitize(&mut pp, &secrets[0], &pk_s, &san_pks[0], 
                 &san_pks, &m, &modif, &sig).unwrap();
-            let duration = start.elapsed();
-            if j == 0 {
-                san_time.push(duration.as_millis() as f64);
-            }
-            m[j] = ns.clone();
+            m[j] = ns;
         }
+        let ns = generate_random_string(10);
+        let modif = vec![FSVMod { i: 0, m: ns.clone() }];
+        let start = Instant::now();
+        sig = FSVKSan::sanitize(pp, &secrets[0], &pk_s, &san_pks[0], 
+            &san_pks, &m, &modif, &sig).unwrap();
+        let duration = start.elapsed();
+        san_time.push(duration.as_secs_f64() * 1000.0);
+        m[0] = ns;
 
         //Verify
         let start = Instant::now();
         let b = FSVKSan::verify(&mut pp, &pk_s, &san_pks, &m, &sig).unwrap();
         let duration = start.elapsed();
-        ver_time.push(duration.as_millis() as f64);
+        ver_time.push(duration.as_secs_f64() * 1000.0);
         assert!(b, "Verify failed");
 
         //Judge
         let start = Instant::now();
         let d = FSVKSan::judge(&mut pp, &pk_s, &san_pks, &m, &sig, None, None).unwrap();
         let duration = start.elapsed();
-        jdg_time.push(duration.as_millis() as f64);
+        jdg_time.push(duration.as_secs_f64() * 1000.0);
         assert!(d == 'Z', "Judge failed");
     }
-    return (average(sig_time), 
-            average(san_time), 
-            average(ver_time), 
-            average(jdg_time));
-}
-
-fn _median(mut numbers: Vec<f64>) -> u64 {
-    let len = numbers.len();
-    
-    numbers.sort_by(|a, b| a.partial_cmp(b).unwrap());
-
-    let mid = len / 2;
-    let mut res: u64;
-    if len % 2 == 0 {
-        res = ((numbers[mid - 1] + numbers[mid]) / 2.0).round() as u64;
-    } else {
-        res = numbers[mid].round() as u64;
-    }
-    if res == 0 {
-        res = 1;
+    for t in [&mut sig_time, &mut san_time, &mut ver_time, &mut jdg_time] {
+        t.drain(..warm_up);
     }
-    return res;
+    (sig_time, san_time, ver_time, jdg_time)
 }
 
 fn average(numbers: Vec<f64>) -> u64 {
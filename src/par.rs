@@ -0,0 +1,30 @@
+//Helpers for the independent per-block and per-sanitizer loops. With the `parallel` feature the
+//iterations are spread over a rayon thread pool, otherwise they run in order. In both cases the results
+//are returned in index order, so the outputs are the same.
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+#[cfg(feature = "parallel")]
+pub fn map_range<T, F>(n: usize, f: F) -> Vec<T>
+where T: Send, F: Fn(usize) -> T + Sync + Send {
+    return (0..n).into_par_iter().map(f).collect();
+}
+
+#[cfg(not(feature = "parallel"))]
+pub fn map_range<T, F>(n: usize, f: F) -> Vec<T>
+where T: Send, F: Fn(usize) -> T + Sync + Send {
+    (0..n).map(f).collect()
+}
+
+#[cfg(feature = "parallel")]
+pub fn all_range<F>(n: usize, f: F) -> bool
+where F: Fn(usize) -> bool + Sync + Send {
+    return (0..n).into_par_iter().all(f);
+}
+
+#[cfg(not(feature = "parallel"))]
+pub fn all_range<F>(n: usize, f: F) -> bool
+where F: Fn(usize) -> bool + Sync + Send {
+    (0..n).all(f)
+}
\ No newline at end of file
@@ -0,0 +1,56 @@
+use serde::Serialize;
+
+//Summary of a set of timing samples. The standard deviation is the sample one and the percentiles are
+//interpolated linearly between the closest ranks.
+#[derive(Clone, Debug, Serialize)]
+pub struct Stats {
+    pub count: usize,
+    pub mean: f64,
+    pub median: f64,
+    pub std: f64,
+    pub min: f64,
+    pub max: f64,
+    pub p5: f64,
+    pub p25: f64,
+    pub p75: f64,
+    pub p95: f64,
+    pub p99: f64
+}
+
+impl Stats {
+    pub fn new(samples: &[f64]) -> Self {
+        let mut sorted = samples.to_owned();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let count = sorted.len();
+        let mean = if count == 0 { 0.0 } else { sorted.iter().sum::<f64>() / count as f64 };
+        let var = if count < 2 {
+            0.0
+        } else {
+            sorted.iter().map(|x| (x - mean) * (x - mean)).sum::<f64>() / (count - 1) as f64
+        };
+        Stats {
+            count,
+            mean,
+            median: percentile(&sorted, 50.0),
+            std: var.sqrt(),
+            min: sorted.first().copied().unwrap_or(0.0),
+            max: sorted.last().copied().unwrap_or(0.0),
+            p5: percentile(&sorted, 5.0),
+            p25: percentile(&sorted, 25.0),
+            p75: percentile(&sorted, 75.0),
+            p95: percentile(&sorted, 95.0),
+            p99: percentile(&sorted, 99.0)
+        }
+    }
+}
+
+//p-th percentile of sorted samples
+pub fn percentile(sorted: &[f64], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = p / 100.0 * (sorted.len() - 1) as f64;
+    let lo = rank.floor() as usize;
+    let hi = rank.ceil() as usize;
+    sorted[lo] + (sorted[hi] - sorted[lo]) * (rank - lo as f64)
+}
\ No newline at end of file